@@ -0,0 +1,95 @@
+use std::error::Error;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::{c_char, c_int};
+
+/// Maximal length of an exception message stored in `CppExceptionInfo`
+/// (including the terminating null byte).
+const MESSAGE_BUFFER_SIZE: usize = 1024;
+
+/// Kind of a caught C++ exception.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CppExceptionKind {
+    /// An object derived from `std::exception` was thrown.
+    StdException,
+    /// An object of an unknown type was thrown.
+    Unknown,
+}
+
+/// C++ exception caught at the FFI boundary.
+///
+/// Wrappers of C++ functions return `Result<T, CppException>`
+/// if the crate was generated with exception catching enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CppException {
+    kind: CppExceptionKind,
+    message: Option<String>,
+}
+
+impl CppException {
+    /// Returns kind of the exception.
+    pub fn kind(&self) -> CppExceptionKind {
+        self.kind
+    }
+
+    /// Returns value of `what()` of the thrown `std::exception`.
+    /// Returns `None` if the thrown object was not a `std::exception`.
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_ref().map(|s| s.as_str())
+    }
+}
+
+impl fmt::Display for CppException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "C++ exception: {}", message),
+            None => write!(f, "unknown C++ exception"),
+        }
+    }
+}
+
+impl Error for CppException {}
+
+/// Storage for information about a caught C++ exception.
+///
+/// This type is used by the generated FFI wrappers and should not be
+/// used directly. It must have the same layout as `ritual::ExceptionInfo`
+/// type in the C++ wrapper library.
+#[doc(hidden)]
+#[repr(C)]
+pub struct CppExceptionInfo {
+    kind: c_int,
+    message: [c_char; MESSAGE_BUFFER_SIZE],
+}
+
+impl CppExceptionInfo {
+    /// Creates an object indicating that no exception was thrown.
+    pub fn new() -> Self {
+        CppExceptionInfo {
+            kind: 0,
+            message: [0; MESSAGE_BUFFER_SIZE],
+        }
+    }
+
+    /// Returns an error if an exception was caught.
+    pub fn to_result(&self) -> Result<(), CppException> {
+        let kind = match self.kind {
+            0 => return Ok(()),
+            1 => CppExceptionKind::StdException,
+            _ => CppExceptionKind::Unknown,
+        };
+        let message = if kind == CppExceptionKind::StdException {
+            let message = unsafe { CStr::from_ptr(self.message.as_ptr()) };
+            Some(message.to_string_lossy().into_owned())
+        } else {
+            None
+        };
+        Err(CppException { kind, message })
+    }
+}
+
+impl Default for CppExceptionInfo {
+    fn default() -> Self {
+        CppExceptionInfo::new()
+    }
+}
@@ -50,12 +50,19 @@
 //! or even `Ptr<DerivedClass>` (where `DerivedClass` inherits `SomeClass`). You can also
 //! pass a null pointer object (`NullPtr`) if you don't have a value
 //! (`Ptr::null()` is also an option but it can cause type inference issues).
+//!
+//! # Exceptions
+//!
+//! C++ exceptions must not propagate into Rust code. If a crate is generated
+//! with exception catching enabled, wrappers of functions that are not `noexcept`
+//! return `Result<T, CppException>`, and the exception is reported as an error.
 
 #![deny(missing_docs)]
 
 pub use crate::casts::{DynamicCast, StaticDowncast, StaticUpcast};
 pub use crate::convert::{CastFrom, CastInto};
 pub use crate::cpp_box::{CppBox, CppDeletable};
+pub use crate::exception::{CppException, CppExceptionInfo, CppExceptionKind};
 pub use crate::iterator::{cpp_iter, CppIterator, EndPtr};
 pub use crate::ptr::{NullPtr, Ptr};
 pub use crate::ref_::Ref;
@@ -65,6 +72,7 @@ mod casts;
 pub mod cmp;
 mod convert;
 mod cpp_box;
+mod exception;
 mod iterator;
 pub mod ops;
 mod ops_impls;
@@ -418,9 +418,14 @@ fn connection_to_bool_function() -> CppFunction {
         }),
         member: None,
         allows_variadic_arguments: false,
+        is_noexcept: false,
+        is_deleted: false,
+        deprecation: None,
+        has_c_linkage: false,
+        is_hidden_friend: false,
         arguments: vec![CppFunctionArgument {
             name: "connection".into(),
-            has_default_value: false,
+            default_value: None,
             argument_type: CppType::new_reference(
                 true,
                 CppType::Class(CppPath::from_good_str("QMetaObject::Connection")),
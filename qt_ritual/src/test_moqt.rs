@@ -127,6 +127,16 @@ fn test_moqt() {
             output_crates_version: "0.0.0".into(),
             cluster: None,
             trace: None,
+            external_steps: Vec::new(),
+            plan_report: None,
+            jobs: None,
+            query_cpp_path: None,
+            query_rust_path: None,
+            query_kind: None,
+            query_check_status: None,
+            query_output: None,
+            diff_base: None,
+            diff_output: None,
         },
         global_config(),
     )
@@ -0,0 +1,477 @@
+//! Implementation of the `diff` operation.
+//!
+//! The operation compares public Rust API of the current crate with API of another
+//! version of the crate (a database file or a published version of the crate).
+//! Rust items are matched by their C++ origin, i.e. the C++ item they were
+//! generated from, so a Rust item of the same kind that got a different path
+//! is reported as renamed instead of removed and added. Items without a C++ origin
+//! (e.g. modules) are only matched by their paths.
+
+use crate::database::{Database, DatabaseItemData, DbItem, ItemId};
+use crate::database_file;
+use crate::processor::ProcessorData;
+use crate::rust_code_generator::{rust_common_type_to_code, rust_type_to_code};
+use crate::rust_info::RustItem;
+use itertools::Itertools;
+use log::{debug, info};
+use ritual_common::errors::{bail, err_msg, Result};
+use semver::Version;
+use serde_derive::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Name of the operation.
+pub const DIFF_OPERATION: &str = "diff";
+
+/// Version of the crate to compare with.
+#[derive(Debug, Clone)]
+pub enum DiffBase {
+    /// Database file in any supported format
+    Path(PathBuf),
+    /// Published version of the crate on crates.io
+    Version(String),
+}
+
+/// Extensions of database files.
+const DATABASE_EXTENSIONS: &[&str] = &["db", "json", "bin"];
+
+impl DiffBase {
+    /// Parses a path to a database file or a crate version.
+    /// Text that looks like a path is never treated as a version,
+    /// so a mistyped path results in an error instead of a download.
+    pub fn parse(text: &str) -> Result<Self> {
+        let path = Path::new(text);
+        let is_path = path.components().count() > 1
+            || path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map_or(false, |extension| DATABASE_EXTENSIONS.contains(&extension))
+            || path.exists();
+        if is_path {
+            Ok(DiffBase::Path(path.to_path_buf()))
+        } else if Version::parse(text).is_ok() {
+            Ok(DiffBase::Version(text.to_string()))
+        } else {
+            bail!(
+                "invalid diff base (expected a database file or a crate version): {}",
+                text
+            );
+        }
+    }
+}
+
+/// Minimal version bump required by API changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionBump {
+    Patch,
+    Minor,
+    Major,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangedItem {
+    pub old: String,
+    pub new: String,
+}
+
+/// Changes of public Rust API between two versions of a crate.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ApiChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub renamed: Vec<ChangedItem>,
+    pub signature_changed: Vec<ChangedItem>,
+}
+
+impl ApiChanges {
+    pub fn is_breaking(&self) -> bool {
+        !self.removed.is_empty() || !self.renamed.is_empty() || !self.signature_changed.is_empty()
+    }
+
+    /// Returns the version bump required by the changes according to semver
+    /// (as interpreted by Cargo, so breaking changes of `0.x` versions require a minor bump).
+    pub fn required_bump(&self, old_version: &Version) -> VersionBump {
+        let is_initial = old_version.major == 0;
+        if self.is_breaking() {
+            if is_initial {
+                VersionBump::Minor
+            } else {
+                VersionBump::Major
+            }
+        } else if !self.added.is_empty() && !is_initial {
+            VersionBump::Minor
+        } else {
+            VersionBump::Patch
+        }
+    }
+}
+
+/// Result of the `diff` operation for a crate.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiDiff {
+    pub crate_name: String,
+    pub old_version: String,
+    pub changes: ApiChanges,
+    pub required_bump: VersionBump,
+    /// The lowest version compatible with `required_bump`
+    pub suggested_version: String,
+}
+
+impl ApiDiff {
+    fn log_summary(&self) {
+        info!(
+            "API changes of {} since v{}: {} added, {} removed, {} renamed, {} signature changed",
+            self.crate_name,
+            self.old_version,
+            self.changes.added.len(),
+            self.changes.removed.len(),
+            self.changes.renamed.len(),
+            self.changes.signature_changed.len()
+        );
+        for item in &self.changes.added {
+            debug!("    added: {}", item);
+        }
+        for item in &self.changes.removed {
+            debug!("    removed: {}", item);
+        }
+        for item in &self.changes.renamed {
+            debug!("    renamed: {} -> {}", item.old, item.new);
+        }
+        for item in &self.changes.signature_changed {
+            debug!("    signature changed: {} -> {}", item.old, item.new);
+        }
+        info!(
+            "Required version bump: {:?} (suggested version: {})",
+            self.required_bump, self.suggested_version
+        );
+    }
+}
+
+/// A public Rust item prepared for comparison.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ApiItem {
+    /// Text of the C++ item the Rust item was generated from
+    origin: String,
+    kind: &'static str,
+    /// Path of the item (or its description if it has no path)
+    identity: String,
+    signature: String,
+}
+
+fn is_public(item: &RustItem) -> bool {
+    match item {
+        RustItem::Module(data) => data.is_public,
+        RustItem::Struct(data) => data.is_public,
+        RustItem::Function(data) => data.is_public,
+        _ => true,
+    }
+}
+
+fn kind(item: &RustItem) -> &'static str {
+    match item {
+        RustItem::Module(_) => "module",
+        RustItem::Struct(_) => "struct",
+        RustItem::EnumValue(_) => "enum value",
+        RustItem::TraitImpl(_) => "trait impl",
+        RustItem::ExtraImpl(_) => "extra impl",
+        RustItem::Function(_) => "function",
+        RustItem::Reexport(_) => "reexport",
+        RustItem::Constant(_) => "constant",
+        RustItem::TypeAlias(_) => "type alias",
+        RustItem::StructField(_) => "struct field",
+    }
+}
+
+fn signature(item: &RustItem) -> String {
+    match item {
+        RustItem::Function(data) => {
+            let arguments = data
+                .arguments
+                .iter()
+                .map(|arg| {
+                    format!(
+                        "{}: {}",
+                        arg.name,
+                        rust_type_to_code(arg.argument_type.api_type(), None)
+                    )
+                })
+                .join(", ");
+            format!(
+                "{}fn {}({}) -> {}",
+                if data.is_unsafe { "unsafe " } else { "" },
+                data.path.full_name(None),
+                arguments,
+                rust_type_to_code(data.return_type.api_type(), None)
+            )
+        }
+        RustItem::Struct(data) => {
+            format!("struct {} ({:?})", data.path.full_name(None), data.kind)
+        }
+        RustItem::EnumValue(data) => format!("{} = {}", data.path.full_name(None), data.value),
+        RustItem::Constant(data) => format!(
+            "const {}: {} = {}",
+            data.path.full_name(None),
+            rust_type_to_code(&data.value_type, None),
+            data.value
+        ),
+        RustItem::TraitImpl(data) => {
+            let associated_types = data.associated_types.iter().map(|associated_type| {
+                format!(
+                    "type {} = {};",
+                    associated_type.name,
+                    rust_type_to_code(&associated_type.value, None)
+                )
+            });
+            let functions = data
+                .functions
+                .iter()
+                .map(|function| signature(&RustItem::Function(function.clone())));
+            format!(
+                "impl {} for {} {{ {} }}",
+                rust_common_type_to_code(&data.trait_type, None),
+                rust_type_to_code(&data.target_type, None),
+                associated_types.chain(functions).join(" ")
+            )
+        }
+        _ => item.short_text(),
+    }
+}
+
+/// Returns text of the nearest C++ item among the item's sources.
+/// FFI items are skipped because their names are generated and may change
+/// between versions. Sources from other crates are represented by the crate name
+/// because item IDs are not preserved between versions.
+fn origin(
+    items: &HashMap<ItemId, DbItem<&DatabaseItemData>>,
+    item: &DbItem<&DatabaseItemData>,
+) -> String {
+    let mut source_id = item.source_id.as_ref();
+    while let Some(id) = source_id {
+        match items.get(id) {
+            Some(source) => {
+                if source.item.is_cpp_item() {
+                    return source.item.short_text();
+                }
+                source_id = source.source_id.as_ref();
+            }
+            None => return format!("<{}>", id.crate_name()),
+        }
+    }
+    String::new()
+}
+
+fn api_items(db: &Database) -> Vec<ApiItem> {
+    let items: HashMap<_, _> = db.items().map(|item| (item.id.clone(), item)).collect();
+    let mut result = Vec::new();
+    for item in db.items() {
+        if let Some(rust_item) = item.item.as_rust_item() {
+            if !is_public(rust_item) {
+                continue;
+            }
+            result.push(ApiItem {
+                origin: origin(&items, &item),
+                kind: kind(rust_item),
+                identity: rust_item
+                    .path()
+                    .map_or_else(|| rust_item.short_text(), |path| path.full_name(None)),
+                signature: signature(rust_item),
+            });
+        }
+    }
+    result
+}
+
+fn compare(old: Vec<ApiItem>, new: Vec<ApiItem>) -> ApiChanges {
+    let group = |items: Vec<ApiItem>| {
+        let mut map = BTreeMap::<_, Vec<_>>::new();
+        for item in items {
+            map.entry(item.origin.clone()).or_default().push(item);
+        }
+        for items in map.values_mut() {
+            items.sort_by(|a, b| a.identity.cmp(&b.identity));
+        }
+        map
+    };
+    let old = group(old);
+    let mut new = group(new);
+    let mut changes = ApiChanges::default();
+
+    for (origin, mut old_items) in old {
+        let mut new_items = new.remove(&origin).unwrap_or_default();
+        // items with the same path
+        old_items.retain(|old_item| {
+            let index = new_items
+                .iter()
+                .position(|new_item| new_item.identity == old_item.identity);
+            match index {
+                Some(index) => {
+                    let new_item = new_items.remove(index);
+                    if new_item.signature != old_item.signature {
+                        changes.signature_changed.push(ChangedItem {
+                            old: old_item.signature.clone(),
+                            new: new_item.signature,
+                        });
+                    }
+                    false
+                }
+                None => true,
+            }
+        });
+        // remaining items with the same origin and kind
+        for old_item in old_items {
+            let index = if origin.is_empty() {
+                // items without an origin can't be identified as renamed
+                None
+            } else {
+                new_items
+                    .iter()
+                    .position(|new_item| new_item.kind == old_item.kind)
+            };
+            match index {
+                Some(index) => changes.renamed.push(ChangedItem {
+                    old: old_item.identity,
+                    new: new_items.remove(index).identity,
+                }),
+                None => changes.removed.push(old_item.signature),
+            }
+        }
+        changes
+            .added
+            .extend(new_items.into_iter().map(|new_item| new_item.signature));
+    }
+    for (_, new_items) in new {
+        changes
+            .added
+            .extend(new_items.into_iter().map(|item| item.signature));
+    }
+    changes.added.sort();
+    changes.removed.sort();
+    changes
+}
+
+/// Compares API of the current crate with `base` version.
+pub fn run(data: &mut ProcessorData<'_>, base: &DiffBase) -> Result<ApiDiff> {
+    let crate_name = data.db.crate_name().to_string();
+    let path = match base {
+        DiffBase::Path(path) => path.clone(),
+        DiffBase::Version(version) => data.workspace.external_db_path(&crate_name, version)?,
+    };
+    info!("Loading database for comparison: {}", path.display());
+    let old_db = database_file::load(&path)?;
+
+    let changes = compare(api_items(&old_db), api_items(data.db.data()));
+    let old_version = Version::parse(old_db.crate_version())
+        .map_err(|err| err_msg(format!("invalid version of base crate: {}", err)))?;
+    let required_bump = changes.required_bump(&old_version);
+    let mut suggested_version = old_version.clone();
+    match required_bump {
+        VersionBump::Major => suggested_version.increment_major(),
+        VersionBump::Minor => suggested_version.increment_minor(),
+        VersionBump::Patch => suggested_version.increment_patch(),
+    }
+
+    let diff = ApiDiff {
+        crate_name,
+        old_version: old_version.to_string(),
+        changes,
+        required_bump,
+        suggested_version: suggested_version.to_string(),
+    };
+    diff.log_summary();
+    Ok(diff)
+}
+
+#[test]
+fn compare_api_items() {
+    let item = |origin: &str, identity: &str, signature: &str| ApiItem {
+        origin: origin.to_string(),
+        kind: if signature.starts_with("fn") {
+            "function"
+        } else if signature.starts_with("mod") {
+            "module"
+        } else {
+            "struct"
+        },
+        identity: identity.to_string(),
+        signature: signature.to_string(),
+    };
+    let old = vec![
+        item("A", "a", "fn a()"),
+        item("B", "b", "fn b()"),
+        item("C", "c", "fn c()"),
+        item("D", "d", "fn d()"),
+        item("F", "f", "fn f()"),
+        item("", "m1", "mod m1"),
+    ];
+    let new = vec![
+        item("A", "a", "fn a()"),
+        item("B", "b", "fn b(x: i32)"),
+        item("C", "c2", "fn c2()"),
+        item("E", "e", "fn e()"),
+        item("F", "F", "struct F"),
+        item("", "m2", "mod m2"),
+    ];
+    let changes = compare(old, new);
+    assert_eq!(
+        changes,
+        ApiChanges {
+            // items of different kinds or without an origin are not paired as renamed
+            added: vec![
+                "fn e()".to_string(),
+                "mod m2".to_string(),
+                "struct F".to_string(),
+            ],
+            removed: vec![
+                "fn d()".to_string(),
+                "fn f()".to_string(),
+                "mod m1".to_string(),
+            ],
+            renamed: vec![ChangedItem {
+                old: "c".to_string(),
+                new: "c2".to_string(),
+            }],
+            signature_changed: vec![ChangedItem {
+                old: "fn b()".to_string(),
+                new: "fn b(x: i32)".to_string(),
+            }],
+        }
+    );
+    assert!(changes.is_breaking());
+    assert_eq!(
+        changes.required_bump(&Version::parse("0.4.1").unwrap()),
+        VersionBump::Minor
+    );
+    assert_eq!(
+        changes.required_bump(&Version::parse("1.2.0").unwrap()),
+        VersionBump::Major
+    );
+
+    let additions = ApiChanges {
+        added: vec!["fn e()".to_string()],
+        ..ApiChanges::default()
+    };
+    assert_eq!(
+        additions.required_bump(&Version::parse("0.4.1").unwrap()),
+        VersionBump::Patch
+    );
+    assert_eq!(
+        additions.required_bump(&Version::parse("1.2.0").unwrap()),
+        VersionBump::Minor
+    );
+}
+
+#[test]
+fn parse_diff_base() {
+    match DiffBase::parse("0.4.1").unwrap() {
+        DiffBase::Version(version) => assert_eq!(version, "0.4.1"),
+        base => panic!("unexpected base: {:?}", base),
+    }
+    for &text in &["missing.db", "db/missing", "missing.json"] {
+        match DiffBase::parse(text).unwrap() {
+            DiffBase::Path(path) => assert_eq!(path, PathBuf::from(text)),
+            base => panic!("unexpected base: {:?}", base),
+        }
+    }
+    assert!(DiffBase::parse("missing").is_err());
+}
@@ -0,0 +1,33 @@
+//! Generic generator that reads configuration of crates from a project file.
+//!
+//! See `ritual::project_file` for the format of the file.
+
+use ritual::cli;
+use ritual::project_file::{ProjectFile, PROJECT_FILE_NAME};
+use ritual_common::errors::{FancyUnwrap, Result};
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+/// Generates Rust crates for C++ libraries described in a project file.
+/// See [ritual](https://github.com/rust-qt/ritual) for more details.
+struct Options {
+    #[structopt(
+        long = "project",
+        parse(from_os_str),
+        raw(default_value = "PROJECT_FILE_NAME")
+    )]
+    /// Path to the project file
+    project: PathBuf,
+    #[structopt(flatten)]
+    cli_options: cli::Options,
+}
+
+fn run(options: Options) -> Result<()> {
+    let project = ProjectFile::load(&options.project)?;
+    cli::run(options.cli_options, project.into_global_config())
+}
+
+fn main() {
+    run(Options::from_args()).fancy_unwrap();
+}
@@ -2,7 +2,8 @@ use ritual::cpp_data::{
     CppItem, CppPath, CppPathItem, CppTypeDeclaration, CppTypeDeclarationKind, CppVisibility,
 };
 use ritual::cpp_function::{
-    CppFunction, CppFunctionArgument, CppFunctionKind, CppFunctionMemberData,
+    CppArgumentDefaultValue, CppFunction, CppFunctionArgument, CppFunctionKind,
+    CppFunctionMemberData,
 };
 use ritual::cpp_type::{
     CppBuiltInNumericType, CppSpecificNumericType, CppSpecificNumericTypeKind,
@@ -53,20 +54,28 @@ fn add_vector_from_pointers_functions(data: &mut ProcessorData<'_>) -> Result<()
                 CppFunctionArgument {
                     name: "first".into(),
                     argument_type: CppType::new_pointer(true, t.clone()),
-                    has_default_value: false,
+                    default_value: None,
                 },
                 CppFunctionArgument {
                     name: "last".into(),
                     argument_type: CppType::new_pointer(true, t.clone()),
-                    has_default_value: false,
+                    default_value: None,
                 },
                 CppFunctionArgument {
                     name: "alloc".into(),
                     argument_type: allocator_type,
-                    has_default_value: true,
+                    default_value: Some(CppArgumentDefaultValue {
+                        text: "allocator_type()".into(),
+                        value: None,
+                    }),
                 },
             ],
             allows_variadic_arguments: false,
+            is_noexcept: false,
+            is_deleted: false,
+            deprecation: None,
+            has_c_linkage: false,
+            is_hidden_friend: false,
             cast: None,
             declaration_code: None,
         }),
@@ -103,15 +112,20 @@ fn add_vector_from_pointers_functions(data: &mut ProcessorData<'_>) -> Result<()
                 CppFunctionArgument {
                     name: "first".into(),
                     argument_type: CppType::new_pointer(true, t.clone()),
-                    has_default_value: false,
+                    default_value: None,
                 },
                 CppFunctionArgument {
                     name: "last".into(),
                     argument_type: CppType::new_pointer(true, t),
-                    has_default_value: false,
+                    default_value: None,
                 },
             ],
             allows_variadic_arguments: false,
+            is_noexcept: false,
+            is_deleted: false,
+            deprecation: None,
+            has_c_linkage: false,
+            is_hidden_friend: false,
             cast: None,
             declaration_code: None,
         }),
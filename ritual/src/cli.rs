@@ -3,17 +3,26 @@
 //! See [README](https://github.com/rust-qt/ritual)
 //! for more information.
 
-use crate::config::{CrateProperties, GlobalConfig};
+use crate::api_diff::{self, DiffBase, DIFF_OPERATION};
+use crate::config::{Config, CrateProperties, GlobalConfig};
+use crate::crate_scheduler::{self, CrateJob, CrateStatus};
 use crate::database::ItemId;
+use crate::external_step::ExternalStep;
+use crate::plan::{self, PLAN_OPERATION};
 use crate::processor;
+use crate::query::{Query, QUERY_OPERATION};
 use crate::workspace::Workspace;
 use flexi_logger::{Duplicate, LevelFilter, LogSpecification, Logger};
 use itertools::Itertools;
-use log::{error, info};
-use ritual_common::errors::{bail, err_msg, Result};
-use ritual_common::file_utils::{canonicalize, create_dir, load_json, path_to_str};
+use log::{error, info, Level, Record};
+use regex::Regex;
+use ritual_common::errors::{bail, err_msg, print_trace, Result};
+use ritual_common::file_utils::{canonicalize, create_dir, load_json, path_to_str, save_json};
 use ritual_common::target::current_target;
+use std::io::{self, Write};
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -30,7 +39,8 @@ pub struct Options {
     /// Crates to process (e.g. `qt_core`)
     pub crates: Vec<String>,
     #[structopt(short = "o", long = "operations", required = true)]
-    /// Operations to perform
+    /// Operations to perform (`main` runs outdated steps of the main procedure,
+    /// `plan` only reports them and can't be combined with other operations)
     pub operations: Vec<String>,
     #[structopt(short = "v", long = "version")]
     /// Version of the output crates.
@@ -41,6 +51,52 @@ pub struct Options {
     #[structopt(long = "trace")]
     /// ID of item to trace
     pub trace: Option<String>,
+    #[structopt(long = "external-step")]
+    /// External processing step (`name=program args...`) that can be requested in `--operations`
+    pub external_steps: Vec<String>,
+    #[structopt(long = "plan-report", parse(from_os_str))]
+    /// Write JSON report of the `plan` operation to this file
+    pub plan_report: Option<PathBuf>,
+    #[structopt(short = "j", long = "jobs")]
+    /// Maximum number of crates processed concurrently (defaults to 1)
+    pub jobs: Option<usize>,
+    #[structopt(long = "query-cpp-path")]
+    /// Regex of C++ paths of items found by the `query` operation
+    pub query_cpp_path: Option<String>,
+    #[structopt(long = "query-rust-path")]
+    /// Regex of Rust paths of items found by the `query` operation
+    pub query_rust_path: Option<String>,
+    #[structopt(long = "query-kind")]
+    /// Kind of items found by the `query` operation (e.g. `cpp_item`, `ffi_item`, `rust_item`)
+    pub query_kind: Option<String>,
+    #[structopt(long = "query-check-status")]
+    /// Status of C++ checks of items found by the `query` operation
+    /// (`passed`, `failed` or `unchecked`)
+    pub query_check_status: Option<String>,
+    #[structopt(long = "query-output", parse(from_os_str))]
+    /// Write JSON results of the `query` operation to this file
+    pub query_output: Option<PathBuf>,
+    #[structopt(long = "diff-base")]
+    /// Database file or published crate version compared by the `diff` operation
+    pub diff_base: Option<String>,
+    #[structopt(long = "diff-output", parse(from_os_str))]
+    /// Write JSON results of the `diff` operation to this file
+    pub diff_output: Option<PathBuf>,
+}
+
+/// Default log format with the name of the crate processed by the current thread
+/// added to the message.
+fn log_format(w: &mut dyn Write, record: &Record<'_>) -> io::Result<()> {
+    write!(
+        w,
+        "{} [{}] ",
+        record.level(),
+        record.module_path().unwrap_or("<unnamed>")
+    )?;
+    match thread::current().name() {
+        Some(name) if name != "main" => write!(w, "{}: {}", name, record.args()),
+        _ => write!(w, "{}", record.args()),
+    }
 }
 
 pub fn run_from_args(config: GlobalConfig) -> Result<()> {
@@ -53,7 +109,7 @@ pub fn run(options: Options, mut config: GlobalConfig) -> Result<()> {
     }
     let workspace_path = canonicalize(options.workspace)?;
 
-    let mut workspace = Workspace::new(workspace_path.clone())?;
+    let workspace = Workspace::new(workspace_path.clone())?;
 
     Logger::with(LogSpecification::default(LevelFilter::Trace).build())
         .log_to_file()
@@ -61,6 +117,7 @@ pub fn run(options: Options, mut config: GlobalConfig) -> Result<()> {
         .suppress_timestamp()
         .append()
         .print_message()
+        .format(log_format)
         .duplicate_to_stderr(Duplicate::Info)
         .start()
         .unwrap_or_else(|e| panic!("Logger initialization failed: {}", e));
@@ -91,6 +148,15 @@ pub fn run(options: Options, mut config: GlobalConfig) -> Result<()> {
         error!("No action requested. Run \"qt_generator --help\".");
         return Ok(());
     }
+    let is_plan = operations
+        .iter()
+        .any(|operation| operation == PLAN_OPERATION);
+    if is_plan && operations.len() > 1 {
+        bail!(
+            "{} operation can't be combined with other operations",
+            PLAN_OPERATION
+        );
+    }
 
     let trace_item_id = if let Some(text) = options.trace {
         let mut parts = text.split('#');
@@ -106,6 +172,37 @@ pub fn run(options: Options, mut config: GlobalConfig) -> Result<()> {
         None
     };
 
+    let query = Query {
+        cpp_path: options
+            .query_cpp_path
+            .as_ref()
+            .map(|text| Regex::new(text))
+            .transpose()?,
+        rust_path: options
+            .query_rust_path
+            .as_ref()
+            .map(|text| Regex::new(text))
+            .transpose()?,
+        kind: options
+            .query_kind
+            .as_ref()
+            .map(|text| text.parse())
+            .transpose()?,
+        check_status: options
+            .query_check_status
+            .as_ref()
+            .map(|text| text.parse())
+            .transpose()?,
+    };
+    let query_matches = Arc::new(Mutex::new(Vec::new()));
+    let diff_base = options
+        .diff_base
+        .as_ref()
+        .map(|text| DiffBase::parse(text))
+        .transpose()?;
+    let api_diffs = Arc::new(Mutex::new(Vec::new()));
+
+    let mut jobs = Vec::new();
     for crate_name in &final_crates {
         let create_config = config
             .create_config_hook()
@@ -124,8 +221,110 @@ pub fn run(options: Options, mut config: GlobalConfig) -> Result<()> {
             config.set_write_dependencies_local_paths(local_paths);
         }
 
+        for spec in &options.external_steps {
+            config
+                .processing_steps_mut()
+                .add_external_custom(ExternalStep::from_spec(spec)?)?;
+        }
+
+        let query = query.clone();
+        let query_matches = Arc::clone(&query_matches);
+        config
+            .processing_steps_mut()
+            .add_custom(QUERY_OPERATION, move |data| {
+                let matches = query.run(data)?;
+                query_matches.lock().unwrap().extend(matches);
+                Ok(())
+            })?;
+
+        let diff_base = diff_base.clone();
+        let api_diffs = Arc::clone(&api_diffs);
+        config
+            .processing_steps_mut()
+            .add_custom(DIFF_OPERATION, move |data| {
+                let base = diff_base
+                    .as_ref()
+                    .ok_or_else(|| err_msg("--diff-base is required for diff operation"))?;
+                let diff = api_diff::run(data, base)?;
+                api_diffs.lock().unwrap().push(diff);
+                Ok(())
+            })?;
+
         was_any_action = true;
-        processor::process(&mut workspace, &config, &operations, trace_item_id.as_ref())?;
+        jobs.push(CrateJob {
+            name: crate_name.clone(),
+            dependencies: config
+                .crate_properties()
+                .dependencies()
+                .iter()
+                .map(|dependency| dependency.name().to_string())
+                .collect(),
+            data: config,
+        });
+    }
+
+    let max_workers = options.jobs.unwrap_or(1);
+    let statuses = crate_scheduler::run(jobs, max_workers, move |config: Config| {
+        let mut workspace = workspace.clone();
+        if is_plan {
+            plan::run(&mut workspace, &config).map(Some)
+        } else {
+            processor::process(&mut workspace, &config, &operations, trace_item_id.as_ref())?;
+            Ok(None)
+        }
+    })?;
+
+    let mut plan_reports = Vec::new();
+    let mut failed_crates = Vec::new();
+    if statuses.len() > 1 {
+        info!("Summary:");
+    }
+    for (crate_name, status) in statuses {
+        match status {
+            CrateStatus::Finished {
+                result: Ok(plan_report),
+                duration,
+            } => {
+                info!(
+                    "{}: finished in {:.1} s",
+                    crate_name,
+                    duration.as_secs_f64()
+                );
+                plan_reports.extend(plan_report);
+            }
+            CrateStatus::Finished {
+                result: Err(err),
+                duration,
+            } => {
+                error!("{}: failed in {:.1} s", crate_name, duration.as_secs_f64());
+                print_trace(&err, Some(Level::Error));
+                failed_crates.push(crate_name);
+            }
+            CrateStatus::Skipped { failed_dependency } => {
+                error!(
+                    "{}: skipped because {} failed",
+                    crate_name, failed_dependency
+                );
+                failed_crates.push(crate_name);
+            }
+        }
+    }
+
+    if let Some(path) = &options.plan_report {
+        save_json(path, &plan_reports, None)?;
+    }
+    if let Some(path) = &options.query_output {
+        let mut query_matches = query_matches.lock().unwrap();
+        query_matches.sort_by(|a, b| a.id.cmp(&b.id));
+        save_json(path, &*query_matches, None)?;
+    }
+    if let Some(path) = &options.diff_output {
+        let mut api_diffs = api_diffs.lock().unwrap();
+        api_diffs.sort_by(|a, b| a.crate_name.cmp(&b.crate_name));
+        save_json(path, &*api_diffs, None)?;
+    }
+    if !failed_crates.is_empty() {
+        bail!("failed to process crates: {}", failed_crates.join(", "));
     }
 
     if was_any_action {
@@ -7,7 +7,7 @@ use crate::processor::{ProcessingSteps, ProcessorData};
 use crate::rust_info::{NameType, RustItem, RustPathScope};
 use crate::rust_type::RustPath;
 use ritual_common::cpp_build_config::{CppBuildConfig, CppBuildPaths};
-use ritual_common::errors::{bail, Result};
+use ritual_common::errors::{bail, format_err, Result, ResultExt};
 use ritual_common::target::Target;
 use ritual_common::toml;
 use serde_derive::{Deserialize, Serialize};
@@ -170,13 +170,71 @@ impl CrateProperties {
     }
 }
 
-pub type RustPathScopeHook = dyn Fn(&CppPath) -> Result<Option<RustPathScope>> + 'static;
+pub type MovableTypesHook = dyn Fn(&CppPath) -> Result<MovableTypesHookOutput> + Send + 'static;
+pub type CppParserPathHook = dyn Fn(&CppPath) -> Result<bool> + Send + 'static;
+pub type RustPathScopeHook = dyn Fn(&CppPath) -> Result<Option<RustPathScope>> + Send + 'static;
 pub type RustPathHook =
-    dyn Fn(&CppPath, NameType<'_>, &ProcessorData<'_>) -> Result<Option<RustPath>> + 'static;
-pub type RustItemHook = dyn Fn(&mut RustItem, &ProcessorData<'_>) -> Result<()> + 'static;
+    dyn Fn(&CppPath, NameType<'_>, &ProcessorData<'_>) -> Result<Option<RustPath>> + Send + 'static;
+pub type RustItemHook = dyn Fn(&mut RustItem, &ProcessorData<'_>) -> Result<()> + Send + 'static;
 pub type AfterCppParserHook =
-    dyn Fn(&mut ProcessorData<'_>, &CppParserOutput) -> Result<()> + 'static;
-pub type CppItemFilterHook = dyn Fn(&CppItem) -> Result<bool> + 'static;
+    dyn Fn(&mut ProcessorData<'_>, &CppParserOutput) -> Result<()> + Send + 'static;
+pub type CppItemFilterHook = dyn Fn(&CppItem) -> Result<bool> + Send + 'static;
+
+/// Name of the hook added by `Config::set_*_hook` methods.
+pub const DEFAULT_HOOK_NAME: &str = "default";
+
+/// An ordered list of named hooks of the same kind.
+pub struct Hooks<F: ?Sized> {
+    items: Vec<(String, Box<F>)>,
+}
+
+impl<F: ?Sized> Default for Hooks<F> {
+    fn default() -> Self {
+        Hooks { items: Vec::new() }
+    }
+}
+
+impl<F: ?Sized> Hooks<F> {
+    fn add(&mut self, name: &str, hook: Box<F>) -> Result<()> {
+        if self.contains(name) {
+            bail!("hook already exists: {}", name);
+        }
+        self.items.push((name.to_string(), hook));
+        Ok(())
+    }
+
+    fn remove(&mut self, name: &str) -> Result<()> {
+        let index = self
+            .items
+            .iter()
+            .position(|(item_name, _)| item_name == name)
+            .ok_or_else(|| format_err!("hook not found: {}", name))?;
+        self.items.remove(index);
+        Ok(())
+    }
+
+    /// Returns true if a hook with `name` was added.
+    pub fn contains(&self, name: &str) -> bool {
+        self.items.iter().any(|(item_name, _)| item_name == name)
+    }
+
+    /// Returns names of the hooks in the order of their execution.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.items.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Returns true if no hooks were added.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns names and functions of the hooks in the order of their execution.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &F)> {
+        self.items
+            .iter()
+            .map(|(name, hook)| (name.as_str(), &**hook))
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkerLibraryConfig {
@@ -212,16 +270,20 @@ pub struct Config {
     cpp_build_paths: CppBuildPaths,
     cpp_parser_arguments: Vec<String>,
     processing_steps: ProcessingSteps,
-    movable_types_hook: Option<Box<dyn Fn(&CppPath) -> Result<MovableTypesHookOutput>>>,
-    cpp_parser_path_hook: Option<Box<dyn Fn(&CppPath) -> Result<bool>>>,
-    rust_path_scope_hook: Option<Box<RustPathScopeHook>>,
-    rust_path_hook: Option<Box<RustPathHook>>,
-    rust_item_hook: Option<Box<RustItemHook>>,
+    movable_types_hooks: Hooks<MovableTypesHook>,
+    cpp_parser_path_hooks: Hooks<CppParserPathHook>,
+    rust_path_scope_hooks: Hooks<RustPathScopeHook>,
+    rust_path_hooks: Hooks<RustPathHook>,
+    rust_item_hooks: Hooks<RustItemHook>,
     after_cpp_parser_hooks: Vec<Box<AfterCppParserHook>>,
-    cpp_item_filter_hook: Option<Box<CppItemFilterHook>>,
+    cpp_item_filter_hooks: Hooks<CppItemFilterHook>,
     cluster_config: Option<ClusterConfig>,
     cpp_checker_tests: Vec<PreliminaryTest>,
     write_dependencies_local_paths: bool,
+    parse_macro_constants: bool,
+    parse_doc_comments: bool,
+    plain_c_mode: bool,
+    catch_exceptions: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -243,18 +305,22 @@ impl Config {
             include_directives: Default::default(),
             cpp_parser_arguments: Default::default(),
             cpp_build_config: Default::default(),
-            movable_types_hook: Default::default(),
+            movable_types_hooks: Default::default(),
             processing_steps: Default::default(),
             cpp_lib_version: Default::default(),
-            cpp_parser_path_hook: Default::default(),
-            rust_path_scope_hook: Default::default(),
-            rust_path_hook: Default::default(),
-            rust_item_hook: Default::default(),
+            cpp_parser_path_hooks: Default::default(),
+            rust_path_scope_hooks: Default::default(),
+            rust_path_hooks: Default::default(),
+            rust_item_hooks: Default::default(),
             after_cpp_parser_hooks: Default::default(),
-            cpp_item_filter_hook: Default::default(),
+            cpp_item_filter_hooks: Default::default(),
             cluster_config: None,
             cpp_checker_tests: Default::default(),
             write_dependencies_local_paths: true,
+            parse_macro_constants: false,
+            parse_doc_comments: false,
+            plain_c_mode: false,
+            catch_exceptions: false,
         }
     }
 
@@ -398,21 +464,47 @@ impl Config {
         &self.cpp_build_config
     }
 
+    /// Adds a hook that determines whether a C++ type can be moved,
+    /// i.e. stored by value in Rust. Hooks are called in the order
+    /// they were added, and the first result other than
+    /// `MovableTypesHookOutput::Unknown` is used.
+    /// Returns an error if a hook with the same `name` already exists.
+    pub fn add_movable_types_hook(
+        &mut self,
+        name: &str,
+        hook: impl Fn(&CppPath) -> Result<MovableTypesHookOutput> + Send + 'static,
+    ) -> Result<()> {
+        self.movable_types_hooks.add(name, Box::new(hook))
+    }
+
+    /// Same as `add_movable_types_hook` with `DEFAULT_HOOK_NAME`.
+    /// Panics if the default hook was already set.
     pub fn set_movable_types_hook(
         &mut self,
-        hook: impl Fn(&CppPath) -> Result<MovableTypesHookOutput> + 'static,
+        hook: impl Fn(&CppPath) -> Result<MovableTypesHookOutput> + Send + 'static,
     ) {
-        assert!(
-            self.movable_types_hook.is_none(),
-            "only one hook can be set"
-        );
-        self.movable_types_hook = Some(Box::new(hook));
+        self.add_movable_types_hook(DEFAULT_HOOK_NAME, hook)
+            .expect("only one default hook can be set");
     }
 
-    pub fn movable_types_hook(
-        &self,
-    ) -> Option<&(dyn Fn(&CppPath) -> Result<MovableTypesHookOutput> + 'static)> {
-        self.movable_types_hook.as_ref().map(|b| &**b)
+    /// Removes a hook added by `add_movable_types_hook`.
+    pub fn remove_movable_types_hook(&mut self, name: &str) -> Result<()> {
+        self.movable_types_hooks.remove(name)
+    }
+
+    pub fn movable_types_hooks(&self) -> &Hooks<MovableTypesHook> {
+        &self.movable_types_hooks
+    }
+
+    /// Evaluates all movable types hooks for `path`.
+    pub fn movable_type_from_hooks(&self, path: &CppPath) -> Result<MovableTypesHookOutput> {
+        for (name, hook) in self.movable_types_hooks.iter() {
+            let output = hook(path).with_context(|_| format!("hook failed: {}", name))?;
+            if output != MovableTypesHookOutput::Unknown {
+                return Ok(output);
+            }
+        }
+        Ok(MovableTypesHookOutput::Unknown)
     }
 
     /// Adds a C++ identifier that should be skipped
@@ -425,60 +517,185 @@ impl Config {
     /// will also be skipped.
     /// All class methods with names matching the blocked name
     /// will be skipped, regardless of class name.
-    pub fn set_cpp_parser_path_hook(&mut self, hook: impl Fn(&CppPath) -> Result<bool> + 'static) {
-        assert!(
-            self.cpp_parser_path_hook.is_none(),
-            "only one hook can be set"
-        );
-        self.cpp_parser_path_hook = Some(Box::new(hook));
+    ///
+    /// Hooks are called in the order they were added, and the path
+    /// is only accepted if all hooks return `true`.
+    /// Returns an error if a hook with the same `name` already exists.
+    pub fn add_cpp_parser_path_hook(
+        &mut self,
+        name: &str,
+        hook: impl Fn(&CppPath) -> Result<bool> + Send + 'static,
+    ) -> Result<()> {
+        self.cpp_parser_path_hooks.add(name, Box::new(hook))
     }
 
-    pub fn cpp_parser_path_hook(&self) -> Option<&(dyn Fn(&CppPath) -> Result<bool> + 'static)> {
-        self.cpp_parser_path_hook.as_ref().map(|b| &**b)
+    /// Same as `add_cpp_parser_path_hook` with `DEFAULT_HOOK_NAME`.
+    /// Panics if the default hook was already set.
+    pub fn set_cpp_parser_path_hook(
+        &mut self,
+        hook: impl Fn(&CppPath) -> Result<bool> + Send + 'static,
+    ) {
+        self.add_cpp_parser_path_hook(DEFAULT_HOOK_NAME, hook)
+            .expect("only one default hook can be set");
     }
 
+    /// Removes a hook added by `add_cpp_parser_path_hook`.
+    pub fn remove_cpp_parser_path_hook(&mut self, name: &str) -> Result<()> {
+        self.cpp_parser_path_hooks.remove(name)
+    }
+
+    pub fn cpp_parser_path_hooks(&self) -> &Hooks<CppParserPathHook> {
+        &self.cpp_parser_path_hooks
+    }
+
+    /// Returns true if `path` is accepted by all C++ parser path hooks.
+    pub fn check_cpp_parser_path(&self, path: &CppPath) -> Result<bool> {
+        for (name, hook) in self.cpp_parser_path_hooks.iter() {
+            if !hook(path).with_context(|_| format!("hook failed: {}", name))? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Adds a hook that determines Rust scope of items nested in a C++ path.
+    /// Hooks are called in the order they were added, and the first
+    /// returned value is used.
+    /// Returns an error if a hook with the same `name` already exists.
+    pub fn add_rust_path_scope_hook(
+        &mut self,
+        name: &str,
+        hook: impl Fn(&CppPath) -> Result<Option<RustPathScope>> + Send + 'static,
+    ) -> Result<()> {
+        self.rust_path_scope_hooks.add(name, Box::new(hook))
+    }
+
+    /// Same as `add_rust_path_scope_hook` with `DEFAULT_HOOK_NAME`.
+    /// Panics if the default hook was already set.
     pub fn set_rust_path_scope_hook(
         &mut self,
-        hook: impl Fn(&CppPath) -> Result<Option<RustPathScope>> + 'static,
+        hook: impl Fn(&CppPath) -> Result<Option<RustPathScope>> + Send + 'static,
     ) {
-        assert!(
-            self.rust_path_scope_hook.is_none(),
-            "only one hook can be set"
-        );
-        self.rust_path_scope_hook = Some(Box::new(hook));
+        self.add_rust_path_scope_hook(DEFAULT_HOOK_NAME, hook)
+            .expect("only one default hook can be set");
+    }
+
+    /// Removes a hook added by `add_rust_path_scope_hook`.
+    pub fn remove_rust_path_scope_hook(&mut self, name: &str) -> Result<()> {
+        self.rust_path_scope_hooks.remove(name)
+    }
+
+    pub fn rust_path_scope_hooks(&self) -> &Hooks<RustPathScopeHook> {
+        &self.rust_path_scope_hooks
+    }
+
+    /// Evaluates Rust path scope hooks for `path`.
+    pub fn rust_path_scope_from_hooks(&self, path: &CppPath) -> Result<Option<RustPathScope>> {
+        for (name, hook) in self.rust_path_scope_hooks.iter() {
+            if let Some(scope) = hook(path).with_context(|_| format!("hook failed: {}", name))? {
+                return Ok(Some(scope));
+            }
+        }
+        Ok(None)
     }
 
-    pub fn rust_path_scope_hook(&self) -> Option<&RustPathScopeHook> {
-        self.rust_path_scope_hook.as_ref().map(|b| &**b)
+    /// Adds a hook that determines Rust path of a C++ item.
+    /// Hooks are called in the order they were added, and the first
+    /// returned value is used.
+    /// Returns an error if a hook with the same `name` already exists.
+    pub fn add_rust_path_hook(
+        &mut self,
+        name: &str,
+        hook: impl Fn(&CppPath, NameType<'_>, &ProcessorData<'_>) -> Result<Option<RustPath>>
+            + Send
+            + 'static,
+    ) -> Result<()> {
+        self.rust_path_hooks.add(name, Box::new(hook))
     }
 
+    /// Same as `add_rust_path_hook` with `DEFAULT_HOOK_NAME`.
+    /// Panics if the default hook was already set.
     pub fn set_rust_path_hook(
         &mut self,
-        hook: impl Fn(&CppPath, NameType<'_>, &ProcessorData<'_>) -> Result<Option<RustPath>> + 'static,
+        hook: impl Fn(&CppPath, NameType<'_>, &ProcessorData<'_>) -> Result<Option<RustPath>>
+            + Send
+            + 'static,
     ) {
-        assert!(self.rust_path_hook.is_none(), "only one hook can be set");
-        self.rust_path_hook = Some(Box::new(hook));
+        self.add_rust_path_hook(DEFAULT_HOOK_NAME, hook)
+            .expect("only one default hook can be set");
     }
 
-    pub fn rust_path_hook(&self) -> Option<&RustPathHook> {
-        self.rust_path_hook.as_ref().map(|b| &**b)
+    /// Removes a hook added by `add_rust_path_hook`.
+    pub fn remove_rust_path_hook(&mut self, name: &str) -> Result<()> {
+        self.rust_path_hooks.remove(name)
     }
 
+    pub fn rust_path_hooks(&self) -> &Hooks<RustPathHook> {
+        &self.rust_path_hooks
+    }
+
+    /// Evaluates Rust path hooks for `path`.
+    pub fn rust_path_from_hooks(
+        &self,
+        path: &CppPath,
+        name_type: &NameType<'_>,
+        data: &ProcessorData<'_>,
+    ) -> Result<Option<RustPath>> {
+        for (name, hook) in self.rust_path_hooks.iter() {
+            if let Some(rust_path) = hook(path, name_type.clone(), data)
+                .with_context(|_| format!("hook failed: {}", name))?
+            {
+                return Ok(Some(rust_path));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Adds a hook that can modify generated Rust items.
+    /// All hooks are applied in the order they were added.
+    /// Returns an error if a hook with the same `name` already exists.
+    pub fn add_rust_item_hook(
+        &mut self,
+        name: &str,
+        hook: impl Fn(&mut RustItem, &ProcessorData<'_>) -> Result<()> + Send + 'static,
+    ) -> Result<()> {
+        self.rust_item_hooks.add(name, Box::new(hook))
+    }
+
+    /// Same as `add_rust_item_hook` with `DEFAULT_HOOK_NAME`.
+    /// Panics if the default hook was already set.
     pub fn set_rust_item_hook(
         &mut self,
-        hook: impl Fn(&mut RustItem, &ProcessorData<'_>) -> Result<()> + 'static,
+        hook: impl Fn(&mut RustItem, &ProcessorData<'_>) -> Result<()> + Send + 'static,
     ) {
-        assert!(self.rust_item_hook.is_none(), "only one hook can be set");
-        self.rust_item_hook = Some(Box::new(hook));
+        self.add_rust_item_hook(DEFAULT_HOOK_NAME, hook)
+            .expect("only one default hook can be set");
+    }
+
+    /// Removes a hook added by `add_rust_item_hook`.
+    pub fn remove_rust_item_hook(&mut self, name: &str) -> Result<()> {
+        self.rust_item_hooks.remove(name)
+    }
+
+    pub fn rust_item_hooks(&self) -> &Hooks<RustItemHook> {
+        &self.rust_item_hooks
     }
 
-    pub fn rust_item_hook(&self) -> Option<&RustItemHook> {
-        self.rust_item_hook.as_ref().map(|b| &**b)
+    /// Applies all Rust item hooks to `item`.
+    pub fn apply_rust_item_hooks(
+        &self,
+        item: &mut RustItem,
+        data: &ProcessorData<'_>,
+    ) -> Result<()> {
+        for (name, hook) in self.rust_item_hooks.iter() {
+            hook(item, data).with_context(|_| format!("hook failed: {}", name))?;
+        }
+        Ok(())
     }
 
     pub fn add_after_cpp_parser_hook(
         &mut self,
-        hook: impl Fn(&mut ProcessorData<'_>, &CppParserOutput) -> Result<()> + 'static,
+        hook: impl Fn(&mut ProcessorData<'_>, &CppParserOutput) -> Result<()> + Send + 'static,
     ) {
         self.after_cpp_parser_hooks.push(Box::new(hook));
     }
@@ -487,16 +704,45 @@ impl Config {
         &self.after_cpp_parser_hooks
     }
 
-    pub fn set_cpp_item_filter_hook(&mut self, hook: impl Fn(&CppItem) -> Result<bool> + 'static) {
-        assert!(
-            self.cpp_item_filter_hook.is_none(),
-            "only one hook can be set"
-        );
-        self.cpp_item_filter_hook = Some(Box::new(hook));
+    /// Adds a hook that determines whether a C++ item should be processed.
+    /// Hooks are called in the order they were added, and the item
+    /// is only accepted if all hooks return `true`.
+    /// Returns an error if a hook with the same `name` already exists.
+    pub fn add_cpp_item_filter_hook(
+        &mut self,
+        name: &str,
+        hook: impl Fn(&CppItem) -> Result<bool> + Send + 'static,
+    ) -> Result<()> {
+        self.cpp_item_filter_hooks.add(name, Box::new(hook))
+    }
+
+    /// Same as `add_cpp_item_filter_hook` with `DEFAULT_HOOK_NAME`.
+    /// Panics if the default hook was already set.
+    pub fn set_cpp_item_filter_hook(
+        &mut self,
+        hook: impl Fn(&CppItem) -> Result<bool> + Send + 'static,
+    ) {
+        self.add_cpp_item_filter_hook(DEFAULT_HOOK_NAME, hook)
+            .expect("only one default hook can be set");
+    }
+
+    /// Removes a hook added by `add_cpp_item_filter_hook`.
+    pub fn remove_cpp_item_filter_hook(&mut self, name: &str) -> Result<()> {
+        self.cpp_item_filter_hooks.remove(name)
     }
 
-    pub fn cpp_item_filter_hook(&self) -> Option<&CppItemFilterHook> {
-        self.cpp_item_filter_hook.as_ref().map(|b| &**b)
+    pub fn cpp_item_filter_hooks(&self) -> &Hooks<CppItemFilterHook> {
+        &self.cpp_item_filter_hooks
+    }
+
+    /// Returns true if `item` is accepted by all C++ item filter hooks.
+    pub fn check_cpp_item(&self, item: &CppItem) -> Result<bool> {
+        for (name, hook) in self.cpp_item_filter_hooks.iter() {
+            if !hook(item).with_context(|_| format!("hook failed: {}", name))? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
     }
 
     pub fn set_cluster_config(&mut self, cluster_config: ClusterConfig) {
@@ -522,6 +768,58 @@ impl Config {
     pub fn write_dependencies_local_paths(&self) -> bool {
         self.write_dependencies_local_paths
     }
+
+    /// Enables parsing of object-like macros defined in the target include paths.
+    /// Macros defined as a single integer, floating point or string literal
+    /// are added to the database and converted to Rust constants.
+    /// Other macros are ignored. This option is disabled by default.
+    pub fn set_parse_macro_constants(&mut self, value: bool) {
+        self.parse_macro_constants = value;
+    }
+
+    pub fn parse_macro_constants(&self) -> bool {
+        self.parse_macro_constants
+    }
+
+    /// Enables extraction of Doxygen comments (`///`, `/** */` and similar)
+    /// attached to parsed declarations. `@brief`, `@param` and `@return` commands
+    /// are converted to documentation of the generated Rust items. Use this option
+    /// for libraries that don't provide a separate documentation source.
+    /// This option is disabled by default.
+    pub fn set_parse_doc_comments(&mut self, value: bool) {
+        self.parse_doc_comments = value;
+    }
+
+    pub fn parse_doc_comments(&self) -> bool {
+        self.parse_doc_comments
+    }
+
+    /// Enables plain C mode for binding libraries written in C.
+    /// Headers are parsed as C (`-x c`), functions with C linkage are declared
+    /// in Rust `extern "C"` blocks and called directly, and structs with
+    /// known layout are generated as `#[repr(C)]` Rust structs.
+    /// The C++ wrapper library is only built if some items still require
+    /// wrapper code (e.g. accessors for global variables).
+    /// This option is disabled by default.
+    pub fn set_plain_c_mode(&mut self, value: bool) {
+        self.plain_c_mode = value;
+    }
+
+    pub fn plain_c_mode(&self) -> bool {
+        self.plain_c_mode
+    }
+
+    /// Enables catching C++ exceptions in FFI wrappers. If enabled, functions
+    /// not declared as `noexcept` return `Result<T, cpp_core::CppException>`
+    /// in the generated Rust API. If disabled (the default), an exception thrown
+    /// by the C++ library aborts the process.
+    pub fn set_catch_exceptions(&mut self, value: bool) {
+        self.catch_exceptions = value;
+    }
+
+    pub fn catch_exceptions(&self) -> bool {
+        self.catch_exceptions
+    }
 }
 
 #[derive(Default)]
@@ -560,3 +858,80 @@ impl GlobalConfig {
         &self.all_crate_names
     }
 }
+
+#[test]
+fn hook_chains() {
+    let mut config = Config::new(CrateProperties::new("test", "0.1.0"));
+    assert!(config
+        .check_cpp_parser_path(&CppPath::from_good_str("a::b"))
+        .unwrap());
+
+    config.set_cpp_parser_path_hook(|path| Ok(path.items()[0].name != "a"));
+    config
+        .add_cpp_parser_path_hook("second", |path| Ok(path.items().len() < 3))
+        .unwrap();
+    assert!(config
+        .add_cpp_parser_path_hook("second", |_| Ok(true))
+        .is_err());
+    assert_eq!(
+        config.cpp_parser_path_hooks().names().collect::<Vec<_>>(),
+        vec![DEFAULT_HOOK_NAME, "second"]
+    );
+    assert!(!config
+        .check_cpp_parser_path(&CppPath::from_good_str("a::b"))
+        .unwrap());
+    assert!(!config
+        .check_cpp_parser_path(&CppPath::from_good_str("c::d::e"))
+        .unwrap());
+    assert!(config
+        .check_cpp_parser_path(&CppPath::from_good_str("c::d"))
+        .unwrap());
+
+    config
+        .remove_cpp_parser_path_hook(DEFAULT_HOOK_NAME)
+        .unwrap();
+    assert!(config
+        .remove_cpp_parser_path_hook(DEFAULT_HOOK_NAME)
+        .is_err());
+    assert!(config
+        .check_cpp_parser_path(&CppPath::from_good_str("a::b"))
+        .unwrap());
+
+    config
+        .add_rust_path_scope_hook("first", |_| Ok(None))
+        .unwrap();
+    config
+        .add_rust_path_scope_hook("second", |_| {
+            Ok(Some(RustPathScope {
+                path: RustPath::from_good_str("second"),
+                prefix: None,
+            }))
+        })
+        .unwrap();
+    config
+        .add_rust_path_scope_hook("third", |_| {
+            Ok(Some(RustPathScope {
+                path: RustPath::from_good_str("third"),
+                prefix: None,
+            }))
+        })
+        .unwrap();
+    let scope = config
+        .rust_path_scope_from_hooks(&CppPath::from_good_str("a"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(scope.path, RustPath::from_good_str("second"));
+
+    config
+        .add_movable_types_hook("first", |_| Ok(MovableTypesHookOutput::Unknown))
+        .unwrap();
+    config
+        .add_movable_types_hook("second", |_| Ok(MovableTypesHookOutput::Immovable))
+        .unwrap();
+    assert_eq!(
+        config
+            .movable_type_from_hooks(&CppPath::from_good_str("a"))
+            .unwrap(),
+        MovableTypesHookOutput::Immovable
+    );
+}
@@ -22,9 +22,14 @@ fn create_cast_method(cast: CppCast, from: &CppType, to: &CppType) -> Result<Cpp
         arguments: vec![CppFunctionArgument {
             name: "ptr".to_string(),
             argument_type: from.clone(),
-            has_default_value: false,
+            default_value: None,
         }],
         allows_variadic_arguments: false,
+        is_noexcept: true,
+        is_deleted: false,
+        deprecation: None,
+        has_c_linkage: false,
+        is_hidden_friend: false,
         declaration_code: None,
         cast: Some(cast),
     };
@@ -483,7 +483,10 @@ impl CppChecker<'_, '_> {
 
     fn run_local(&mut self) -> Result<()> {
         let instance_provider = LocalCppChecker::new(
-            self.data.workspace.tmp_path().join("cpp_checker"),
+            self.data
+                .workspace
+                .crate_tmp_path(self.data.config.crate_properties().name())?
+                .join("cpp_checker"),
             &self.data.config,
         )?;
 
@@ -674,6 +677,7 @@ fn type_paths(type1: &CppType) -> Vec<&CppPath> {
             .flat_map(|type1| type_paths(type1))
             .collect(),
         CppType::PointerLike { target, .. } => type_paths(target),
+        CppType::TemplateArgumentValue(value) => type_paths(&value.value_type),
     }
 }
 
@@ -708,7 +712,9 @@ pub fn check_cpp_parser_hook(
 }
 
 pub fn delete_blacklisted_items(data: &mut ProcessorData<'_>) -> Result<()> {
-    if let Some(hook) = data.config.cpp_parser_path_hook() {
+    if !data.config.cpp_parser_path_hooks().is_empty() {
+        let config = data.config;
+        let hook = |path: &CppPath| config.check_cpp_parser_path(path);
         let mut bad_cpp_item_ids = Vec::new();
         for cpp_item in data.db.cpp_items() {
             if !check_cpp_parser_hook(&cpp_item.item, &hook)? {
@@ -720,10 +726,10 @@ pub fn delete_blacklisted_items(data: &mut ProcessorData<'_>) -> Result<()> {
             .delete_items(|item| bad_cpp_item_ids.contains(&item.id));
     }
 
-    if let Some(hook) = data.config.cpp_item_filter_hook() {
+    if !data.config.cpp_item_filter_hooks().is_empty() {
         let mut bad_cpp_item_ids = Vec::new();
         for cpp_item in data.db.cpp_items() {
-            if !hook(&cpp_item.item)? {
+            if !data.config.check_cpp_item(&cpp_item.item)? {
                 info!("deleting {}: {}", cpp_item.id, cpp_item.item.short_text());
                 bad_cpp_item_ids.push(cpp_item.id);
             }
@@ -7,7 +7,7 @@ use crate::cpp_ffi_data::{
 use crate::cpp_ffi_data::{CppFfiFunction, CppFfiItem};
 use crate::cpp_function::{CppFunction, ReturnValueAllocationPlace};
 use crate::cpp_type::CppPointerLikeTypeKind;
-use crate::cpp_type::CppType;
+use crate::cpp_type::{CppFunctionPointerType, CppType};
 use crate::database::{DatabaseClient, DbItem};
 use crate::rust_info::{RustItem, RustStructKind};
 use itertools::Itertools;
@@ -275,6 +275,14 @@ impl Generator<'_> {
                     .find(|x| x.meaning == CppFfiArgumentMeaning::This)
                 {
                     format!("{}->{}", arg.name, path.last().to_cpp_code()?)
+                } else if cpp_item
+                    .item
+                    .as_function_ref()
+                    .map_or(false, |f| f.is_hidden_friend)
+                {
+                    // hidden friends can't be found by qualified name lookup,
+                    // so an unqualified call is used to perform argument-dependent lookup
+                    path.last().to_cpp_code()?
                 } else {
                     path.to_cpp_code()?
                 }
@@ -313,18 +321,18 @@ impl Generator<'_> {
             .map_or(false, |f| f.is_destructor());
 
         let method = item.item;
-        if is_destructor && method.allocation_place == ReturnValueAllocationPlace::Heap {
+        let body = if is_destructor && method.allocation_place == ReturnValueAllocationPlace::Heap {
             if let Some(arg) = method
                 .arguments
                 .iter()
                 .find(|x| x.meaning == CppFfiArgumentMeaning::This)
             {
-                Ok(format!("delete {};\n", arg.name))
+                format!("delete {};\n", arg.name)
             } else {
                 panic!("Error: no this argument found\n{:?}", method);
             }
         } else {
-            Ok(format!(
+            format!(
                 "{}{};\n",
                 if method.return_type.ffi_type().is_void() {
                     ""
@@ -332,12 +340,38 @@ impl Generator<'_> {
                     "return "
                 },
                 self.returned_expression(item)?
+            )
+        };
+
+        if let Some(arg) = method
+            .arguments
+            .iter()
+            .find(|x| x.meaning == CppFfiArgumentMeaning::Exception)
+        {
+            // the returned value is ignored by the caller if an exception was caught
+            let fallback_return = if method.return_type.ffi_type().is_void() {
+                ""
+            } else {
+                "  return {};\n"
+            };
+            Ok(format!(
+                "try {{\n    {body}  }} catch (const std::exception& e) {{\n    \
+                 ritual::set_exception({arg}, e);\n  }} catch (...) {{\n    \
+                 ritual::set_unknown_exception({arg});\n  }}\n{fallback_return}",
+                body = body,
+                arg = arg.name,
+                fallback_return = fallback_return,
             ))
+        } else {
+            Ok(body)
         }
     }
 
     /// Generates implementation of the FFI method for the source file.
     fn function_implementation(&self, method: DbItem<&CppFfiFunction>) -> Result<String> {
+        if method.item.is_direct_call {
+            return self.direct_call_check(method.item);
+        }
         Ok(format!(
             "RITUAL_EXPORT {} {{\n  {}}}\n\n",
             self.function_signature(method.item)?,
@@ -345,6 +379,26 @@ impl Generator<'_> {
         ))
     }
 
+    /// Generates code that verifies that a C function called directly from Rust
+    /// is available and has the expected signature.
+    fn direct_call_check(&self, method: &CppFfiFunction) -> Result<String> {
+        let function_type = CppType::FunctionPointer(CppFunctionPointerType {
+            return_type: Box::new(method.return_type.ffi_type().clone()),
+            arguments: method
+                .arguments
+                .iter()
+                .map(|arg| arg.argument_type.ffi_type().clone())
+                .collect(),
+            allows_variadic_arguments: false,
+        });
+        let variable_name = format!("ritual_check_{}", method.path.last().name);
+        Ok(format!(
+            "static {} = &{};\n\n",
+            function_type.to_cpp_code(Some(&variable_name))?,
+            method.path.to_cpp_code()?
+        ))
+    }
+
     fn condition_expression(&self, condition: &Condition) -> String {
         match condition {
             Condition::CppLibraryVersion(version) => {
@@ -382,11 +436,8 @@ impl Generator<'_> {
         )
     }
 
-    /// Generates a source file with the specified FFI methods.
-    fn generate_cpp_file(&self, file_path: &Path, global_header_name: &str) -> Result<()> {
-        let mut cpp_file = create_file(file_path)?;
-        writeln!(cpp_file, "#include \"{}\"", global_header_name)?;
-
+    /// Returns FFI items that require C++ wrapper code.
+    fn wrapper_ffi_items(&self) -> Vec<DbItem<&CppFfiItem>> {
         let used_ffi_functions = self
             .0
             .rust_items()
@@ -395,14 +446,47 @@ impl Generator<'_> {
             .map(|item| item.path.last())
             .collect::<HashSet<&str>>();
 
-        let ffi_items = self
-            .0
+        self.0
             .ffi_items()
-            .filter(|item| {
-                !item.item.is_function()
-                    || used_ffi_functions.contains(item.item.path().last().name.as_str())
+            .filter(|item| match &item.item {
+                CppFfiItem::Function(function) => {
+                    !function.is_direct_call
+                        && used_ffi_functions.contains(function.path.last().name.as_str())
+                }
+                _ => true,
             })
-            .collect_vec();
+            .collect_vec()
+    }
+
+    /// Returns true if the C++ wrapper library contains any code
+    /// and needs to be built.
+    fn has_wrapper_code(&self) -> Result<bool> {
+        for ffi_item in self.wrapper_ffi_items() {
+            if self.0.cpp_checks(&ffi_item.id)?.any_success() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Checks if sizes of some types should be determined by `sized_types.cxx`.
+    fn has_sized_types(&self) -> bool {
+        self.0.rust_items().any(|item| {
+            if let RustItem::Struct(data) = item.item {
+                if let RustStructKind::SizedType(_) = &data.kind {
+                    return true;
+                }
+            }
+            false
+        })
+    }
+
+    /// Generates a source file with the specified FFI methods.
+    fn generate_cpp_file(&self, file_path: &Path, global_header_name: &str) -> Result<()> {
+        let mut cpp_file = create_file(file_path)?;
+        writeln!(cpp_file, "#include \"{}\"", global_header_name)?;
+
+        let ffi_items = self.wrapper_ffi_items();
 
         let mut needs_moc = false;
         for ffi_item in &ffi_items {
@@ -517,6 +601,14 @@ pub fn generate_cpp_file(
     Generator(db).generate_cpp_file(file_path, global_header_name)
 }
 
+pub fn has_wrapper_code(db: &DatabaseClient) -> Result<bool> {
+    Generator(db).has_wrapper_code()
+}
+
+pub fn has_sized_types(db: &DatabaseClient) -> bool {
+    Generator(db).has_sized_types()
+}
+
 pub fn generate_cpp_type_size_requester(
     db: &DatabaseClient,
     include_directives: &[PathBuf],
@@ -9,6 +9,8 @@ use ritual_common::errors::{bail, ensure, Error, Result};
 use ritual_common::utils::MapIfOk;
 use serde_derive::{Deserialize, Serialize};
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
 use std::str::FromStr;
 
 /// One item of a C++ enum declaration
@@ -16,7 +18,9 @@ use std::str::FromStr;
 pub struct CppEnumValue {
     /// Full path containing enum path and variant name.
     pub path: CppPath,
-    /// Corresponding value
+    /// Corresponding value. Values of enums with an unsigned 64-bit
+    /// underlying type that don't fit in `i64` are stored as negative numbers
+    /// with the same bit representation.
     pub value: i64,
 }
 
@@ -49,6 +53,22 @@ fn unscoped_path_should_work() {
     check("A::B", "B");
 }
 
+#[test]
+fn constant_values_compare_floats_by_bits() {
+    use std::collections::hash_map::DefaultHasher;
+
+    let hash = |value: &CppConstantValue| {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    };
+    let nan = CppConstantValue::Float(std::f64::NAN);
+    assert_eq!(nan, nan.clone());
+    assert_eq!(hash(&nan), hash(&nan.clone()));
+    assert_ne!(CppConstantValue::Float(0.0), CppConstantValue::Float(-0.0));
+    assert_ne!(CppConstantValue::Float(1.0), CppConstantValue::Integer(1));
+}
+
 /// Member field of a C++ class declaration
 #[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
 pub struct CppClassField {
@@ -83,6 +103,130 @@ impl CppClassField {
     }
 }
 
+/// Value of a C++ compile-time constant evaluated by the parser
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CppConstantValue {
+    /// Integer value. Unsigned values that don't fit in `i64` are stored
+    /// as their bit pattern, so the value must be interpreted according to
+    /// the type of the constant.
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+// Floats are compared by their bit representation, so NaN values
+// (e.g. `std::numeric_limits<double>::quiet_NaN()`) are equal to themselves.
+impl PartialEq for CppConstantValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CppConstantValue::Integer(a), CppConstantValue::Integer(b)) => a == b,
+            (CppConstantValue::Float(a), CppConstantValue::Float(b)) => a.to_bits() == b.to_bits(),
+            (CppConstantValue::String(a), CppConstantValue::String(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for CppConstantValue {}
+
+impl Hash for CppConstantValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            CppConstantValue::Integer(value) => value.hash(state),
+            CppConstantValue::Float(value) => value.to_bits().hash(state),
+            CppConstantValue::String(value) => value.hash(state),
+        }
+    }
+}
+
+impl fmt::Display for CppConstantValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CppConstantValue::Integer(value) => write!(f, "{}", value),
+            CppConstantValue::Float(value) => write!(f, "{:?}", value),
+            CppConstantValue::String(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+/// Variable declared at namespace scope
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct CppGlobalVariable {
+    pub path: CppPath,
+    /// Variable type
+    pub variable_type: CppType,
+    /// True if the variable is `const` or `constexpr`
+    pub is_const: bool,
+    /// Value of the variable if it's a constant of an arithmetic type
+    /// and clang was able to evaluate it
+    pub value: Option<CppConstantValue>,
+}
+
+impl CppGlobalVariable {
+    pub fn is_same(&self, other: &CppGlobalVariable) -> bool {
+        self.path == other.path
+            && self.variable_type == other.variable_type
+            && self.is_const == other.is_const
+    }
+
+    pub fn short_text(&self) -> String {
+        let mut text = format!(
+            "{}{} {}",
+            if self.is_const { "const " } else { "" },
+            self.variable_type.to_cpp_pseudo_code(),
+            self.path.to_cpp_pseudo_code(),
+        );
+        if let Some(value) = &self.value {
+            text += &format!(" = {}", value);
+        }
+        text
+    }
+}
+
+/// Object-like preprocessor macro defined as a literal value
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct CppMacroConstant {
+    pub path: CppPath,
+    /// Type of the literal
+    pub value_type: CppType,
+    pub value: CppConstantValue,
+    /// Location of the `#define` directive
+    pub origin_location: CppOriginLocation,
+    /// Namespace of declarations of the header that defines the macro
+    /// (if all of them belong to the same namespace).
+    /// The Rust constant is placed into the module of this namespace.
+    pub namespace: Option<CppPath>,
+}
+
+impl CppMacroConstant {
+    pub fn is_same(&self, other: &CppMacroConstant) -> bool {
+        self.path == other.path && self.value_type == other.value_type && self.value == other.value
+    }
+
+    pub fn short_text(&self) -> String {
+        format!("#define {} {}", self.path.to_cpp_pseudo_code(), self.value)
+    }
+}
+
+/// Typedef or `using` declaration of a type alias
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct CppTypeAlias {
+    pub path: CppPath,
+    /// Type the alias refers to
+    pub target_type: CppType,
+}
+
+impl CppTypeAlias {
+    pub fn short_text(&self) -> String {
+        format!(
+            "using {} = {}",
+            self.path.to_cpp_pseudo_code(),
+            self.target_type.to_cpp_pseudo_code()
+        )
+    }
+}
+
 /// Item of base class list in a class declaration
 #[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
 pub struct CppBaseSpecifier {
@@ -369,8 +513,19 @@ impl fmt::Debug for CppPathItem {
 /// Information about a C++ type declaration
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, Hash)]
 pub enum CppTypeDeclarationKind {
-    Enum,
+    Enum {
+        /// Integer type used to store values of the enum
+        /// (`int` if the underlying type is not fixed)
+        underlying_type: CppType,
+        /// True if the enum is declared as `enum class` or `enum struct`
+        is_scoped: bool,
+    },
     Class,
+    Union {
+        /// True if the union can be copied with `memcpy`. Only POD unions
+        /// are detected as trivially copyable.
+        is_trivially_copyable: bool,
+    },
 }
 
 /// Information about a C++ type declaration
@@ -388,17 +543,24 @@ impl CppTypeDeclaration {
 }
 
 impl CppTypeDeclarationKind {
-    /// Checks if the type is a class type.
+    /// Checks if the type is a class type. Unions are also considered class types.
     pub fn is_class(&self) -> bool {
         match self {
-            CppTypeDeclarationKind::Class { .. } => true,
+            CppTypeDeclarationKind::Class { .. } | CppTypeDeclarationKind::Union { .. } => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_union(&self) -> bool {
+        match self {
+            CppTypeDeclarationKind::Union { .. } => true,
             _ => false,
         }
     }
 
     pub fn is_enum(&self) -> bool {
         match self {
-            CppTypeDeclarationKind::Enum => true,
+            CppTypeDeclarationKind::Enum { .. } => true,
             _ => false,
         }
     }
@@ -418,6 +580,9 @@ pub enum CppItem {
     Function(CppFunction),
     ClassField(CppClassField),
     ClassBase(CppBaseSpecifier),
+    GlobalVariable(CppGlobalVariable),
+    MacroConstant(CppMacroConstant),
+    TypeAlias(CppTypeAlias),
 }
 
 impl CppItem {
@@ -467,6 +632,27 @@ impl CppItem {
                     false
                 }
             }
+            GlobalVariable(v) => {
+                if let GlobalVariable(v2) = &other {
+                    v.is_same(v2)
+                } else {
+                    false
+                }
+            }
+            MacroConstant(v) => {
+                if let MacroConstant(v2) = &other {
+                    v.is_same(v2)
+                } else {
+                    false
+                }
+            }
+            TypeAlias(v) => {
+                if let TypeAlias(v2) = &other {
+                    v == v2
+                } else {
+                    false
+                }
+            }
         }
     }
 
@@ -477,6 +663,9 @@ impl CppItem {
             CppItem::EnumValue(data) => &data.path,
             CppItem::Function(data) => &data.path,
             CppItem::ClassField(data) => &data.path,
+            CppItem::GlobalVariable(data) => &data.path,
+            CppItem::MacroConstant(data) => &data.path,
+            CppItem::TypeAlias(data) => &data.path,
             CppItem::ClassBase(_) => return None,
         };
         Some(path)
@@ -485,10 +674,12 @@ impl CppItem {
     pub fn all_involved_types(&self) -> Vec<CppType> {
         match self {
             CppItem::Type(t) => match t.kind {
-                CppTypeDeclarationKind::Enum => vec![CppType::Enum {
+                CppTypeDeclarationKind::Enum { .. } => vec![CppType::Enum {
                     path: t.path.clone(),
                 }],
-                CppTypeDeclarationKind::Class { .. } => vec![CppType::Class(t.path.clone())],
+                CppTypeDeclarationKind::Class { .. } | CppTypeDeclarationKind::Union { .. } => {
+                    vec![CppType::Class(t.path.clone())]
+                }
             },
             CppItem::EnumValue(enum_value) => vec![CppType::Enum {
                 path: enum_value
@@ -507,6 +698,9 @@ impl CppItem {
                 CppType::Class(base.base_class_type.clone()),
                 CppType::Class(base.derived_class_type.clone()),
             ],
+            CppItem::GlobalVariable(variable) => vec![variable.variable_type.clone()],
+            CppItem::MacroConstant(constant) => vec![constant.value_type.clone()],
+            CppItem::TypeAlias(alias) => vec![alias.target_type.clone()],
         }
     }
 
@@ -531,6 +725,27 @@ impl CppItem {
             None
         }
     }
+    pub fn as_global_variable_ref(&self) -> Option<&CppGlobalVariable> {
+        if let CppItem::GlobalVariable(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
+    pub fn as_macro_constant_ref(&self) -> Option<&CppMacroConstant> {
+        if let CppItem::MacroConstant(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
+    pub fn as_type_alias_ref(&self) -> Option<&CppTypeAlias> {
+        if let CppItem::TypeAlias(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
     pub fn as_enum_value_ref(&self) -> Option<&CppEnumValue> {
         if let CppItem::EnumValue(data) = self {
             Some(data)
@@ -568,6 +783,9 @@ impl CppItem {
             CppItem::Function(value) => value.short_text(),
             CppItem::ClassField(value) => value.short_text(),
             CppItem::ClassBase(_) => format!("{:?}", self),
+            CppItem::GlobalVariable(value) => value.short_text(),
+            CppItem::MacroConstant(value) => value.short_text(),
+            CppItem::TypeAlias(value) => value.short_text(),
         }
     }
 }
@@ -578,11 +796,22 @@ impl fmt::Display for CppItem {
             CppItem::Namespace(namespace) => {
                 format!("namespace {}", namespace.path.to_cpp_pseudo_code())
             }
-            CppItem::Type(type1) => match type1.kind {
-                CppTypeDeclarationKind::Enum => format!("enum {}", type1.path.to_cpp_pseudo_code()),
+            CppItem::Type(type1) => match &type1.kind {
+                CppTypeDeclarationKind::Enum {
+                    underlying_type,
+                    is_scoped,
+                } => format!(
+                    "enum {}{} : {}",
+                    if *is_scoped { "class " } else { "" },
+                    type1.path.to_cpp_pseudo_code(),
+                    underlying_type.to_cpp_pseudo_code()
+                ),
                 CppTypeDeclarationKind::Class { .. } => {
                     format!("class {}", type1.path.to_cpp_pseudo_code())
                 }
+                CppTypeDeclarationKind::Union { .. } => {
+                    format!("union {}", type1.path.to_cpp_pseudo_code())
+                }
             },
             CppItem::Function(method) => method.short_text(),
             CppItem::EnumValue(value) => format!(
@@ -591,6 +820,9 @@ impl fmt::Display for CppItem {
                 value.value
             ),
             CppItem::ClassField(field) => field.short_text(),
+            CppItem::GlobalVariable(variable) => variable.short_text(),
+            CppItem::MacroConstant(constant) => constant.short_text(),
+            CppItem::TypeAlias(alias) => alias.short_text(),
             CppItem::ClassBase(class_base) => {
                 let virtual_text = if class_base.is_virtual {
                     "virtual "
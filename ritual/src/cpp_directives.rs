@@ -0,0 +1,125 @@
+//! Generation directives specified in C++ headers using
+//! `__attribute__((annotate("ritual:...")))`.
+
+use ritual_common::errors::{bail, Result};
+use serde_derive::{Deserialize, Serialize};
+
+/// Prefix of annotations recognized as directives.
+pub const DIRECTIVE_PREFIX: &str = "ritual:";
+
+/// Directives attached to a C++ item.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CppDirectives {
+    /// `ritual:skip`: the item is ignored by the parser,
+    /// same as rejecting it in `cpp_parser_path_hook`.
+    pub skip: bool,
+    /// `ritual:rename=name`: last part of the Rust path
+    /// generated for the item.
+    pub rename: Option<String>,
+    /// `ritual:movable`: the class is stored by value,
+    /// same as returning `Movable` from `movable_types_hook`.
+    pub movable: bool,
+    /// `ritual:nullable`: the pointer returned by the function may be null,
+    /// so it's represented as `Option<Ref<T>>` in Rust API.
+    pub nullable: bool,
+}
+
+/// A single directive specified in an annotation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CppDirective {
+    Skip,
+    Rename(String),
+    Movable,
+    Nullable,
+}
+
+impl CppDirective {
+    /// Parses an annotation. Returns `None` if the annotation doesn't have the `ritual:` prefix.
+    pub fn parse(annotation: &str) -> Result<Option<Self>> {
+        let annotation = annotation.trim();
+        if !annotation.starts_with(DIRECTIVE_PREFIX) {
+            return Ok(None);
+        }
+        let directive = annotation[DIRECTIVE_PREFIX.len()..].trim();
+        let mut parts = directive.splitn(2, '=');
+        let name = parts.next().unwrap_or("").trim();
+        let value = parts.next().map(str::trim);
+        let directive = match (name, value) {
+            ("skip", None) => CppDirective::Skip,
+            ("movable", None) => CppDirective::Movable,
+            ("nullable", None) => CppDirective::Nullable,
+            ("rename", Some(value)) => {
+                if value.is_empty()
+                    || !value.chars().all(|c| c.is_alphanumeric() || c == '_')
+                    || value.starts_with(|c: char| c.is_digit(10))
+                {
+                    bail!("invalid name in directive: {}", annotation);
+                }
+                CppDirective::Rename(value.to_string())
+            }
+            _ => bail!("unknown directive: {}", annotation),
+        };
+        Ok(Some(directive))
+    }
+}
+
+impl CppDirectives {
+    /// Applies an annotation. Annotations without the `ritual:` prefix are ignored.
+    pub fn add_annotation(&mut self, annotation: &str) -> Result<()> {
+        match CppDirective::parse(annotation)? {
+            Some(CppDirective::Skip) => self.skip = true,
+            Some(CppDirective::Rename(name)) => self.rename = Some(name),
+            Some(CppDirective::Movable) => self.movable = true,
+            Some(CppDirective::Nullable) => self.nullable = true,
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Returns true if no directives were specified.
+    pub fn is_empty(&self) -> bool {
+        self == &CppDirectives::default()
+    }
+}
+
+#[test]
+fn add_annotations() {
+    let mut directives = CppDirectives::default();
+    assert!(directives.is_empty());
+    directives.add_annotation("other:skip").unwrap();
+    assert!(directives.is_empty());
+    directives.add_annotation("ritual:rename=new_name").unwrap();
+    directives.add_annotation("ritual:nullable").unwrap();
+    assert_eq!(
+        directives,
+        CppDirectives {
+            skip: false,
+            rename: Some("new_name".to_string()),
+            movable: false,
+            nullable: true,
+        }
+    );
+
+    assert!(directives.add_annotation("ritual:unknown").is_err());
+    assert!(directives.add_annotation("ritual:skip=1").is_err());
+    assert!(directives.add_annotation("ritual:rename=").is_err());
+    assert!(directives.add_annotation("ritual:rename=a::b").is_err());
+}
+
+#[test]
+fn parse_directives() {
+    assert_eq!(CppDirective::parse("other:skip").unwrap(), None);
+    assert_eq!(
+        CppDirective::parse("ritual:skip").unwrap(),
+        Some(CppDirective::Skip)
+    );
+    assert_eq!(
+        CppDirective::parse(" ritual: skip ").unwrap(),
+        Some(CppDirective::Skip)
+    );
+    assert_eq!(
+        CppDirective::parse("ritual:rename = name2").unwrap(),
+        Some(CppDirective::Rename("name2".to_string()))
+    );
+    assert!(CppDirective::parse("ritual:skip=1").is_err());
+}
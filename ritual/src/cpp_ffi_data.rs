@@ -64,7 +64,7 @@ pub enum CppFfiFunctionKind {
     /// This is a real C++ function.
     Function,
     /// This is a field accessor, i.e. a non-existing getter or setter
-    /// method for a public field.
+    /// method for a public field or a global variable.
     FieldAccessor {
         /// Type of the accessor
         accessor_type: CppFieldAccessorType,
@@ -104,6 +104,9 @@ pub enum CppFfiArgumentMeaning {
     /// This argument receives pointer to the buffer where
     /// the return value should be transferred to using placement new.
     ReturnValue,
+    /// This argument receives pointer to `ritual::ExceptionInfo` where
+    /// information about a caught C++ exception should be written.
+    Exception,
 }
 
 impl CppFfiArgumentMeaning {
@@ -162,6 +165,10 @@ pub struct CppFfiFunction {
     pub path: CppPath,
 
     pub kind: CppFfiFunctionKind,
+
+    /// True if this is the original C function itself. Such functions are
+    /// called from Rust directly, so no C++ wrapper code is generated for them.
+    pub is_direct_call: bool,
 }
 
 impl CppFfiFunction {
@@ -1,8 +1,10 @@
 use crate::cpp_data::CppClassField;
+use crate::cpp_data::CppGlobalVariable;
 use crate::cpp_data::CppItem;
 use crate::cpp_data::CppPath;
 use crate::cpp_data::CppPathItem;
 use crate::cpp_data::CppVisibility;
+use crate::cpp_data::{CppTypeDeclaration, CppTypeDeclarationKind};
 use crate::cpp_ffi_data::CppFfiType;
 use crate::cpp_ffi_data::{CppFfiArgumentMeaning, CppToFfiTypeConversion};
 use crate::cpp_ffi_data::{CppFfiFunction, CppFfiFunctionKind, CppFieldAccessorType};
@@ -13,11 +15,12 @@ use crate::cpp_type::CppPointerLikeTypeKind;
 use crate::cpp_type::CppType;
 use crate::cpp_type::CppTypeRole;
 use crate::cpp_type::{is_qflags, CppFunctionPointerType};
-use crate::database::DbItem;
+use crate::database::{DatabaseClient, DbItem};
 use crate::processor::ProcessorData;
 use itertools::Itertools;
 use log::{debug, trace};
 use ritual_common::errors::{bail, Result, ResultExt};
+use ritual_common::utils::MapIfOk;
 use std::collections::HashSet;
 
 /// Converts this C++ type to its adaptation for FFI interface,
@@ -71,6 +74,9 @@ pub fn ffi_type(original_type: &CppType, role: CppTypeRole) -> Result<CppFfiType
                     }
                 }
             }
+            CppType::TemplateArgumentValue(_) => {
+                bail!("template argument values cannot be used as types");
+            }
             CppType::PointerLike {
                 kind,
                 is_const,
@@ -143,13 +149,45 @@ impl FfiNameProvider {
     }
 }
 
+/// Checks if values of the type are stored by value in Rust.
+/// Trivially copyable unions are always movable. Other classes are only movable
+/// if they are marked with the `ritual:movable` directive.
+pub fn is_movable(db: &DatabaseClient, type1: &CppTypeDeclaration) -> bool {
+    match type1.kind {
+        CppTypeDeclarationKind::Union {
+            is_trivially_copyable,
+        } => is_trivially_copyable,
+        CppTypeDeclarationKind::Class => db
+            .find_directives_for_cpp_path(&type1.path)
+            .map_or(false, |directives| directives.movable),
+        CppTypeDeclarationKind::Enum { .. } => false,
+    }
+}
+
 /// Runs the FFI generator
 pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
-    // movable types are not supported for now
-    let movable_types = Vec::new();
+    let movable_types = data
+        .db
+        .all_cpp_items()
+        .filter_map(|item| item.item.as_type_ref())
+        .filter(|type1| is_movable(data.db, type1))
+        .map(|type1| type1.path.clone())
+        .collect_vec();
 
     let mut name_provider = FfiNameProvider::new(data);
 
+    // C structs passed by value are only compatible with Rust structs
+    // if all their fields are known
+    let c_structs = if data.config.plain_c_mode() {
+        data.db
+            .cpp_items()
+            .filter_map(|item| item.item.as_field_ref())
+            .map(|field| field.path.parent())
+            .collect::<Result<HashSet<_>>>()?
+    } else {
+        HashSet::new()
+    };
+
     let all_cpp_item_ids = data.db.cpp_item_ids().collect_vec();
 
     for cpp_item_id in all_cpp_item_ids {
@@ -163,17 +201,44 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
         }
         let result = match &item.item {
             CppItem::Function(method) => {
-                generate_ffi_methods_for_method(method, &movable_types, &mut name_provider)
-                    .map(|v| v.into_iter().collect_vec())
+                let direct_function = if method.has_c_linkage {
+                    to_direct_ffi_method(method, &c_structs)
+                        .map_err(|err| {
+                            trace!("{} can't be called directly: {}", method.short_text(), err)
+                        })
+                        .ok()
+                } else {
+                    None
+                };
+                if let Some(function) = direct_function {
+                    Ok(vec![CppFfiItem::Function(function)])
+                } else {
+                    generate_ffi_methods_for_method(
+                        method,
+                        &movable_types,
+                        data.config.catch_exceptions(),
+                        &mut name_provider,
+                    )
+                }
             }
             CppItem::ClassField(field) => {
+                if c_structs.contains(&field.path.parent()?) {
+                    // fields of C structs are available in the generated Rust struct
+                    continue;
+                }
                 generate_field_accessors(field, &movable_types, &mut name_provider)
                     .map(|v| v.into_iter().collect_vec())
             }
+            CppItem::GlobalVariable(variable) => {
+                generate_global_variable_accessors(variable, &movable_types, &mut name_provider)
+                    .map(|v| v.into_iter().collect_vec())
+            }
             CppItem::ClassBase(_)
             | CppItem::Type(_)
             | CppItem::EnumValue(_)
-            | CppItem::Namespace(_) => {
+            | CppItem::Namespace(_)
+            | CppItem::MacroConstant(_)
+            | CppItem::TypeAlias(_) => {
                 // no FFI methods for these items
                 continue;
             }
@@ -200,20 +265,97 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
 fn generate_ffi_methods_for_method(
     method: &CppFunction,
     movable_types: &[CppPath],
+    catch_exceptions: bool,
     name_provider: &mut FfiNameProvider,
 ) -> Result<Vec<CppFfiItem>> {
     let mut methods = Vec::new();
-    methods.push(CppFfiItem::Function(to_ffi_method(
+    let mut ffi_function = to_ffi_method(
         NewFfiFunctionKind::Function {
             cpp_function: method.clone(),
         },
         movable_types,
         name_provider,
-    )?));
+    )?;
+    // destructors are implicitly `noexcept`
+    if catch_exceptions && !method.is_noexcept && !method.is_destructor() {
+        add_exception_argument(&mut ffi_function)?;
+    }
+    methods.push(CppFfiItem::Function(ffi_function));
 
     Ok(methods)
 }
 
+/// Creates an FFI function that refers to the C function `function` itself,
+/// so that it's called from Rust without a C++ wrapper.
+/// `c_structs` contains C structs that can be passed by value.
+fn to_direct_ffi_method(
+    function: &CppFunction,
+    c_structs: &HashSet<CppPath>,
+) -> Result<CppFfiFunction> {
+    if function.member.is_some() {
+        bail!("class member functions can't be called directly");
+    }
+    if function.allows_variadic_arguments {
+        bail!("Variable arguments are not supported");
+    }
+    let direct_type = |cpp_type: &CppType, role: CppTypeRole| -> Result<CppFfiType> {
+        if let CppType::Class(path) = cpp_type {
+            if !c_structs.contains(path) {
+                bail!(
+                    "struct with unknown layout is passed by value: {}",
+                    path.to_cpp_pseudo_code()
+                );
+            }
+            return CppFfiType::new(cpp_type.clone(), CppToFfiTypeConversion::NoChange);
+        }
+        let ffi_type = ffi_type(cpp_type, role)?;
+        if ffi_type.conversion() != &CppToFfiTypeConversion::NoChange {
+            bail!(
+                "type is not compatible with C ABI: {}",
+                cpp_type.to_cpp_pseudo_code()
+            );
+        }
+        Ok(ffi_type)
+    };
+
+    let arguments =
+        function
+            .arguments
+            .iter()
+            .enumerate()
+            .map_if_ok(|(index, arg)| -> Result<_> {
+                Ok(CppFfiFunctionArgument {
+                    name: arg.name.clone(),
+                    argument_type: direct_type(&arg.argument_type, CppTypeRole::NotReturnType)?,
+                    meaning: CppFfiArgumentMeaning::Argument(index),
+                })
+            })?;
+
+    Ok(CppFfiFunction {
+        arguments,
+        return_type: direct_type(&function.return_type, CppTypeRole::ReturnType)?,
+        allocation_place: ReturnValueAllocationPlace::NotApplicable,
+        path: function.path.clone(),
+        kind: CppFfiFunctionKind::Function,
+        is_direct_call: true,
+    })
+}
+
+/// Adds an output argument that receives information about
+/// a C++ exception caught by the FFI function.
+fn add_exception_argument(function: &mut CppFfiFunction) -> Result<()> {
+    let exception_type = CppType::new_pointer(
+        false,
+        CppType::Class(CppPath::from_good_str("ritual::ExceptionInfo")),
+    );
+    function.arguments.push(CppFfiFunctionArgument {
+        name: "exception".to_string(),
+        argument_type: ffi_type(&exception_type, CppTypeRole::NotReturnType)?,
+        meaning: CppFfiArgumentMeaning::Exception,
+    });
+    Ok(())
+}
+
 pub enum NewFfiFunctionKind {
     Function {
         cpp_function: CppFunction,
@@ -222,6 +364,10 @@ pub enum NewFfiFunctionKind {
         accessor_type: CppFieldAccessorType,
         field: CppClassField,
     },
+    GlobalVariableAccessor {
+        accessor_type: CppFieldAccessorType,
+        variable: CppGlobalVariable,
+    },
 }
 
 /// Creates FFI function signature for this function:
@@ -249,6 +395,19 @@ pub fn to_ffi_method(
                 CppFieldAccessorType::Setter => format!("set_{}", field_caption),
             }
         }
+        NewFfiFunctionKind::GlobalVariableAccessor {
+            variable,
+            accessor_type,
+        } => {
+            let variable_caption = variable.path.ascii_caption();
+            match *accessor_type {
+                CppFieldAccessorType::CopyGetter | CppFieldAccessorType::ConstRefGetter => {
+                    variable_caption
+                }
+                CppFieldAccessorType::MutRefGetter => format!("{}_mut", variable_caption),
+                CppFieldAccessorType::Setter => format!("set_{}", variable_caption),
+            }
+        }
     };
 
     let mut r = CppFfiFunction {
@@ -256,9 +415,11 @@ pub fn to_ffi_method(
         return_type: CppFfiType::void(),
         path: name_provider.create_path(&ascii_caption),
         allocation_place: ReturnValueAllocationPlace::NotApplicable,
+        is_direct_call: false,
         kind: match kind {
             NewFfiFunctionKind::Function { .. } => CppFfiFunctionKind::Function,
-            NewFfiFunctionKind::FieldAccessor { accessor_type, .. } => {
+            NewFfiFunctionKind::FieldAccessor { accessor_type, .. }
+            | NewFfiFunctionKind::GlobalVariableAccessor { accessor_type, .. } => {
                 CppFfiFunctionKind::FieldAccessor { accessor_type }
             }
         },
@@ -287,6 +448,7 @@ pub fn to_ffi_method(
                 Some(CppType::new_pointer(is_const, class_type))
             }
         }
+        NewFfiFunctionKind::GlobalVariableAccessor { .. } => None,
     };
 
     if let Some(this_arg_type) = this_arg_type {
@@ -323,7 +485,22 @@ pub fn to_ffi_method(
                 let arg = CppFunctionArgument {
                     name: "value".to_string(),
                     argument_type: field.field_type.clone(),
-                    has_default_value: false,
+                    default_value: None,
+                };
+                vec![arg]
+            } else {
+                Vec::new()
+            }
+        }
+        NewFfiFunctionKind::GlobalVariableAccessor {
+            variable,
+            accessor_type,
+        } => {
+            if accessor_type == &CppFieldAccessorType::Setter {
+                let arg = CppFunctionArgument {
+                    name: "value".to_string(),
+                    argument_type: variable.variable_type.clone(),
+                    default_value: None,
                 };
                 vec![arg]
             } else {
@@ -361,6 +538,19 @@ pub fn to_ffi_method(
             }
             CppFieldAccessorType::Setter => CppType::Void,
         },
+        NewFfiFunctionKind::GlobalVariableAccessor {
+            variable,
+            accessor_type,
+        } => match *accessor_type {
+            CppFieldAccessorType::CopyGetter => variable.variable_type.clone(),
+            CppFieldAccessorType::ConstRefGetter => {
+                CppType::new_reference(true, variable.variable_type.clone())
+            }
+            CppFieldAccessorType::MutRefGetter => {
+                CppType::new_reference(false, variable.variable_type.clone())
+            }
+            CppFieldAccessorType::Setter => CppType::Void,
+        },
     };
     let real_return_type_ffi = ffi_type(&real_return_type, CppTypeRole::ReturnType)?;
     match &real_return_type {
@@ -417,9 +607,48 @@ fn generate_field_accessors(
     Ok(new_methods)
 }
 
+/// Adds fictional getter and setter functions for a global variable.
+/// Constants with a known value don't need accessors because they are
+/// generated as Rust constants.
+fn generate_global_variable_accessors(
+    variable: &CppGlobalVariable,
+    movable_types: &[CppPath],
+    name_provider: &mut FfiNameProvider,
+) -> Result<Vec<CppFfiItem>> {
+    let mut new_methods = Vec::new();
+    if variable.value.is_some() {
+        return Ok(new_methods);
+    }
+    let mut create_method = |accessor_type| -> Result<CppFfiItem> {
+        let kind = NewFfiFunctionKind::GlobalVariableAccessor {
+            variable: variable.clone(),
+            accessor_type,
+        };
+        let ffi_function = to_ffi_method(kind, movable_types, name_provider)?;
+        Ok(CppFfiItem::Function(ffi_function))
+    };
+
+    if variable.variable_type.is_class() {
+        new_methods.push(create_method(CppFieldAccessorType::ConstRefGetter)?);
+        if !variable.is_const {
+            new_methods.push(create_method(CppFieldAccessorType::MutRefGetter)?);
+        }
+    } else {
+        new_methods.push(create_method(CppFieldAccessorType::CopyGetter)?);
+    }
+    if !variable.is_const {
+        new_methods.push(create_method(CppFieldAccessorType::Setter)?);
+    }
+
+    Ok(new_methods)
+}
+
 fn check_preconditions(item: &CppItem, source_ffi_item: Option<DbItem<&CppFfiItem>>) -> Result<()> {
     match item {
         CppItem::Function(function) => {
+            if function.is_deleted {
+                bail!("function is deleted");
+            }
             if let Some(membership) = &function.member {
                 if membership.visibility == CppVisibility::Private {
                     bail!("function is private");
@@ -1,6 +1,6 @@
 //! Types for handling information about C++ methods.
 
-use crate::cpp_data::{CppPath, CppPathItem, CppVisibility};
+use crate::cpp_data::{CppConstantValue, CppPath, CppPathItem, CppVisibility};
 use crate::cpp_ffi_data::CppCast;
 pub use crate::cpp_operator::{CppOperator, CppOperatorInfo};
 use crate::cpp_type::{CppPointerLikeTypeKind, CppType};
@@ -20,12 +20,26 @@ pub struct CppFunctionArgument {
     pub name: String,
     /// Argument type
     pub argument_type: CppType,
-    /// Flag indicating that the argument has default value and
-    /// therefore can be omitted when calling the method
-    pub has_default_value: bool,
+    /// Default value of the argument. If present, the argument
+    /// can be omitted when calling the method
+    pub default_value: Option<CppArgumentDefaultValue>,
+}
+
+/// Default value of a C++ function argument
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct CppArgumentDefaultValue {
+    /// C++ expression of the default value, as written in the declaration
+    pub text: String,
+    /// Value of the expression if clang was able to evaluate it
+    pub value: Option<CppConstantValue>,
 }
 
 impl CppFunctionArgument {
+    /// Returns true if the argument can be omitted when calling the method
+    pub fn has_default_value(&self) -> bool {
+        self.default_value.is_some()
+    }
+
     /// Generates C++ code for the argument declaration
     pub fn to_cpp_code(&self) -> Result<String> {
         if let CppType::FunctionPointer(..) = self.argument_type {
@@ -40,6 +54,13 @@ impl CppFunctionArgument {
     }
 }
 
+/// Information about a deprecated C++ function
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct CppDeprecation {
+    /// Message provided in the deprecation attribute, if any
+    pub message: Option<String>,
+}
+
 /// Enumerator indicating special cases of C++ methods.
 #[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
 pub enum CppFunctionKind {
@@ -117,6 +138,20 @@ pub struct CppFunction {
     pub arguments: Vec<CppFunctionArgument>,
     /// Whether the argument list is terminated with "..."
     pub allows_variadic_arguments: bool,
+    /// True if the function is declared as `noexcept` and can't throw exceptions
+    pub is_noexcept: bool,
+    /// True if the function is deleted (`= delete`) and can't be called
+    pub is_deleted: bool,
+    /// Information about the deprecation if the function is marked as deprecated
+    pub deprecation: Option<CppDeprecation>,
+    /// True if the function has C language linkage and an external symbol,
+    /// so it can be called from Rust without a C++ wrapper.
+    /// Only detected in plain C mode.
+    pub has_c_linkage: bool,
+    /// True if the function is only declared as a friend inside a class,
+    /// so it's not visible to qualified lookup and can only be found
+    /// by argument-dependent lookup.
+    pub is_hidden_friend: bool,
     pub cast: Option<CppCast>,
     /// C++ code of the method's declaration.
     /// None if the method was not explicitly declared.
@@ -295,6 +330,9 @@ impl CppFunction {
         if self.allows_variadic_arguments {
             s = format!("{} [var args]", s);
         }
+        if self.deprecation.is_some() {
+            s = format!("{} [deprecated]", s);
+        }
         s = format!("{} {}", s, self.return_type.to_cpp_pseudo_code());
         s = format!("{} {}", s, self.path.to_cpp_pseudo_code());
         s = format!(
@@ -306,8 +344,8 @@ impl CppFunction {
                     "{} {}{}",
                     arg.argument_type.to_cpp_pseudo_code(),
                     arg.name,
-                    if arg.has_default_value {
-                        " = …".to_string()
+                    if let Some(default_value) = &arg.default_value {
+                        format!(" = {}", default_value.text)
                     } else {
                         String::new()
                     }
@@ -319,6 +357,12 @@ impl CppFunction {
                 s = format!("{} const", s);
             }
         }
+        if self.is_noexcept {
+            s = format!("{} noexcept", s);
+        }
+        if self.is_deleted {
+            s = format!("{} = delete", s);
+        }
         s.trim().to_string()
     }
 
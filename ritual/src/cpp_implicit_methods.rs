@@ -1,19 +1,188 @@
-use crate::cpp_data::{CppItem, CppPathItem, CppVisibility};
+use crate::cpp_data::{
+    CppBaseSpecifier, CppClassField, CppItem, CppPath, CppPathItem, CppVisibility,
+};
 use crate::cpp_function::{
     CppFunction, CppFunctionArgument, CppFunctionKind, CppFunctionMemberData,
 };
 use crate::cpp_operator::CppOperator;
-use crate::cpp_type::CppType;
+use crate::cpp_type::{CppPointerLikeTypeKind, CppType};
 use crate::database::ItemWithSource;
 use crate::processor::ProcessorData;
 use ritual_common::errors::Result;
+use std::collections::{HashMap, HashSet};
+
+/// Copy operation that can be implicitly declared by the compiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CopyOperation {
+    Constructor,
+    Assignment,
+}
+
+impl CopyOperation {
+    fn matches(self, function: &CppFunction) -> bool {
+        match self {
+            CopyOperation::Constructor => function.is_constructor(),
+            CopyOperation::Assignment => function.operator == Some(CppOperator::Assignment),
+        }
+    }
+}
+
+/// Returns the kind of reference if `function` takes a single reference to `class_path`.
+fn copy_argument_kind(
+    function: &CppFunction,
+    class_path: &CppPath,
+) -> Option<CppPointerLikeTypeKind> {
+    if function.arguments.len() != 1 {
+        return None;
+    }
+    match &function.arguments[0].argument_type {
+        CppType::PointerLike { kind, target, .. }
+            if *kind != CppPointerLikeTypeKind::Pointer
+                && **target == CppType::Class(class_path.clone()) =>
+        {
+            Some(kind.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Returns classes whose copy constructor or copy assignment operator (depending on `operation`)
+/// is deleted, explicitly or implicitly.
+///
+/// The implicitly declared operation is deleted if the class declares a move constructor
+/// or a move assignment operator, or if the same operation is deleted for a base class or
+/// a non-static field. Copy assignment is also deleted if the class has a reference field.
+/// Fields that were not parsed (e.g. because their types are unknown) are not taken into account.
+fn deleted_copy_operations(
+    classes: &[&CppPath],
+    functions: &[&CppFunction],
+    fields: &[&CppClassField],
+    bases: &[&CppBaseSpecifier],
+    operation: CopyOperation,
+) -> HashSet<CppPath> {
+    // `is_deleted` of explicitly declared operations
+    let mut declared = HashMap::new();
+    let mut declares_move = HashSet::new();
+    for &function in functions {
+        let class_path = match function.class_path() {
+            Ok(path) => path,
+            Err(_) => continue,
+        };
+        match copy_argument_kind(function, &class_path) {
+            Some(CppPointerLikeTypeKind::RValueReference) => {
+                if CopyOperation::Constructor.matches(function)
+                    || CopyOperation::Assignment.matches(function)
+                {
+                    declares_move.insert(class_path);
+                }
+            }
+            Some(_) => {
+                if operation.matches(function) {
+                    declared.insert(class_path, function.is_deleted);
+                }
+            }
+            None => {}
+        }
+    }
+
+    let mut deleted: HashSet<CppPath> = declared
+        .iter()
+        .filter(|(_, is_deleted)| **is_deleted)
+        .map(|(path, _)| path.clone())
+        .collect();
+    deleted.extend(
+        declares_move
+            .into_iter()
+            .filter(|path| !declared.contains_key(path)),
+    );
+
+    // deletion propagates from bases and fields to derived and containing classes
+    loop {
+        let mut is_changed = false;
+        for &class_path in classes {
+            if declared.contains_key(class_path) || deleted.contains(class_path) {
+                continue;
+            }
+            let has_deleted_base = bases.iter().any(|base| {
+                &base.derived_class_type == class_path && deleted.contains(&base.base_class_type)
+            });
+            let has_deleted_field = fields
+                .iter()
+                .filter(|field| {
+                    !field.is_static && field.path.parent().ok().as_ref() == Some(class_path)
+                })
+                .any(|field| match &field.field_type {
+                    CppType::Class(path) => deleted.contains(path),
+                    CppType::PointerLike { kind, .. } => {
+                        *kind != CppPointerLikeTypeKind::Pointer
+                            && operation == CopyOperation::Assignment
+                    }
+                    _ => false,
+                });
+            if has_deleted_base || has_deleted_field {
+                deleted.insert(class_path.clone());
+                is_changed = true;
+            }
+        }
+        if !is_changed {
+            break;
+        }
+    }
+    deleted
+}
 
 /// Adds constructors and destructors for every class that does not have explicitly
 /// defined constructor or destructor, allowing to create wrappings for
 /// constructors and destructors implicitly available in C++.
+///
+/// Explicitly deleted methods are recorded by the parser with `is_deleted` flag,
+/// so the implicit method with the same signature is not added for them.
+/// Implicitly deleted copy constructors and copy assignment operators
+/// (see `deleted_copy_operations`) are added with `is_deleted` flag.
 pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
+    // C structs don't have constructors and destructors
+    if data.config.plain_c_mode() {
+        return Ok(());
+    }
     let mut methods = Vec::new();
 
+    let class_paths: Vec<_> = data
+        .db
+        .cpp_items()
+        .filter_map(|item| item.item.as_type_ref())
+        .filter(|type1| type1.kind.is_class())
+        .map(|type1| &type1.path)
+        .collect();
+    let functions: Vec<_> = data
+        .db
+        .cpp_items()
+        .filter_map(|item| item.item.as_function_ref())
+        .collect();
+    let fields: Vec<_> = data
+        .db
+        .cpp_items()
+        .filter_map(|item| item.item.as_field_ref())
+        .collect();
+    let bases: Vec<_> = data
+        .db
+        .cpp_items()
+        .filter_map(|item| item.item.as_base_ref())
+        .collect();
+    let deleted_copy_constructors = deleted_copy_operations(
+        &class_paths,
+        &functions,
+        &fields,
+        &bases,
+        CopyOperation::Constructor,
+    );
+    let deleted_copy_assignments = deleted_copy_operations(
+        &class_paths,
+        &functions,
+        &fields,
+        &bases,
+        CopyOperation::Assignment,
+    );
+
     let classes = data
         .db
         .cpp_items()
@@ -43,6 +212,11 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                 return_type: CppType::Void,
                 arguments: vec![],
                 allows_variadic_arguments: false,
+                is_noexcept: true,
+                is_deleted: false,
+                deprecation: None,
+                has_c_linkage: false,
+                is_hidden_friend: false,
                 cast: None,
                 declaration_code: None,
             };
@@ -67,6 +241,11 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                 return_type: CppType::Void,
                 arguments: vec![],
                 allows_variadic_arguments: false,
+                is_noexcept: false,
+                is_deleted: false,
+                deprecation: None,
+                has_c_linkage: false,
+                is_hidden_friend: false,
                 declaration_code: None,
                 cast: None,
             };
@@ -75,7 +254,7 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
             let copy_arg = CppFunctionArgument {
                 argument_type: CppType::new_reference(true, CppType::Class(class_path.clone())),
                 name: "other".to_string(),
-                has_default_value: false,
+                default_value: None,
             };
 
             let copy_constructor = CppFunction {
@@ -97,6 +276,11 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                 return_type: CppType::Void,
                 arguments: vec![copy_arg.clone()],
                 allows_variadic_arguments: false,
+                is_noexcept: false,
+                is_deleted: deleted_copy_constructors.contains(class_path),
+                deprecation: None,
+                has_c_linkage: false,
+                is_hidden_friend: false,
                 cast: None,
                 declaration_code: None,
             };
@@ -121,6 +305,11 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
                 return_type: CppType::new_reference(false, CppType::Class(class_path.clone())),
                 arguments: vec![copy_arg],
                 allows_variadic_arguments: false,
+                is_noexcept: false,
+                is_deleted: deleted_copy_assignments.contains(class_path),
+                deprecation: None,
+                has_c_linkage: false,
+                is_hidden_friend: false,
                 cast: None,
                 declaration_code: None,
             };
@@ -132,3 +321,111 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn deleted_copy_operations_propagate() {
+    let path = |name: &str| CppPath::from_good_str(name);
+    let function = |name: &str, class: &str, is_assignment: bool, kind: CppPointerLikeTypeKind| {
+        let class_path = path(class);
+        CppFunction {
+            path: class_path.join(CppPathItem::from_good_str(name)),
+            member: Some(CppFunctionMemberData {
+                is_virtual: false,
+                is_pure_virtual: false,
+                is_const: false,
+                is_static: false,
+                visibility: CppVisibility::Public,
+                is_signal: false,
+                is_slot: false,
+                kind: if is_assignment {
+                    CppFunctionKind::Regular
+                } else {
+                    CppFunctionKind::Constructor
+                },
+            }),
+            operator: if is_assignment {
+                Some(CppOperator::Assignment)
+            } else {
+                None
+            },
+            return_type: CppType::Void,
+            arguments: vec![CppFunctionArgument {
+                argument_type: CppType::PointerLike {
+                    kind,
+                    is_const: true,
+                    target: Box::new(CppType::Class(class_path)),
+                },
+                name: "other".to_string(),
+                default_value: None,
+            }],
+            allows_variadic_arguments: false,
+            is_noexcept: false,
+            is_deleted: false,
+            deprecation: None,
+            has_c_linkage: false,
+            is_hidden_friend: false,
+            cast: None,
+            declaration_code: None,
+        }
+    };
+    let field = |name: &str, field_type: CppType| CppClassField {
+        path: path(name),
+        field_type,
+        visibility: CppVisibility::Private,
+        is_static: false,
+    };
+
+    let classes = ["A", "B", "C", "D", "E", "F"]
+        .iter()
+        .map(|name| path(name))
+        .collect::<Vec<_>>();
+    let class_refs = classes.iter().collect::<Vec<_>>();
+    // A(const A&) = delete;
+    let mut a_copy = function("A", "A", false, CppPointerLikeTypeKind::Reference);
+    a_copy.is_deleted = true;
+    // E(E&&);
+    let e_move = function("E", "E", false, CppPointerLikeTypeKind::RValueReference);
+    // F(const F&);
+    let f_copy = function("F", "F", false, CppPointerLikeTypeKind::Reference);
+    let functions = vec![&a_copy, &e_move, &f_copy];
+
+    let fields = vec![
+        field("B::a", CppType::Class(path("A"))),
+        field("D::i", CppType::new_reference(false, CppType::Void)),
+        field("F::a", CppType::Class(path("A"))),
+    ];
+    let field_refs = fields.iter().collect::<Vec<_>>();
+    let base = CppBaseSpecifier {
+        base_class_type: path("B"),
+        base_index: 0,
+        is_virtual: false,
+        visibility: CppVisibility::Public,
+        derived_class_type: path("C"),
+    };
+    let bases = vec![&base];
+
+    let constructors = deleted_copy_operations(
+        &class_refs,
+        &functions,
+        &field_refs,
+        &bases,
+        CopyOperation::Constructor,
+    );
+    let mut constructors = constructors.into_iter().collect::<Vec<_>>();
+    constructors.sort_by_key(|path| path.to_cpp_pseudo_code());
+    assert_eq!(
+        constructors,
+        vec![path("A"), path("B"), path("C"), path("E")]
+    );
+
+    let assignments = deleted_copy_operations(
+        &class_refs,
+        &functions,
+        &field_refs,
+        &bases,
+        CopyOperation::Assignment,
+    );
+    let mut assignments = assignments.into_iter().collect::<Vec<_>>();
+    assignments.sort_by_key(|path| path.to_cpp_pseudo_code());
+    assert_eq!(assignments, vec![path("D"), path("E")]);
+}
@@ -3,6 +3,13 @@ use crate::database::ItemWithSource;
 use crate::processor::ProcessorData;
 use ritual_common::errors::Result;
 
+/// Adds a variant of every function with default arguments for each number of
+/// omitted trailing arguments. The Rust generator disambiguates these overloads,
+/// usually by adding the number of arguments to the name (e.g. `f_1a`).
+///
+/// Default values (`CppFunctionArgument::default_value`) are only used in the documentation
+/// for now. Generating `Option<T>`-style or builder-style Rust functions instead of
+/// the numbered variants is left for a separate change.
 pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     let mut results = Vec::new();
     for item in data.db.cpp_items() {
@@ -12,10 +19,10 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
             continue;
         };
 
-        if function.arguments.iter().any(|arg| arg.has_default_value) {
+        if function.arguments.iter().any(|arg| arg.has_default_value()) {
             let mut function_copy = function.clone();
             while let Some(arg) = function_copy.arguments.pop() {
-                if !arg.has_default_value {
+                if !arg.has_default_value() {
                     break;
                 }
                 results.push(ItemWithSource::new(&item.id, function_copy.clone()));
@@ -1,18 +1,22 @@
 use crate::config::Config;
 use crate::cpp_code_generator::{all_include_directives, write_include_directives};
 use crate::cpp_data::{
-    CppBaseSpecifier, CppClassField, CppEnumValue, CppItem, CppNamespace, CppOriginLocation,
-    CppPath, CppPathItem, CppTypeDeclaration, CppTypeDeclarationKind, CppVisibility,
+    CppBaseSpecifier, CppClassField, CppConstantValue, CppEnumValue, CppGlobalVariable, CppItem,
+    CppMacroConstant, CppNamespace, CppOriginLocation, CppPath, CppPathItem, CppTypeAlias,
+    CppTypeDeclaration, CppTypeDeclarationKind, CppVisibility,
 };
+use crate::cpp_directives::{CppDirective, CppDirectives};
 use crate::cpp_function::{
-    CppFunction, CppFunctionArgument, CppFunctionKind, CppFunctionMemberData,
+    CppArgumentDefaultValue, CppDeprecation, CppFunction, CppFunctionArgument, CppFunctionKind,
+    CppFunctionMemberData,
 };
 use crate::cpp_operator::CppOperator;
 use crate::cpp_type::{
     CppBuiltInNumericType, CppFunctionPointerType, CppPointerLikeTypeKind, CppSpecificNumericType,
-    CppSpecificNumericTypeKind, CppTemplateParameter, CppType,
+    CppSpecificNumericTypeKind, CppTemplateArgumentValue, CppTemplateParameter, CppType,
 };
 use crate::database::ItemId;
+use crate::doc_comments::doc_item_from_comment;
 use crate::processor::ProcessorData;
 use clang::diagnostic::{Diagnostic, Severity};
 use clang::*;
@@ -27,6 +31,7 @@ use ritual_common::file_utils::{
 };
 use ritual_common::target::{current_env, current_target, Env, LibraryTarget};
 use ritual_common::utils::MapIfOk;
+use std::collections::HashMap;
 use std::io::Write;
 use std::mem;
 use std::path::{Path, PathBuf};
@@ -129,7 +134,10 @@ fn get_template_arguments(entity: Entity<'_>) -> Option<Vec<CppType>> {
     let args = entity
         .get_children()
         .into_iter()
-        .filter(|c| c.get_kind() == EntityKind::TemplateTypeParameter)
+        .filter(|c| {
+            c.get_kind() == EntityKind::TemplateTypeParameter
+                || c.get_kind() == EntityKind::NonTypeTemplateParameter
+        })
         .enumerate()
         .map(|(i, c)| {
             CppType::TemplateParameter(CppTemplateParameter {
@@ -146,6 +154,40 @@ fn get_template_arguments(entity: Entity<'_>) -> Option<Vec<CppType>> {
     }
 }
 
+/// Checks that all non-type template parameters of `entity`
+/// have integer or enum types.
+fn check_non_type_template_parameters(entity: Entity<'_>) -> Result<()> {
+    for child in entity.get_children() {
+        if child.get_kind() != EntityKind::NonTypeTemplateParameter {
+            continue;
+        }
+        let type1 = child
+            .get_type()
+            .ok_or_else(|| err_msg("failed to get type of template parameter"))?;
+        match type1.get_canonical_type().get_kind() {
+            TypeKind::Bool
+            | TypeKind::CharS
+            | TypeKind::CharU
+            | TypeKind::SChar
+            | TypeKind::UChar
+            | TypeKind::Short
+            | TypeKind::UShort
+            | TypeKind::Int
+            | TypeKind::UInt
+            | TypeKind::Long
+            | TypeKind::ULong
+            | TypeKind::LongLong
+            | TypeKind::ULongLong
+            | TypeKind::Enum => {}
+            _ => bail!(
+                "Non-type template parameter of type {} is not supported",
+                type1.get_display_name()
+            ),
+        }
+    }
+    Ok(())
+}
+
 fn get_context_template_args(entity: Entity<'_>) -> Vec<CppType> {
     let mut current_entity = entity;
     let mut args = Vec::new();
@@ -183,6 +225,7 @@ fn get_path(entity: Entity<'_>) -> Result<CppPath> {
             EntityKind::ClassDecl
             | EntityKind::ClassTemplate
             | EntityKind::StructDecl
+            | EntityKind::UnionDecl
             | EntityKind::Namespace
             | EntityKind::EnumDecl
             | EntityKind::ClassTemplatePartialSpecialization => {
@@ -202,6 +245,134 @@ fn get_path(entity: Entity<'_>) -> Result<CppPath> {
     Ok(CppPath::from_items(parts))
 }
 
+/// Returns deprecation information if `entity` is marked as deprecated.
+fn get_deprecation(entity: Entity<'_>) -> Option<CppDeprecation> {
+    if entity.get_availability() != Availability::Deprecated {
+        return None;
+    }
+    let message = entity
+        .get_children()
+        .into_iter()
+        .filter(|child| child.get_kind() == EntityKind::UnexposedAttr)
+        .filter_map(|attr| {
+            let tokens = attr
+                .get_range()?
+                .tokenize()
+                .into_iter()
+                .map(|token| token.get_spelling())
+                .collect_vec();
+            deprecation_message(&tokens)
+        })
+        .next();
+    Some(CppDeprecation { message })
+}
+
+/// Extracts the message from tokens of an attribute like
+/// `deprecated("message")` or `QT_DEPRECATED_X("message")`.
+fn deprecation_message(tokens: &[String]) -> Option<String> {
+    let index = tokens
+        .iter()
+        .position(|token| token.to_lowercase().contains("deprecated"))?;
+    match &tokens[index + 1..] {
+        [open, literal, ..] if open == "(" && literal.len() >= 2 && literal.starts_with('"') => {
+            unescape_string_literal(&literal[1..literal.len() - 1]).ok()
+        }
+        _ => None,
+    }
+}
+
+/// Returns default value of a function argument, if any.
+fn get_default_value(argument_entity: Entity<'_>) -> Result<Option<CppArgumentDefaultValue>> {
+    let tokens = argument_entity
+        .get_range()
+        .ok_or_else(|| {
+            format_err!(
+                "failed to get range from argument entity: {:?}",
+                argument_entity
+            )
+        })?
+        .tokenize()
+        .into_iter()
+        .map(|token| token.get_spelling())
+        .collect_vec();
+    if !has_default_value(&tokens) {
+        return Ok(None);
+    }
+    // the default value follows the type, which may also contain expressions
+    // (e.g. template arguments)
+    let expression = argument_entity
+        .get_children()
+        .into_iter()
+        .filter(|child| child.is_expression())
+        .last()
+        .ok_or_else(|| {
+            format_err!(
+                "failed to find default value of argument: {:?}",
+                argument_entity
+            )
+        })?;
+    let expression_tokens = expression
+        .get_range()
+        .ok_or_else(|| format_err!("failed to get range of default value: {:?}", expression))?
+        .tokenize()
+        .into_iter()
+        .map(|token| token.get_spelling())
+        .collect_vec();
+    Ok(Some(CppArgumentDefaultValue {
+        text: join_tokens(&expression_tokens),
+        value: evaluate_constant(expression),
+    }))
+}
+
+/// Checks if tokens of an argument declaration like `int x = 42`
+/// contain a default value.
+fn has_default_value(tokens: &[String]) -> bool {
+    for token in tokens {
+        if token == "=" {
+            return true;
+        }
+        if token == "{" {
+            // clang sometimes reports incorrect range for arguments
+            return false;
+        }
+    }
+    false
+}
+
+/// Joins tokens of a C++ expression, only putting spaces where
+/// they are required to separate tokens and after commas.
+fn join_tokens(tokens: &[impl AsRef<str>]) -> String {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_' || c == '"' || c == '\'';
+    let mut result = String::new();
+    for token in tokens {
+        let token = token.as_ref();
+        let needs_space = match (result.chars().last(), token.chars().next()) {
+            (Some(','), Some(_)) => true,
+            (Some(last), Some(first)) => is_word_char(last) && is_word_char(first),
+            _ => false,
+        };
+        if needs_space {
+            result.push(' ');
+        }
+        result.push_str(token);
+    }
+    result
+}
+
+/// Evaluates `entity` as a compile-time constant if clang is able to do it.
+fn evaluate_constant(entity: Entity<'_>) -> Option<CppConstantValue> {
+    match entity.evaluate()? {
+        EvaluationResult::SignedInteger(value) => Some(CppConstantValue::Integer(value)),
+        // values that don't fit in `i64` are stored as their bit pattern
+        EvaluationResult::UnsignedInteger(value) => Some(CppConstantValue::Integer(value as i64)),
+        EvaluationResult::Float(value) => Some(CppConstantValue::Float(value)),
+        EvaluationResult::String(value) => Some(CppConstantValue::String(
+            value.to_string_lossy().into_owned(),
+        )),
+        _ => None,
+    }
+}
+
 fn get_full_name_display(entity: Entity<'_>) -> String {
     match get_path(entity) {
         Ok(name) => name.to_cpp_pseudo_code(),
@@ -209,6 +380,35 @@ fn get_full_name_display(entity: Entity<'_>) -> String {
     }
 }
 
+/// Returns annotations specified with `annotate` attributes on `entity`.
+fn get_annotations(entity: Entity<'_>) -> Vec<String> {
+    entity
+        .get_children()
+        .into_iter()
+        .filter(|child| child.get_kind() == EntityKind::AnnotateAttr)
+        .filter_map(|child| child.get_display_name())
+        .collect()
+}
+
+/// Returns true if `entity` has the `ritual:skip` directive.
+fn has_skip_directive(entity: Entity<'_>) -> bool {
+    get_annotations(entity)
+        .iter()
+        .any(|annotation| CppDirective::parse(annotation).ok() == Some(Some(CppDirective::Skip)))
+}
+
+/// Collects generation directives specified for `entity`.
+/// Invalid directives are reported and ignored.
+fn get_directives(entity: Entity<'_>) -> CppDirectives {
+    let mut directives = CppDirectives::default();
+    for annotation in get_annotations(entity) {
+        if let Err(err) = directives.add_annotation(&annotation) {
+            warn!("{}: {}", get_full_name_display(entity), err);
+        }
+    }
+    directives
+}
+
 #[cfg(test)]
 fn init_clang() -> Result<Clang> {
     for _ in 0..12000 {
@@ -241,11 +441,12 @@ fn run_clang<R, F: FnMut(Entity<'_>) -> Result<R>>(
 
     let global_file_path = tmp_path.join("global.h");
     let mut global_file = create_file(&global_file_path)?;
-    writeln!(
-        global_file,
-        "{}",
-        include_str!("../templates/c_lib/global.h"),
-    )?;
+    let global_header = if config.plain_c_mode() {
+        include_str!("../templates/c_lib/global_c.h")
+    } else {
+        include_str!("../templates/c_lib/global.h")
+    };
+    writeln!(global_file, "{}", global_header)?;
     write_include_directives(&mut global_file, &all_include_directives(config)?)?;
     drop(global_file);
 
@@ -272,7 +473,10 @@ fn run_clang<R, F: FnMut(Entity<'_>) -> Result<R>>(
         "-Xclang".to_string(),
         "-detailed-preprocessing-record".to_string(),
     ];
-    if current_env() != Env::Msvc {
+    if config.plain_c_mode() {
+        args.push("-x".to_string());
+        args.push("c".to_string());
+    } else if current_env() != Env::Msvc {
         args.push("-std=c++11".to_string());
     }
     args.extend_from_slice(config.cpp_parser_arguments());
@@ -335,10 +539,26 @@ fn run_clang<R, F: FnMut(Entity<'_>) -> Result<R>>(
     result
 }
 
+/// Returns files included in the translation unit, excluding temporary files
+/// in `tmp_path`.
+fn included_files(translation_unit: Entity<'_>, tmp_path: &Path) -> Vec<PathBuf> {
+    translation_unit
+        .get_children()
+        .into_iter()
+        .filter(|entity| entity.get_kind() == EntityKind::InclusionDirective)
+        .filter_map(|entity| entity.get_file())
+        .map(|file| file.get_path())
+        .filter(|path| !path.starts_with(tmp_path))
+        .collect()
+}
+
 /// Runs the parser on specified data.
 pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     debug!("clang version: {}", get_version());
     debug!("Initializing clang");
+    let tmp_path = data
+        .workspace
+        .crate_tmp_path(data.config.crate_properties().name())?;
     let mut parser = CppParser {
         current_target_paths: data
             .config
@@ -351,13 +571,14 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     };
     parser
         .current_target_paths
-        .push(canonicalize(parser.data.workspace.tmp_path())?.join("extra"));
-    run_clang(
-        &parser.data.config,
-        &parser.data.workspace.tmp_path(),
-        None,
-        |translation_unit| parser.parse(translation_unit),
-    )?;
+        .push(canonicalize(&tmp_path)?.join("extra"));
+    run_clang(&parser.data.config, &tmp_path, None, |translation_unit| {
+        parser
+            .data
+            .db
+            .add_step_input_files(included_files(translation_unit, &tmp_path));
+        parser.parse(translation_unit)
+    })?;
 
     Ok(())
 }
@@ -380,17 +601,24 @@ pub fn parse_generated_items(data: &mut ProcessorData<'_>) -> Result<()> {
             continue;
         }
         let code = ffi_item.item.source_item_cpp_code(data.db)?;
+        let tmp_path = data
+            .workspace
+            .crate_tmp_path(data.config.crate_properties().name())?;
         let mut parser = CppParser {
-            current_target_paths: vec![canonicalize(data.workspace.tmp_path())?.join("1.cpp")],
+            current_target_paths: vec![canonicalize(&tmp_path)?.join("1.cpp")],
             source_id: Some(ffi_item_id),
             data,
             output: Default::default(),
         };
         run_clang(
             &parser.data.config,
-            &parser.data.workspace.tmp_path(),
+            &tmp_path,
             Some(code),
             |translation_unit| {
+                parser
+                    .data
+                    .db
+                    .add_step_input_files(included_files(translation_unit, &tmp_path));
                 parser.parse(translation_unit)?;
                 Ok(())
             },
@@ -402,11 +630,28 @@ pub fn parse_generated_items(data: &mut ProcessorData<'_>) -> Result<()> {
 impl CppParser<'_, '_> {
     fn add_output(
         &mut self,
+        entity: Entity<'_>,
         include_file: String,
         origin_location: CppOriginLocation,
         item: CppItem,
     ) -> Result<()> {
+        let directives = get_directives(entity);
+        if directives.skip {
+            trace!("skipping {} (ritual:skip directive)", item);
+            return Ok(());
+        }
         if let Some(id) = self.data.add_cpp_item(self.source_id.clone(), item)? {
+            if !directives.is_empty() {
+                self.data.db.add_directives_item(id.clone(), directives);
+            }
+            if self.data.config.parse_doc_comments() {
+                if let Some(doc_item) = entity
+                    .get_comment()
+                    .and_then(|comment| doc_item_from_comment(&comment))
+                {
+                    self.data.db.add_doc_item(id.clone(), doc_item);
+                }
+            }
             self.output.0.push(CppParserOutputItem {
                 include_file,
                 origin_location,
@@ -429,6 +674,46 @@ impl CppParser<'_, '_> {
             .find(|i| f(i))
     }
 
+    /// Parses value of the non-type template argument at `index`
+    /// of a class template specialization `declaration`.
+    fn parse_template_argument_value(
+        &self,
+        declaration: Entity<'_>,
+        index: usize,
+    ) -> Result<CppType> {
+        let template = declaration
+            .get_template()
+            .ok_or_else(|| err_msg("failed to get template of the specialization"))?;
+        let parameter = template
+            .get_children()
+            .into_iter()
+            .filter(|c| {
+                c.get_kind() == EntityKind::TemplateTypeParameter
+                    || c.get_kind() == EntityKind::NonTypeTemplateParameter
+            })
+            .nth(index)
+            .ok_or_else(|| format_err!("template parameter not found: {}", index))?;
+        if parameter.get_kind() != EntityKind::NonTypeTemplateParameter {
+            bail!("expected a non-type template parameter at index {}", index);
+        }
+        let parameter_type = parameter
+            .get_type()
+            .ok_or_else(|| err_msg("failed to get type of template parameter"))?;
+        let value_type = self.parse_type(parameter_type, &[])?;
+
+        let arguments = declaration
+            .get_template_arguments()
+            .ok_or_else(|| err_msg("failed to get template arguments"))?;
+        let value = match arguments.get(index) {
+            Some(TemplateArgument::Integral(value, _)) => *value,
+            argument => bail!("unsupported template argument: {:?}", argument),
+        };
+        Ok(CppType::TemplateArgumentValue(CppTemplateArgumentValue {
+            value_type: Box::new(value_type),
+            value,
+        }))
+    }
+
     /// Attempts to parse an unexposed type, i.e. a type the used `clang` API
     /// is not able to describe. Either `type1` or `string` must be specified,
     /// and both may be specified at the same time.
@@ -459,6 +744,7 @@ impl CppParser<'_, '_> {
                 if declaration.get_kind() == EntityKind::ClassDecl
                     || declaration.get_kind() == EntityKind::ClassTemplate
                     || declaration.get_kind() == EntityKind::StructDecl
+                    || declaration.get_kind() == EntityKind::UnionDecl
                 {
                     if declaration
                         .get_accessibility()
@@ -577,16 +863,14 @@ impl CppParser<'_, '_> {
             self.find_type(|x| x.path.to_cpp_code().ok().as_ref() == Some(&name))
         {
             let path = CppPath::from_str(&name)?;
-            if let Some(hook) = self.data.config.cpp_parser_path_hook() {
-                if !hook(&path)? {
-                    bail!("blacklisted path: {}", path.to_cpp_pseudo_code());
-                }
+            if !self.data.config.check_cpp_parser_path(&path)? {
+                bail!("blacklisted path: {}", path.to_cpp_pseudo_code());
             }
             match type_data.kind {
                 CppTypeDeclarationKind::Enum { .. } => {
                     return Ok(CppType::Enum { path });
                 }
-                CppTypeDeclarationKind::Class { .. } => {
+                CppTypeDeclarationKind::Class { .. } | CppTypeDeclarationKind::Union { .. } => {
                     return Ok(CppType::Class(path));
                 }
             }
@@ -701,10 +985,11 @@ impl CppParser<'_, '_> {
             TypeKind::Enum => {
                 if let Some(declaration) = type1.get_declaration() {
                     let path = get_path(declaration)?;
-                    if let Some(hook) = self.data.config.cpp_parser_path_hook() {
-                        if !hook(&path)? {
-                            bail!("blacklisted path: {}", path.to_cpp_pseudo_code());
-                        }
+                    if has_skip_directive(declaration) {
+                        bail!("skipped path: {}", path.to_cpp_pseudo_code());
+                    }
+                    if !self.data.config.check_cpp_parser_path(&path)? {
+                        bail!("blacklisted path: {}", path.to_cpp_pseudo_code());
                     }
                     Ok(CppType::Enum { path })
                 } else {
@@ -724,13 +1009,14 @@ impl CppParser<'_, '_> {
                         );
                     }
                     let mut declaration_name = get_path(declaration)?;
-                    if let Some(hook) = self.data.config.cpp_parser_path_hook() {
-                        if !hook(&declaration_name)? {
-                            bail!(
-                                "blacklisted path: {}",
-                                declaration_name.to_cpp_pseudo_code()
-                            );
-                        }
+                    if has_skip_directive(declaration) {
+                        bail!("skipped path: {}", declaration_name.to_cpp_pseudo_code());
+                    }
+                    if !self.data.config.check_cpp_parser_path(&declaration_name)? {
+                        bail!(
+                            "blacklisted path: {}",
+                            declaration_name.to_cpp_pseudo_code()
+                        );
                     }
                     let template_arguments = match type1.get_template_argument_types() {
                         None => None,
@@ -739,9 +1025,11 @@ impl CppParser<'_, '_> {
                             if arg_types.is_empty() {
                                 bail!("arg_types is empty");
                             }
-                            for arg_type in arg_types {
+                            for (index, arg_type) in arg_types.into_iter().enumerate() {
                                 match arg_type {
-                                    None => bail!("Template argument is None"),
+                                    None => r.push(
+                                        self.parse_template_argument_value(declaration, index)?,
+                                    ),
                                     Some(arg_type) => {
                                         match self.parse_type(arg_type, context_template_args) {
                                             Ok(parsed_type) => r.push(parsed_type),
@@ -967,17 +1255,18 @@ impl CppParser<'_, '_> {
     fn parse_function(&mut self, entity: Entity<'_>) -> Result<()> {
         let class_name = match entity.get_semantic_parent() {
             Some(p) => match p.get_kind() {
-                EntityKind::ClassDecl | EntityKind::ClassTemplate | EntityKind::StructDecl => {
-                    match get_path(p) {
-                        Ok(class_name) => Some(class_name),
-                        Err(msg) => {
-                            bail!(
-                                "function parent is a class but it doesn't have a name: {}",
-                                msg
-                            );
-                        }
+                EntityKind::ClassDecl
+                | EntityKind::ClassTemplate
+                | EntityKind::StructDecl
+                | EntityKind::UnionDecl => match get_path(p) {
+                    Ok(class_name) => Some(class_name),
+                    Err(msg) => {
+                        bail!(
+                            "function parent is a class but it doesn't have a name: {}",
+                            msg
+                        );
                     }
-                }
+                },
                 EntityKind::ClassTemplatePartialSpecialization => {
                     bail!("this function is part of a template partial specialization");
                 }
@@ -1044,31 +1333,11 @@ impl CppParser<'_, '_> {
                         clang_type.get_display_name()
                     )
                 })?;
-            let mut has_default_value = false;
-            for token in argument_entity
-                .get_range()
-                .ok_or_else(|| {
-                    format_err!(
-                        "failed to get range from argument entity: {:?}",
-                        argument_entity
-                    )
-                })?
-                .tokenize()
-            {
-                let spelling = token.get_spelling();
-                if spelling == "=" {
-                    has_default_value = true;
-                    break;
-                }
-                if spelling == "{" {
-                    // clang sometimes reports incorrect range for arguments
-                    break;
-                }
-            }
+            let default_value = get_default_value(argument_entity)?;
             arguments.push(CppFunctionArgument {
                 name,
                 argument_type,
-                has_default_value,
+                default_value,
             });
         }
 
@@ -1091,13 +1360,7 @@ impl CppParser<'_, '_> {
 
         let template_arguments = match entity.get_kind() {
             EntityKind::FunctionTemplate => {
-                if entity
-                    .get_children()
-                    .into_iter()
-                    .any(|c| c.get_kind() == EntityKind::NonTypeTemplateParameter)
-                {
-                    bail!("Non-type template parameter is not supported");
-                }
+                check_non_type_template_parameters(entity)?;
                 get_template_arguments(entity)
             }
             _ => None,
@@ -1219,6 +1482,30 @@ impl CppParser<'_, '_> {
             Some(token_strings.join(" "))
         };
 
+        // libclang doesn't report the value of `noexcept(expr)`,
+        // so such functions are assumed to throw
+        let is_noexcept = match entity.get_exception_specification() {
+            Some(ExceptionSpecification::BasicNoexcept)
+            | Some(ExceptionSpecification::DynamicNone) => true,
+            _ => false,
+        };
+
+        // A function first declared as a friend inside a class belongs to the enclosing
+        // namespace, but it's only visible to argument-dependent lookup.
+        let is_hidden_friend = class_name.is_none()
+            && entity
+                .get_canonical_entity()
+                .get_lexical_parent()
+                .map_or(false, |parent| match parent.get_kind() {
+                    EntityKind::ClassDecl | EntityKind::StructDecl | EntityKind::UnionDecl => true,
+                    _ => false,
+                });
+
+        // In plain C mode, all functions have C language linkage, but only
+        // functions with external linkage have a symbol that can be called directly.
+        let has_c_linkage =
+            self.data.config.plain_c_mode() && entity.get_linkage() == Some(Linkage::External);
+
         let function = CppFunction {
             path: name_with_namespace,
             operator: method_operator,
@@ -1247,12 +1534,19 @@ impl CppParser<'_, '_> {
             },
             arguments,
             allows_variadic_arguments,
+            is_noexcept,
+            // deleted functions are reported as unavailable
+            is_deleted: entity.get_availability() == Availability::Unavailable,
+            deprecation: get_deprecation(entity),
+            has_c_linkage,
+            is_hidden_friend,
             return_type: return_type_parsed,
             cast: None,
             declaration_code,
         };
 
         self.add_output(
+            entity,
             self.entity_include_file(entity)?,
             get_origin_location(entity)?,
             CppItem::Function(function),
@@ -1271,11 +1565,41 @@ impl CppParser<'_, '_> {
             )
         })?;
         let enum_name = get_path(entity)?;
+        let underlying_type = entity
+            .get_enum_underlying_type()
+            .ok_or_else(|| err_msg("failed to get enum underlying type"))?;
+        let mut underlying_type = self
+            .parse_type(underlying_type, &[])
+            .with_context(|_| err_msg("failed to parse enum underlying type"))?;
+        // if the underlying type is not specified, it's chosen by the compiler:
+        // MSVC always uses `int`, and other compilers use `unsigned int` if no values
+        // are negative. `int` is used in this case if all values fit in it,
+        // so that the enum is compatible with APIs that take its values as `int`.
+        if !entity.is_scoped()
+            && underlying_type == CppType::BuiltInNumeric(CppBuiltInNumericType::UInt)
+        {
+            let fits_in_int = entity
+                .get_children()
+                .into_iter()
+                .filter(|child| child.get_kind() == EntityKind::EnumConstantDecl)
+                .all(|child| {
+                    child
+                        .get_enum_constant_value()
+                        .map_or(false, |(_, value)| value <= i32::max_value() as u64)
+                });
+            if fits_in_int {
+                underlying_type = CppType::BuiltInNumeric(CppBuiltInNumericType::Int);
+            }
+        }
         self.add_output(
+            entity,
             include_file.clone(),
             get_origin_location(entity)?,
             CppItem::Type(CppTypeDeclaration {
-                kind: CppTypeDeclarationKind::Enum,
+                kind: CppTypeDeclarationKind::Enum {
+                    underlying_type,
+                    is_scoped: entity.is_scoped(),
+                },
                 path: enum_name.clone(),
             }),
         )?;
@@ -1289,6 +1613,7 @@ impl CppParser<'_, '_> {
                     .get_name()
                     .ok_or_else(|| err_msg("failed to get name of enum variant"))?;
                 self.add_output(
+                    child,
                     include_file.clone(),
                     get_origin_location(child)?,
                     CppItem::EnumValue(CppEnumValue {
@@ -1316,6 +1641,7 @@ impl CppParser<'_, '_> {
             .parse_type(field_clang_type, &get_context_template_args(entity))
             .with_context(|_| err_msg("failed to parse field type"))?;
         self.add_output(
+            entity,
             include_file,
             get_origin_location(entity)?,
             CppItem::ClassField(CppClassField {
@@ -1333,6 +1659,82 @@ impl CppParser<'_, '_> {
         Ok(())
     }
 
+    /// Parses a variable `entity` declared at namespace scope.
+    fn parse_global_variable(&mut self, entity: Entity<'_>) -> Result<()> {
+        let include_file = self
+            .entity_include_file(entity)
+            .with_context(|_| err_msg("Origin of global variable is unknown"))?;
+        let clang_type = entity
+            .get_type()
+            .ok_or_else(|| err_msg("failed to get variable type"))?;
+        let variable_type = self
+            .parse_type(clang_type, &[])
+            .with_context(|_| err_msg("failed to parse variable type"))?;
+        let is_const = clang_type.is_const_qualified();
+
+        // Only constants of arithmetic types can be turned into Rust constants.
+        // Mutable variables may change at runtime, so they are always
+        // exposed through accessors.
+        let is_arithmetic = match &variable_type {
+            CppType::BuiltInNumeric(_) | CppType::SpecificNumeric(_) => true,
+            _ => false,
+        };
+        let value = if is_const && is_arithmetic {
+            evaluate_constant(entity)
+        } else {
+            None
+        };
+
+        self.add_output(
+            entity,
+            include_file,
+            get_origin_location(entity)?,
+            CppItem::GlobalVariable(CppGlobalVariable {
+                path: get_path(entity)?,
+                variable_type,
+                is_const,
+                value,
+            }),
+        )?;
+        Ok(())
+    }
+
+    /// Parses a typedef or a `using` alias `entity`.
+    fn parse_type_alias(&mut self, entity: Entity<'_>) -> Result<()> {
+        let include_file = self
+            .entity_include_file(entity)
+            .with_context(|_| err_msg("Origin of type alias is unknown"))?;
+        let path = get_path(entity)?;
+        if self
+            .parse_special_typedef(&path.to_templateless_string())
+            .is_some()
+        {
+            bail!("type alias is handled as a special typedef");
+        }
+        let underlying_type = entity
+            .get_typedef_underlying_type()
+            .ok_or_else(|| err_msg("failed to get underlying type"))?;
+        let target_type = self
+            .parse_type(underlying_type, &get_context_template_args(entity))
+            .with_context(|_| err_msg("failed to parse underlying type"))?;
+        if target_type.is_or_contains_template_parameter() {
+            bail!("type aliases containing template parameters are not supported");
+        }
+        if let CppType::Class(class_path) | CppType::Enum { path: class_path } = &target_type {
+            if class_path == &path {
+                // `typedef struct A {} A;`
+                return Ok(());
+            }
+        }
+        self.add_output(
+            entity,
+            include_file,
+            get_origin_location(entity)?,
+            CppItem::TypeAlias(CppTypeAlias { path, target_type }),
+        )?;
+        Ok(())
+    }
+
     // we pass parent manually because both lexical and semantic parent are missing for these
     // entities for some reason
     fn parse_class_base(
@@ -1349,6 +1751,7 @@ impl CppParser<'_, '_> {
             .with_context(|_| "Can't parse base class type")?;
         if let CppType::Class(base_type) = &base_type {
             self.add_output(
+                entity,
                 self.entity_include_file(entity)?,
                 get_origin_location(entity).unwrap(),
                 CppItem::ClassBase(CppBaseSpecifier {
@@ -1369,7 +1772,7 @@ impl CppParser<'_, '_> {
         Ok(())
     }
 
-    /// Parses a class or a struct `entity`.
+    /// Parses a class, a struct or a union `entity`.
     fn parse_class(&mut self, entity: Entity<'_>) -> Result<()> {
         let include_file = self.entity_include_file(entity).with_context(|_| {
             format!(
@@ -1381,13 +1784,7 @@ impl CppParser<'_, '_> {
         let full_name = get_path(entity)?;
         let template_arguments = get_template_arguments(entity);
         if entity.get_kind() == EntityKind::ClassTemplate {
-            if entity
-                .get_children()
-                .into_iter()
-                .any(|c| c.get_kind() == EntityKind::NonTypeTemplateParameter)
-            {
-                bail!("Non-type template parameter is not supported");
-            }
+            check_non_type_template_parameters(entity)?;
 
             if template_arguments.is_none() {
                 dump_entity(entity, 0);
@@ -1396,9 +1793,14 @@ impl CppParser<'_, '_> {
         } else if template_arguments.is_some() {
             bail!("unexpected template arguments");
         }
+        // In plain C mode, fields determine the layout of the generated Rust struct,
+        // so they are only added if all of them can be represented.
+        let parse_fields = !self.data.config.plain_c_mode() || self.has_known_c_layout(entity);
         let mut current_base_index = 0;
         for child in entity.get_children() {
-            if child.get_kind() == EntityKind::FieldDecl || child.get_kind() == EntityKind::VarDecl
+            if parse_fields
+                && (child.get_kind() == EntityKind::FieldDecl
+                    || child.get_kind() == EntityKind::VarDecl)
             {
                 if let Err(err) = self.parse_class_field(child, &full_name) {
                     debug!(
@@ -1419,21 +1821,55 @@ impl CppParser<'_, '_> {
                 }
                 current_base_index += 1;
             }
-            if child.get_kind() == EntityKind::NonTypeTemplateParameter {
-                bail!("Non-type template parameter is not supported");
-            }
         }
         self.add_output(
+            entity,
             include_file,
             get_origin_location(entity).unwrap(),
             CppItem::Type(CppTypeDeclaration {
-                kind: CppTypeDeclarationKind::Class,
+                kind: if entity.get_kind() == EntityKind::UnionDecl {
+                    CppTypeDeclarationKind::Union {
+                        is_trivially_copyable: entity.get_type().map_or(false, |t| t.is_pod()),
+                    }
+                } else {
+                    CppTypeDeclarationKind::Class
+                },
                 path: full_name,
             }),
         )?;
         Ok(())
     }
 
+    /// Returns true if all fields of the C struct or union `entity`
+    /// can be represented in a `#[repr(C)]` Rust type.
+    fn has_known_c_layout(&self, entity: Entity<'_>) -> bool {
+        entity.get_children().into_iter().all(|child| {
+            if child.get_kind() != EntityKind::FieldDecl {
+                return true;
+            }
+            if child.is_bit_field() || has_skip_directive(child) {
+                return false;
+            }
+            let field_type = match child.get_type() {
+                Some(field_type) => field_type,
+                None => return false,
+            };
+            if self.parse_type(field_type, &[]).is_err() {
+                return false;
+            }
+            // nested structs stored by value must also have a known layout
+            let canonical_type = field_type.get_canonical_type();
+            if canonical_type.get_kind() == TypeKind::Record {
+                canonical_type
+                    .get_declaration()
+                    .and_then(|declaration| declaration.get_definition())
+                    .map_or(false, |definition| self.has_known_c_layout(definition))
+            } else {
+                true
+            }
+        })
+    }
+
     /// Determines file path of the include file this `entity` is located in.
     fn entity_include_path(&self, entity: Entity<'_>) -> Result<PathBuf> {
         if let Some(location) = entity.get_location() {
@@ -1457,29 +1893,34 @@ impl CppParser<'_, '_> {
         Ok(os_str_to_str(file_name)?.to_string())
     }
 
-    /// Returns false if this `entity` was blacklisted in some way.
-    fn should_process_entity(&self, entity: Entity<'_>) -> Result<bool> {
-        if entity.get_kind() == EntityKind::TranslationUnit {
-            return Ok(true);
-        }
+    /// Returns true if `entity` is located within the target include paths.
+    fn is_in_target_paths(&self, entity: Entity<'_>) -> Result<bool> {
         if let Ok(file_path) = self.entity_include_path(entity) {
             let file_path = canonicalize(Path::new(&file_path))?;
-            if !self.current_target_paths.is_empty()
-                && !self
+            Ok(self.current_target_paths.is_empty()
+                || self
                     .current_target_paths
                     .iter()
-                    .any(|x| file_path.starts_with(x))
-            {
-                return Ok(false);
-            }
+                    .any(|x| file_path.starts_with(x)))
         } else {
+            Ok(false)
+        }
+    }
+
+    /// Returns false if this `entity` was blacklisted in some way.
+    fn should_process_entity(&self, entity: Entity<'_>) -> Result<bool> {
+        if entity.get_kind() == EntityKind::TranslationUnit {
+            return Ok(true);
+        }
+        if !self.is_in_target_paths(entity)? {
+            return Ok(false);
+        }
+        if has_skip_directive(entity) {
             return Ok(false);
         }
         if let Ok(full_name) = get_path(entity) {
-            if let Some(hook) = self.data.config.cpp_parser_path_hook() {
-                if !hook(&full_name)? {
-                    return Ok(false);
-                }
+            if !self.data.config.check_cpp_parser_path(&full_name)? {
+                return Ok(false);
             }
         } else {
             return Ok(false);
@@ -1492,12 +1933,124 @@ impl CppParser<'_, '_> {
         self.parse_types(entity)?;
         debug!("Parsing functions");
         self.parse_functions(entity)?;
+        if self.data.config.parse_macro_constants() {
+            debug!("Parsing macros");
+            self.parse_macros(entity)?;
+        }
         for hook in self.data.config.after_cpp_parser_hooks() {
             hook(self.data, &self.output)?;
         }
         Ok(())
     }
 
+    /// Parses object-like macros defined as literals in translation unit `entity`.
+    fn parse_macros(&mut self, entity: Entity<'_>) -> Result<()> {
+        let mut header_namespaces = HashMap::new();
+        for child in entity.get_children() {
+            if child.get_kind() != EntityKind::MacroDefinition
+                || child.is_builtin_macro()
+                || child.is_function_like_macro()
+            {
+                continue;
+            }
+            if !self.is_in_target_paths(child)? {
+                continue;
+            }
+            if let Err(error) = self.parse_macro(child, &mut header_namespaces) {
+                trace!(
+                    "skipping macro {}: {}",
+                    child.get_name().unwrap_or_default(),
+                    error
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the namespace that contains all declarations parsed from `include_file`
+    /// or `None` if there are declarations in multiple namespaces or in the global namespace.
+    fn header_namespace(&self, include_file: &str) -> Result<Option<CppPath>> {
+        let mut namespaces = Vec::new();
+        for output_item in &self.output.0 {
+            if output_item.include_file != include_file {
+                continue;
+            }
+            let item = self.data.db.cpp_item(&output_item.id)?;
+            if let CppItem::Namespace(_) = item.item {
+                // namespace items are only added for the first header that declares them
+                continue;
+            }
+            let path = match item.item.path() {
+                Some(path) => path,
+                None => continue,
+            };
+            let top_level = CppPath::from_item(path.items()[0].clone());
+            let is_namespace = path.items().len() > 1
+                && self.data.db.all_cpp_items().any(|item| {
+                    item.item
+                        .as_namespace_ref()
+                        .map_or(false, |namespace| namespace.path == top_level)
+                });
+            let namespace = if is_namespace { Some(top_level) } else { None };
+            if !namespaces.contains(&namespace) {
+                namespaces.push(namespace);
+            }
+        }
+        if namespaces.len() == 1 {
+            Ok(namespaces.pop().unwrap())
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Parses a macro definition `entity`.
+    /// `header_namespaces` caches namespaces of headers (see `header_namespace`).
+    fn parse_macro(
+        &mut self,
+        entity: Entity<'_>,
+        header_namespaces: &mut HashMap<String, Option<CppPath>>,
+    ) -> Result<()> {
+        let name = entity
+            .get_name()
+            .ok_or_else(|| err_msg("failed to get macro name"))?;
+        let path = CppPath::from_good_str(&name);
+        if !self.data.config.check_cpp_parser_path(&path)? {
+            return Ok(());
+        }
+        let tokens = entity
+            .get_range()
+            .ok_or_else(|| err_msg("failed to get macro range"))?
+            .tokenize()
+            .into_iter()
+            .map(|token| token.get_spelling())
+            .collect_vec();
+        // the first token is the macro name
+        let (value_type, value) = parse_macro_literal(tokens.get(1..).unwrap_or(&[]))?;
+        let origin_location = get_origin_location(entity)?;
+        let include_file = self.entity_include_file(entity)?;
+        let namespace = match header_namespaces.get(&include_file) {
+            Some(namespace) => namespace.clone(),
+            None => {
+                let namespace = self.header_namespace(&include_file)?;
+                header_namespaces.insert(include_file.clone(), namespace.clone());
+                namespace
+            }
+        };
+        self.add_output(
+            entity,
+            include_file,
+            origin_location.clone(),
+            CppItem::MacroConstant(CppMacroConstant {
+                path,
+                value_type,
+                value,
+                origin_location,
+                namespace,
+            }),
+        )?;
+        Ok(())
+    }
+
     /// Parses type declarations in translation unit `entity`
     /// and saves them to `self`.
     fn parse_types(&mut self, entity: Entity<'_>) -> Result<()> {
@@ -1520,12 +2073,18 @@ impl CppParser<'_, '_> {
                     }
                 }
             }
-            EntityKind::ClassDecl | EntityKind::ClassTemplate | EntityKind::StructDecl => {
+            EntityKind::ClassDecl
+            | EntityKind::ClassTemplate
+            | EntityKind::StructDecl
+            | EntityKind::UnionDecl => {
                 if entity.get_accessibility() == Some(Accessibility::Private) {
                     return Ok(()); // skipping private stuff
                 }
+                // C libraries often declare opaque structs that are only used through pointers
+                let is_opaque_c_struct =
+                    self.data.config.plain_c_mode() && entity.get_definition().is_none();
                 let ok = entity.get_name().is_some() && // not an anonymous struct
-                    entity.is_definition() && // not a forward declaration
+                    (entity.is_definition() || is_opaque_c_struct) && // not a forward declaration
                     entity.get_template().is_none(); // not a template specialization
                 if ok {
                     if let Err(error) = self.parse_class(entity) {
@@ -1541,6 +2100,7 @@ impl CppParser<'_, '_> {
             EntityKind::Namespace => match get_path(entity) {
                 Ok(path) => {
                     self.add_output(
+                        entity,
                         self.entity_include_file(entity)?,
                         get_origin_location(entity).unwrap(),
                         CppItem::Namespace(CppNamespace { path }),
@@ -1555,6 +2115,7 @@ impl CppParser<'_, '_> {
             | EntityKind::Namespace
             | EntityKind::StructDecl
             | EntityKind::ClassDecl
+            | EntityKind::UnionDecl
             | EntityKind::UnexposedDecl
             | EntityKind::ClassTemplate => {
                 for c in entity.get_children() {
@@ -1588,6 +2149,37 @@ impl CppParser<'_, '_> {
                     trace!("entity: {:?}", entity);
                 }
             }
+            EntityKind::TypedefDecl | EntityKind::TypeAliasDecl => {
+                if entity.get_accessibility().unwrap_or(Accessibility::Public)
+                    == Accessibility::Public
+                {
+                    if let Err(error) = self.parse_type_alias(entity) {
+                        debug!(
+                            "failed to parse type alias: {}: {}",
+                            get_full_name_display(entity),
+                            error
+                        );
+                        trace!("entity: {:?}", entity);
+                    }
+                }
+            }
+            EntityKind::VarDecl => {
+                // static class members are handled by `parse_class`
+                let is_global = entity.get_semantic_parent().map_or(false, |parent| {
+                    parent.get_kind() == EntityKind::TranslationUnit
+                        || parent.get_kind() == EntityKind::Namespace
+                });
+                if is_global {
+                    if let Err(error) = self.parse_global_variable(entity) {
+                        debug!(
+                            "failed to parse global variable: {}: {}",
+                            get_full_name_display(entity),
+                            error
+                        );
+                        trace!("entity: {:?}", entity);
+                    }
+                }
+            }
             EntityKind::StructDecl
             | EntityKind::ClassDecl
             | EntityKind::ClassTemplate
@@ -1623,16 +2215,268 @@ impl CppParser<'_, '_> {
             | EntityKind::Namespace
             | EntityKind::StructDecl
             | EntityKind::ClassDecl
+            | EntityKind::UnionDecl
             | EntityKind::UnexposedDecl
             | EntityKind::ClassTemplate => {
                 for c in entity.get_children() {
-                    self.parse_functions(c)?;
+                    if c.get_kind() == EntityKind::FriendDecl {
+                        self.parse_friend_functions(entity, c)?;
+                    } else {
+                        self.parse_functions(c)?;
+                    }
                 }
             }
             _ => {}
         }
         Ok(())
     }
+
+    /// Parses functions declared in a friend declaration `entity` inside the class `class`.
+    /// Such functions are free functions of the enclosing namespace.
+    fn parse_friend_functions(&mut self, class: Entity<'_>, entity: Entity<'_>) -> Result<()> {
+        if class.get_kind() == EntityKind::ClassTemplate {
+            // friends of class templates depend on the class's template parameters
+            // and can't be called without an instantiation
+            trace!(
+                "skipping friend functions of class template: {}",
+                get_full_name_display(class)
+            );
+            return Ok(());
+        }
+        for child in entity.get_children() {
+            // friend declarations of other classes' methods are handled with those classes
+            if child.get_kind() == EntityKind::FunctionDecl
+                || child.get_kind() == EntityKind::FunctionTemplate
+            {
+                self.parse_functions(child)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses an integer literal, returning its type and value.
+fn parse_integer_literal(text: &str) -> Result<(CppBuiltInNumericType, u64)> {
+    let text = text.replace('\'', "").to_lowercase();
+    let digits = text.trim_end_matches(|c| c == 'u' || c == 'l');
+    let suffix = &text[digits.len()..];
+    let is_unsigned = suffix.contains('u');
+    let long_count = suffix.matches('l').count();
+
+    let (radix, digits) = if digits.starts_with("0x") {
+        (16, &digits[2..])
+    } else if digits.starts_with("0b") {
+        (2, &digits[2..])
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        (8, &digits[1..])
+    } else {
+        (10, digits)
+    };
+    let value = u64::from_str_radix(digits, radix)
+        .map_err(|_| format_err!("invalid integer literal: {}", text))?;
+
+    // Follows the C++ rules for choosing the literal type. `long` is assumed
+    // to be 32-bit, so the chosen type can hold the value on all platforms.
+    use self::CppBuiltInNumericType::*;
+    let candidates: &[(CppBuiltInNumericType, u64)] = &[
+        (Int, i32::max_value() as u64),
+        (UInt, u32::max_value().into()),
+        (Long, i32::max_value() as u64),
+        (ULong, u32::max_value().into()),
+        (LongLong, i64::max_value() as u64),
+        (ULongLong, u64::max_value()),
+    ];
+    let numeric_type = candidates[long_count.min(2) * 2..]
+        .iter()
+        .filter(|(t, _)| {
+            if is_unsigned {
+                t.is_unsigned_integer()
+            } else {
+                // decimal literals without a suffix are never unsigned
+                radix != 10 || !t.is_unsigned_integer()
+            }
+        })
+        .find(|(_, max)| value <= *max)
+        .map(|(t, _)| t.clone())
+        .ok_or_else(|| format_err!("integer literal is too large: {}", text))?;
+    Ok((numeric_type, value))
+}
+
+/// Replaces escape sequences in the content of a C string literal.
+fn unescape_string_literal(text: &str) -> Result<String> {
+    let mut result = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        let escaped = chars
+            .next()
+            .ok_or_else(|| err_msg("unterminated escape sequence"))?;
+        let value = match escaped {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            'a' => '\x07',
+            'b' => '\x08',
+            'f' => '\x0c',
+            'v' => '\x0b',
+            '\\' | '\'' | '"' | '?' => escaped,
+            'x' => {
+                let mut code = String::new();
+                while let Some(digit) = chars.peek().filter(|c| c.is_ascii_hexdigit()) {
+                    code.push(*digit);
+                    chars.next();
+                }
+                let code = u8::from_str_radix(&code, 16)
+                    .map_err(|_| format_err!("invalid hex escape: {}", code))?;
+                if !code.is_ascii() {
+                    bail!("non-ASCII escape sequences are not supported");
+                }
+                code as char
+            }
+            '0'..='7' => {
+                let mut code = escaped.to_string();
+                while code.len() < 3 {
+                    match chars.peek() {
+                        Some(digit @ '0'..='7') => {
+                            code.push(*digit);
+                            chars.next();
+                        }
+                        _ => break,
+                    }
+                }
+                let code = u8::from_str_radix(&code, 8)
+                    .map_err(|_| format_err!("invalid octal escape: {}", code))?;
+                if !code.is_ascii() {
+                    bail!("non-ASCII escape sequences are not supported");
+                }
+                code as char
+            }
+            _ => bail!("unsupported escape sequence: \\{}", escaped),
+        };
+        result.push(value);
+    }
+    Ok(result)
+}
+
+/// Parses tokens of a macro definition body consisting of a single literal
+/// (optionally negated or enclosed in parentheses). Adjacent string literals
+/// are concatenated.
+fn parse_macro_literal(tokens: &[String]) -> Result<(CppType, CppConstantValue)> {
+    let mut tokens = tokens;
+    while tokens.len() > 2 && tokens[0] == "(" && tokens[tokens.len() - 1] == ")" {
+        tokens = &tokens[1..tokens.len() - 1];
+    }
+    if tokens.is_empty() {
+        bail!("macro is empty");
+    }
+
+    if tokens.iter().all(|token| token.starts_with('"')) {
+        let mut value = String::new();
+        for token in tokens {
+            if token.len() < 2 || !token.ends_with('"') {
+                bail!("invalid string literal: {}", token);
+            }
+            value.push_str(&unescape_string_literal(&token[1..token.len() - 1])?);
+        }
+        let value_type =
+            CppType::new_pointer(true, CppType::BuiltInNumeric(CppBuiltInNumericType::Char));
+        return Ok((value_type, CppConstantValue::String(value)));
+    }
+
+    let (is_negative, literal) = match tokens {
+        [literal] => (false, literal),
+        [sign, literal] if sign == "-" => (true, literal),
+        [sign, literal] if sign == "+" => (false, literal),
+        _ => bail!("macro is not a literal: {}", tokens.join(" ")),
+    };
+    let lowercase = literal.to_lowercase();
+    if !lowercase.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        bail!("macro is not a numeric literal: {}", literal);
+    }
+    let is_float = !lowercase.starts_with("0x")
+        && (lowercase.contains('.') || lowercase.contains('e') || lowercase.ends_with('f'));
+    if is_float {
+        let (numeric_type, digits) = if lowercase.ends_with('f') {
+            (
+                CppBuiltInNumericType::Float,
+                &lowercase[..lowercase.len() - 1],
+            )
+        } else if lowercase.ends_with('l') {
+            (
+                CppBuiltInNumericType::LongDouble,
+                &lowercase[..lowercase.len() - 1],
+            )
+        } else {
+            (CppBuiltInNumericType::Double, &lowercase[..])
+        };
+        let value = f64::from_str(&digits.replace('\'', ""))
+            .map_err(|_| format_err!("invalid floating point literal: {}", literal))?;
+        let value = if is_negative { -value } else { value };
+        Ok((
+            CppType::BuiltInNumeric(numeric_type),
+            CppConstantValue::Float(value),
+        ))
+    } else {
+        let (numeric_type, value) = parse_integer_literal(literal)?;
+        let value = if is_negative {
+            (value as i64).wrapping_neg()
+        } else {
+            value as i64
+        };
+        Ok((
+            CppType::BuiltInNumeric(numeric_type),
+            CppConstantValue::Integer(value),
+        ))
+    }
+}
+
+#[test]
+fn should_parse_macro_literals() {
+    fn check(tokens: &[&str], value_type: CppType, value: CppConstantValue) {
+        let tokens = tokens.iter().map(|s| s.to_string()).collect_vec();
+        assert_eq!(parse_macro_literal(&tokens).unwrap(), (value_type, value));
+    }
+    let int = CppType::BuiltInNumeric(CppBuiltInNumericType::Int);
+    check(&["64"], int.clone(), CppConstantValue::Integer(64));
+    check(&["(", "-", "1", ")"], int, CppConstantValue::Integer(-1));
+    check(
+        &["0xFFFFFFFF"],
+        CppType::BuiltInNumeric(CppBuiltInNumericType::UInt),
+        CppConstantValue::Integer(0xFFFF_FFFF),
+    );
+    check(
+        &["10UL"],
+        CppType::BuiltInNumeric(CppBuiltInNumericType::ULong),
+        CppConstantValue::Integer(10),
+    );
+    check(
+        &["1.5f"],
+        CppType::BuiltInNumeric(CppBuiltInNumericType::Float),
+        CppConstantValue::Float(1.5),
+    );
+    check(
+        &["\"a\\tb\"", "\"c\""],
+        CppType::new_pointer(true, CppType::BuiltInNumeric(CppBuiltInNumericType::Char)),
+        CppConstantValue::String("a\tbc".to_string()),
+    );
+    assert!(parse_macro_literal(&["FOO".to_string()]).is_err());
+    assert!(parse_macro_literal(&["1".to_string(), "+".to_string(), "2".to_string()]).is_err());
+}
+
+#[test]
+fn should_join_tokens() {
+    assert_eq!(join_tokens(&["A", "(", "1", ",", "2.0", ")"]), "A(1, 2.0)");
+    assert_eq!(
+        join_tokens(&["QMap", "<", "int", ",", "int", ">", "(", ")"]),
+        "QMap<int, int>()"
+    );
+    assert_eq!(
+        join_tokens(&["unsigned", "(", "-", "1", ")", "+", "x"]),
+        "unsigned(-1)+x"
+    );
 }
 
 fn parse_template_args(str: &str) -> Option<(String, Vec<String>)> {
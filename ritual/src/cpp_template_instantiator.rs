@@ -47,7 +47,7 @@ pub fn instantiate_function(
     for arg in &function.arguments {
         new_method.arguments.push(CppFunctionArgument {
             name: arg.name.clone(),
-            has_default_value: arg.has_default_value,
+            default_value: arg.default_value.clone(),
             argument_type: arg.argument_type.instantiate(nested_level, arguments)?,
         });
     }
@@ -88,6 +88,33 @@ pub struct CppTemplateParameter {
     pub name: String,
 }
 
+/// Value of a non-type template argument, like `4` in `std::array<int, 4>`
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct CppTemplateArgumentValue {
+    /// Type of the corresponding template parameter
+    /// (an integer type, `bool` or an enum)
+    pub value_type: Box<CppType>,
+    /// Integer value of the argument
+    pub value: i64,
+}
+
+impl CppTemplateArgumentValue {
+    /// Returns C++ code representing this value.
+    pub fn to_cpp_code(&self) -> Result<String> {
+        match &*self.value_type {
+            CppType::BuiltInNumeric(CppBuiltInNumericType::Bool) => {
+                Ok((self.value != 0).to_string())
+            }
+            CppType::Enum { path } => Ok(format!(
+                "static_cast<{}>({})",
+                path.to_cpp_code()?,
+                self.value
+            )),
+            _ => Ok(self.value.to_string()),
+        }
+    }
+}
+
 /// Base C++ type. `CppType` can add indirection
 /// and constness to `CppTypeBase`, but otherwise
 /// this enum lists all supported types.
@@ -113,6 +140,9 @@ pub enum CppType {
     /// Template parameter, like `"T"` anywhere inside
     /// `QVector<T>` declaration
     TemplateParameter(CppTemplateParameter),
+    /// Value of a non-type template argument. This is not a real type
+    /// and may only appear in template arguments.
+    TemplateArgumentValue(CppTemplateArgumentValue),
     /// Function pointer type
     FunctionPointer(CppFunctionPointerType),
     PointerLike {
@@ -317,6 +347,7 @@ impl CppType {
             CppType::TemplateParameter { .. } => {
                 bail!("template parameters are not allowed in C++ code generator");
             }
+            CppType::TemplateArgumentValue(value) => value.to_cpp_code(),
             CppType::FunctionPointer(CppFunctionPointerType {
                 return_type,
                 arguments,
@@ -401,6 +432,13 @@ impl CppType {
             | CppType::Enum { path }
             | CppType::Class(path) => path.ascii_caption(),
             CppType::TemplateParameter(param) => param.name.to_string(),
+            CppType::TemplateArgumentValue(value) => {
+                if value.value < 0 {
+                    format!("minus_{}", value.value.wrapping_neg() as u64)
+                } else {
+                    value.value.to_string()
+                }
+            }
             CppType::FunctionPointer(_) => "fn".into(),
             CppType::PointerLike {
                 kind,
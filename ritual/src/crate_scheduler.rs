@@ -0,0 +1,230 @@
+//! Concurrent processing of crates in the order of their dependencies.
+
+use log::info;
+use ritual_common::errors::{bail, err_msg, Error, Result};
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A crate to process.
+pub struct CrateJob<T> {
+    pub name: String,
+    /// Names of crates that must be processed before this crate.
+    /// Crates without jobs are ignored.
+    pub dependencies: Vec<String>,
+    pub data: T,
+}
+
+/// Outcome of processing a crate.
+#[derive(Debug)]
+pub enum CrateStatus<R> {
+    Finished {
+        result: std::result::Result<R, Error>,
+        duration: Duration,
+    },
+    /// The crate was not processed because processing of its dependency failed.
+    Skipped { failed_dependency: String },
+}
+
+impl<R> CrateStatus<R> {
+    pub fn is_success(&self) -> bool {
+        match self {
+            CrateStatus::Finished { result, .. } => result.is_ok(),
+            CrateStatus::Skipped { .. } => false,
+        }
+    }
+}
+
+/// Runs `function` for all `jobs`, using at most `max_workers` threads.
+/// Each job is run in a thread named after the crate.
+/// A job is started when all its dependencies have been processed successfully.
+/// Returns statuses of the jobs in the original order.
+pub fn run<T, R, F>(
+    jobs: Vec<CrateJob<T>>,
+    max_workers: usize,
+    function: F,
+) -> Result<Vec<(String, CrateStatus<R>)>>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> Result<R> + Send + Sync + 'static,
+{
+    if max_workers == 0 {
+        bail!("number of workers must be positive");
+    }
+    let names = jobs.iter().map(|job| job.name.clone()).collect::<Vec<_>>();
+    let function = Arc::new(function);
+    let (sender, receiver) = mpsc::channel();
+    let mut pending = jobs;
+    let mut statuses = HashMap::new();
+    let mut running = 0;
+
+    loop {
+        // skipping a job may make other jobs skippable, including the ones
+        // already passed over, so the scan is repeated until nothing changes
+        let mut is_changed = true;
+        while is_changed {
+            is_changed = false;
+            let mut index = 0;
+            while index < pending.len() && running < max_workers {
+                let job = &pending[index];
+                let mut is_ready = true;
+                let mut failed_dependency = None;
+                for dependency in &job.dependencies {
+                    if !names.contains(dependency) || dependency == &job.name {
+                        continue;
+                    }
+                    match statuses.get(dependency) {
+                        None => is_ready = false,
+                        Some(status) => {
+                            if !status.is_success() {
+                                failed_dependency = Some(dependency.clone());
+                            }
+                        }
+                    }
+                }
+                if let Some(failed_dependency) = failed_dependency {
+                    let job = pending.remove(index);
+                    info!("Skipping crate {}: {} failed", job.name, failed_dependency);
+                    statuses.insert(job.name, CrateStatus::Skipped { failed_dependency });
+                    is_changed = true;
+                } else if is_ready {
+                    let job = pending.remove(index);
+                    let name = job.name.clone();
+                    let function = Arc::clone(&function);
+                    let sender = sender.clone();
+                    // the thread name is used as a prefix of log messages
+                    let spawned = thread::Builder::new().name(name.clone()).spawn(move || {
+                        let started = Instant::now();
+                        let result = catch_unwind(AssertUnwindSafe(|| function(job.data)))
+                            .unwrap_or_else(|_| Err(err_msg("processing thread panicked")));
+                        let status = CrateStatus::Finished {
+                            result,
+                            duration: started.elapsed(),
+                        };
+                        // the receiver is alive until all started jobs are finished
+                        sender.send((job.name, status)).unwrap();
+                    });
+                    match spawned {
+                        Ok(_) => running += 1,
+                        Err(err) => {
+                            let status = CrateStatus::Finished {
+                                result: Err(Error::from(err)),
+                                duration: Duration::default(),
+                            };
+                            statuses.insert(name, status);
+                        }
+                    }
+                    is_changed = true;
+                } else {
+                    index += 1;
+                }
+            }
+        }
+
+        if running == 0 {
+            if !pending.is_empty() {
+                let names = pending
+                    .iter()
+                    .map(|job| job.name.as_str())
+                    .collect::<Vec<_>>();
+                bail!("dependency cycle between crates: {}", names.join(", "));
+            }
+            break;
+        }
+        let (name, status) = receiver.recv()?;
+        running -= 1;
+        statuses.insert(name, status);
+    }
+
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            let status = statuses.remove(&name).expect("all jobs must be processed");
+            (name, status)
+        })
+        .collect())
+}
+
+#[test]
+fn run_in_dependency_order() {
+    use std::sync::Mutex;
+
+    let job = |name: &str, dependencies: &[&str]| CrateJob {
+        name: name.to_string(),
+        dependencies: dependencies.iter().map(|s| s.to_string()).collect(),
+        data: name.to_string(),
+    };
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let order2 = Arc::clone(&order);
+    let statuses = run(
+        vec![
+            job("c", &["b"]),
+            job("b", &["a", "external"]),
+            job("a", &[]),
+            job("d", &["a"]),
+            job("e", &["fail"]),
+            job("fail", &[]),
+        ],
+        2,
+        move |name: String| {
+            order2.lock().unwrap().push(name.clone());
+            if name == "fail" {
+                bail!("failed");
+            }
+            Ok(name)
+        },
+    )
+    .unwrap();
+
+    let names = statuses
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["c", "b", "a", "d", "e", "fail"]);
+    for (name, status) in &statuses {
+        assert_eq!(status.is_success(), name != "e" && name != "fail");
+    }
+    match &statuses[4].1 {
+        CrateStatus::Skipped { failed_dependency } => assert_eq!(failed_dependency, "fail"),
+        _ => panic!("expected skipped status"),
+    }
+
+    let order = order.lock().unwrap();
+    let position = |name: &str| order.iter().position(|x| x == name).unwrap();
+    assert!(position("a") < position("b"));
+    assert!(position("b") < position("c"));
+    assert!(position("a") < position("d"));
+
+    let statuses = run(
+        vec![job("w", &["g"]), job("g", &["c"]), job("c", &[])],
+        1,
+        |name: String| {
+            if name == "c" {
+                bail!("failed");
+            }
+            Ok(name)
+        },
+    )
+    .unwrap();
+    let skipped = statuses
+        .iter()
+        .map(|(name, status)| match status {
+            CrateStatus::Skipped { failed_dependency } => {
+                Some((name.as_str(), failed_dependency.as_str()))
+            }
+            CrateStatus::Finished { .. } => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(skipped, vec![Some(("w", "g")), Some(("g", "c")), None]);
+
+    let cycle = run(
+        vec![job("a", &["b"]), job("b", &["a"])],
+        2,
+        |name: String| Ok(name),
+    );
+    assert!(cycle.is_err());
+}
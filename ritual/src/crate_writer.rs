@@ -4,6 +4,7 @@ use crate::cpp_code_generator::{
     all_include_directives, generate_cpp_type_size_requester, write_include_directives,
 };
 use crate::database::CRATE_DB_FILE_NAME;
+use crate::database_file::{self, DatabaseFormat};
 use crate::processor::ProcessorData;
 use crate::rust_code_generator;
 use itertools::Itertools;
@@ -255,27 +256,33 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     generate_crate_template(data, &output_path)?;
     data.workspace.update_cargo_toml()?;
 
-    let c_lib_path = output_path.join("c_lib");
-    if !c_lib_path.exists() {
-        create_dir(&c_lib_path)?;
-    }
     let c_lib_name = format!("{}_c", data.config.crate_properties().name());
-    let global_header_name = format!("{}_global.h", c_lib_name);
-    generate_c_lib_template(
-        &c_lib_name,
-        &c_lib_path,
-        &global_header_name,
-        &all_include_directives(data.config)?,
-    )?;
+    // In plain C mode, all functions may be called directly,
+    // so the wrapper library is not needed unless it's used to determine type sizes.
+    let has_wrapper_library = cpp_code_generator::has_wrapper_code(&data.db)?
+        || cpp_code_generator::has_sized_types(&data.db);
+    if has_wrapper_library {
+        let c_lib_path = output_path.join("c_lib");
+        if !c_lib_path.exists() {
+            create_dir(&c_lib_path)?;
+        }
+        let global_header_name = format!("{}_global.h", c_lib_name);
+        generate_c_lib_template(
+            &c_lib_name,
+            &c_lib_path,
+            &global_header_name,
+            &all_include_directives(data.config)?,
+        )?;
 
-    cpp_code_generator::generate_cpp_file(
-        &data.db,
-        &c_lib_path.join("file1.cpp"),
-        &global_header_name,
-    )?;
+        cpp_code_generator::generate_cpp_file(
+            &data.db,
+            &c_lib_path.join("file1.cpp"),
+            &global_header_name,
+        )?;
 
-    let file = create_file(c_lib_path.join("sized_types.cxx"))?;
-    generate_cpp_type_size_requester(data.db, data.config.include_directives(), file)?;
+        let file = create_file(c_lib_path.join("sized_types.cxx"))?;
+        generate_cpp_type_size_requester(data.db, data.config.include_directives(), file)?;
+    }
 
     rust_code_generator::generate(
         &data.db,
@@ -302,14 +309,17 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
         &BuildScriptData {
             cpp_build_config: data.config.cpp_build_config().clone(),
             cpp_wrapper_lib_name: c_lib_name,
+            has_wrapper_library,
             known_targets: data.db.environments().to_vec(),
         },
         None,
     )?;
 
-    copy_file(
-        data.workspace.database_path(crate_name),
+    database_file::save(
         output_path.join(CRATE_DB_FILE_NAME),
+        data.db.data(),
+        DatabaseFormat::Binary,
+        None,
     )?;
 
     Ok(())
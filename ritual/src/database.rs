@@ -1,25 +1,35 @@
 use crate::cpp_checks::{CppChecks, CppChecksItem};
 use crate::cpp_data::{CppItem, CppPath};
+use crate::cpp_directives::CppDirectives;
 use crate::cpp_ffi_data::CppFfiItem;
+use crate::database_file::{self, SCHEMA_VERSION};
 use crate::rust_info::RustItem;
 use crate::rust_type::RustPath;
+use itertools::Itertools;
 use log::{debug, error, info, trace, warn};
 use once_cell::sync::OnceCell;
 use ritual_common::errors::{bail, err_msg, format_err, Result};
-use ritual_common::file_utils::load_json;
 use ritual_common::string_utils::ends_with_digit;
 use ritual_common::target::LibraryTarget;
 use ritual_common::ReadOnly;
 use serde_derive::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::iter::once;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::{fmt, mem};
 
-pub const CRATE_DB_FILE_NAME: &str = "ritual_db_v1.json";
+/// Name of the database file in a generated crate. The file is stored in the binary
+/// format, so it can be migrated by newer versions of `ritual`.
+pub const CRATE_DB_FILE_NAME: &str = "ritual_db_v2.bin";
+/// Name of the database file in crates generated by older versions of `ritual`.
+pub const LEGACY_CRATE_DB_FILE_NAME: &str = "ritual_db_v1.json";
 
-pub struct DatabaseCache(HashMap<PathBuf, IndexedDatabase>);
+/// Databases that are not currently used as the current database of a `DatabaseClient`.
+///
+/// Databases of dependencies stay in the cache while they are used, so that
+/// crates processed concurrently can share them.
+pub struct DatabaseCache(HashMap<PathBuf, Arc<IndexedDatabase>>);
 
 impl DatabaseCache {
     pub fn global() -> &'static Mutex<Self> {
@@ -37,11 +47,17 @@ impl DatabaseCache {
         let path = PathBuf::from(path.as_ref());
         if allow_load {
             if let Some(r) = self.0.remove(&path) {
-                return Ok(r);
+                match Arc::try_unwrap(r) {
+                    Ok(r) => return Ok(r),
+                    Err(r) => {
+                        self.0.insert(path, r);
+                        bail!("database of {} is used by another crate", crate_name);
+                    }
+                }
             }
-            if path.exists() {
+            if let Some(existing_path) = database_file::existing_path(&path) {
                 info!("Loading database for {}", crate_name);
-                let db = load_json(&path)?;
+                let db = database_file::load(&existing_path)?;
                 return Ok(IndexedDatabase::new(db, path));
             }
         }
@@ -52,9 +68,30 @@ impl DatabaseCache {
         bail!("can't get database for {}", crate_name);
     }
 
+    /// Returns a database for read-only use (as a dependency of another crate).
+    pub fn get_shared(
+        &mut self,
+        path: impl AsRef<Path>,
+        crate_name: &str,
+    ) -> Result<Arc<IndexedDatabase>> {
+        let path = PathBuf::from(path.as_ref());
+        if let Some(r) = self.0.get(&path) {
+            return Ok(Arc::clone(r));
+        }
+        let existing_path = database_file::existing_path(&path)
+            .ok_or_else(|| format_err!("can't get database for {}", crate_name))?;
+        info!("Loading database for {}", crate_name);
+        let db = Arc::new(IndexedDatabase::new(
+            database_file::load(&existing_path)?,
+            path.clone(),
+        ));
+        self.0.insert(path, Arc::clone(&db));
+        Ok(db)
+    }
+
     pub fn put(&mut self, db: IndexedDatabase) {
         let path = db.path.clone();
-        let r = self.0.insert(path, db);
+        let r = self.0.insert(path, Arc::new(db));
         if r.is_some() {
             warn!("duplicate db put in cache");
         }
@@ -144,6 +181,11 @@ impl ItemId {
     pub fn crate_name(&self) -> &str {
         &self.crate_name
     }
+
+    /// Index of the item within its crate
+    pub fn id(&self) -> u32 {
+        self.id
+    }
 }
 
 impl fmt::Display for ItemId {
@@ -170,7 +212,7 @@ pub struct DocItem {
     pub cross_references: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(clippy::large_enum_variant)]
 pub enum DatabaseItemData {
     CppItem(CppItem),
@@ -178,6 +220,7 @@ pub enum DatabaseItemData {
     CppChecksItem(CppChecksItem),
     RustItem(RustItem),
     DocItem(DocItem),
+    DirectivesItem(CppDirectives),
 }
 
 impl DatabaseItemData {
@@ -279,6 +322,20 @@ impl DatabaseItemData {
             None
         }
     }
+    pub fn is_directives_item(&self) -> bool {
+        if let DatabaseItemData::DirectivesItem(_) = self {
+            true
+        } else {
+            false
+        }
+    }
+    pub fn as_directives_item(&self) -> Option<&CppDirectives> {
+        if let DatabaseItemData::DirectivesItem(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
 
     pub fn short_text(&self) -> String {
         match self {
@@ -287,31 +344,73 @@ impl DatabaseItemData {
             DatabaseItemData::RustItem(item) => item.short_text(),
             DatabaseItemData::CppChecksItem(_) => "CppChecksItem".into(),
             DatabaseItemData::DocItem(_) => "DocItem".into(),
+            DatabaseItemData::DirectivesItem(_) => "DirectivesItem".into(),
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Information about the last run of a main procedure step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepRecord {
+    /// Fingerprint of the step's inputs
+    pub fingerprint: String,
+    /// Range of IDs of items created by the step
+    pub first_id: u32,
+    pub end_id: u32,
+    /// Number of items in the ID range right after the step completed
+    pub item_count: usize,
+    /// Files read by the step that are not covered by the fingerprint
+    /// (e.g. headers included by the parsed headers)
+    #[serde(default)]
+    pub input_files: Vec<PathBuf>,
+    /// Fingerprint of `input_files` right after the step completed
+    #[serde(default)]
+    pub input_files_fingerprint: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Database {
+    /// See `database_migrations`
+    schema_version: u32,
     crate_name: Arc<String>,
     crate_version: String,
     items: Vec<DbItem<DatabaseItemData>>,
     targets: Vec<LibraryTarget>,
     next_id: u32,
+    #[serde(default)]
+    step_records: BTreeMap<String, StepRecord>,
 }
 
 impl Database {
     pub fn empty(crate_name: String) -> Self {
         Database {
+            schema_version: SCHEMA_VERSION,
             crate_name: Arc::new(crate_name),
             crate_version: "0.0.0".into(),
             items: Vec::new(),
             targets: Vec::new(),
             next_id: 1,
+            step_records: BTreeMap::new(),
         }
     }
 
-    fn items(&self) -> impl Iterator<Item = DbItem<&DatabaseItemData>> {
+    pub fn crate_name(&self) -> &str {
+        &self.crate_name
+    }
+
+    pub fn crate_version(&self) -> &str {
+        &self.crate_version
+    }
+
+    pub fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    pub fn environments(&self) -> &[LibraryTarget] {
+        &self.targets
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = DbItem<&DatabaseItemData>> {
         self.items.iter().map(|item| item.as_ref())
     }
     fn items_mut(&mut self) -> impl Iterator<Item = DbItem<&mut DatabaseItemData>> {
@@ -345,6 +444,7 @@ pub struct IndexedDatabase {
     path: PathBuf,
     source_id_to_index: HashMap<Option<ItemId>, Vec<usize>>,
     cpp_path_to_index: HashMap<CppPath, Vec<usize>>,
+    cpp_parent_path_to_index: HashMap<CppPath, Vec<usize>>,
     rust_path_to_index: HashMap<RustPath, usize>,
 }
 
@@ -355,6 +455,7 @@ impl IndexedDatabase {
             path,
             source_id_to_index: HashMap::new(),
             cpp_path_to_index: HashMap::new(),
+            cpp_parent_path_to_index: HashMap::new(),
             rust_path_to_index: HashMap::new(),
         };
         value.refresh();
@@ -368,26 +469,15 @@ impl IndexedDatabase {
     fn refresh(&mut self) {
         self.source_id_to_index.clear();
         self.cpp_path_to_index.clear();
+        self.cpp_parent_path_to_index.clear();
         self.rust_path_to_index.clear();
-        for (index, item) in self.db.items.iter().enumerate() {
-            self.source_id_to_index
-                .entry(item.source_id.clone())
-                .or_default()
-                .push(index);
-            if let Some(path) = item.item.as_rust_item().and_then(|item| item.path()) {
-                self.rust_path_to_index.insert(path.clone(), index);
-            }
-            if let Some(path) = item.item.as_cpp_item().and_then(|item| item.path()) {
-                self.cpp_path_to_index
-                    .entry(path.clone())
-                    .or_default()
-                    .push(index);
-            }
+        for index in 0..self.db.items.len() {
+            self.add_to_index(index);
         }
     }
 
-    fn push(&mut self, item: DbItem<DatabaseItemData>) {
-        let index = self.db.items.len();
+    fn add_to_index(&mut self, index: usize) {
+        let item = &self.db.items[index];
         self.source_id_to_index
             .entry(item.source_id.clone())
             .or_default()
@@ -400,8 +490,18 @@ impl IndexedDatabase {
                 .entry(path.clone())
                 .or_default()
                 .push(index);
+            if let Ok(parent) = path.parent() {
+                self.cpp_parent_path_to_index
+                    .entry(parent)
+                    .or_default()
+                    .push(index);
+            }
         }
+    }
+
+    fn push(&mut self, item: DbItem<DatabaseItemData>) {
         self.db.items.push(item);
+        self.add_to_index(self.db.items.len() - 1);
     }
 
     fn filter_by_source(
@@ -427,6 +527,22 @@ impl IndexedDatabase {
             })
     }
 
+    fn filter_by_cpp_parent_path(
+        &self,
+        parent_path: &CppPath,
+    ) -> impl Iterator<Item = DbItem<&CppItem>> {
+        self.cpp_parent_path_to_index
+            .get(parent_path)
+            .into_iter()
+            .flat_map(move |ids| {
+                ids.iter().map(move |&id| {
+                    self.db.items[id]
+                        .as_ref()
+                        .map(|item| item.as_cpp_item().expect("invalid db index"))
+                })
+            })
+    }
+
     fn find_rust_item(&self, path: &RustPath) -> Option<DbItem<&RustItem>> {
         self.rust_path_to_index.get(path).map(|&index| {
             self.db.items[index]
@@ -447,9 +563,10 @@ pub struct Counters {
 #[derive(Debug)]
 pub struct DatabaseClient {
     current_database: IndexedDatabase,
-    dependencies: ReadOnly<Vec<IndexedDatabase>>,
+    dependencies: ReadOnly<Vec<Arc<IndexedDatabase>>>,
     is_modified: bool,
     counters: Counters,
+    step_input_files: BTreeSet<PathBuf>,
 }
 
 impl Drop for DatabaseClient {
@@ -458,26 +575,22 @@ impl Drop for DatabaseClient {
             &mut self.current_database,
             IndexedDatabase::new(Database::empty(String::new()), PathBuf::new()),
         );
-        let dependencies = mem::replace(&mut self.dependencies, ReadOnly::new(Vec::new()));
-
         let mut cache = DatabaseCache::global().lock().unwrap();
         cache.put(current_database);
-        for dependency in dependencies.into_inner() {
-            cache.put(dependency);
-        }
     }
 }
 
 impl DatabaseClient {
     pub fn new(
         current_database: IndexedDatabase,
-        dependencies: ReadOnly<Vec<IndexedDatabase>>,
+        dependencies: ReadOnly<Vec<Arc<IndexedDatabase>>>,
     ) -> DatabaseClient {
         DatabaseClient {
             current_database,
             dependencies,
             is_modified: false,
             counters: Counters::default(),
+            step_input_files: BTreeSet::new(),
         }
     }
 
@@ -485,6 +598,14 @@ impl DatabaseClient {
         &self.current_database.db
     }
 
+    /// Replaces data of the current crate with `data` and returns the previous data.
+    pub fn replace_data(&mut self, data: Database) -> Database {
+        let old_data = mem::replace(&mut self.current_database.db, data);
+        self.current_database.refresh();
+        self.is_modified = true;
+        old_data
+    }
+
     pub fn is_modified(&self) -> bool {
         self.is_modified
     }
@@ -832,6 +953,127 @@ impl DatabaseClient {
         Some(id)
     }
 
+    pub fn add_directives_item(
+        &mut self,
+        source_id: ItemId,
+        item: CppDirectives,
+    ) -> Option<ItemId> {
+        if self
+            .current_database
+            .filter_by_source(&Some(source_id.clone()))
+            .any(|other| other.item.is_directives_item())
+        {
+            self.counters.items_ignored += 1;
+            return None;
+        }
+
+        let id = self.new_id();
+
+        self.current_database.push(DbItem {
+            id: id.clone(),
+            source_id: Some(source_id),
+            item: DatabaseItemData::DirectivesItem(item),
+        });
+        self.counters.items_added += 1;
+        Some(id)
+    }
+
+    /// Replaces data of existing items of the current crate.
+    /// Source IDs of the items can't be changed.
+    pub fn replace_items(&mut self, items: Vec<DbItem<DatabaseItemData>>) -> Result<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        for item in items {
+            if *item.id.crate_name != self.crate_name() {
+                bail!("can't modify item of dependency");
+            }
+            let index = self
+                .current_database
+                .db
+                .items
+                .binary_search_by_key(&&item.id, |other| &other.id)
+                .map_err(|_| format_err!("invalid item id: {}", item.id))?;
+            let old_item = &mut self.current_database.db.items[index];
+            if old_item.source_id != item.source_id {
+                bail!("can't change source id of item {}", item.id);
+            }
+            debug!("replaced item {}: {}", item.id, item.item.short_text());
+            old_item.item = item.item;
+        }
+        self.is_modified = true;
+        self.current_database.refresh();
+        Ok(())
+    }
+
+    /// Returns the ID that will be assigned to the next added item.
+    pub fn next_id(&self) -> u32 {
+        self.current_database.db.next_id
+    }
+
+    /// Returns number of items of the current crate with IDs in `first_id..end_id`.
+    pub fn item_count_in_range(&self, first_id: u32, end_id: u32) -> usize {
+        self.current_database
+            .db
+            .items
+            .iter()
+            .filter(|item| item.id.id >= first_id && item.id.id < end_id)
+            .count()
+    }
+
+    pub fn step_record(&self, step_name: &str) -> Option<&StepRecord> {
+        self.current_database.db.step_records.get(step_name)
+    }
+
+    pub fn set_step_record(&mut self, step_name: &str, record: StepRecord) {
+        self.is_modified = true;
+        self.current_database
+            .db
+            .step_records
+            .insert(step_name.to_string(), record);
+    }
+
+    pub fn remove_step_record(&mut self, step_name: &str) {
+        if self
+            .current_database
+            .db
+            .step_records
+            .remove(step_name)
+            .is_some()
+        {
+            self.is_modified = true;
+        }
+    }
+
+    /// Adds files read by the current step. They are saved in the step's record,
+    /// so the step becomes outdated when any of the files change.
+    pub fn add_step_input_files(&mut self, files: impl IntoIterator<Item = PathBuf>) {
+        self.step_input_files.extend(files);
+    }
+
+    /// Returns files added with `add_step_input_files` since the previous call.
+    pub fn take_step_input_files(&mut self) -> Vec<PathBuf> {
+        mem::replace(&mut self.step_input_files, BTreeSet::new())
+            .into_iter()
+            .collect()
+    }
+
+    /// Returns a text that changes whenever any of the dependency databases change.
+    pub fn dependencies_state(&self) -> String {
+        self.dependencies
+            .iter()
+            .map(|dependency| {
+                format!(
+                    "{}:{}:{}:{}",
+                    dependency.db.crate_name,
+                    dependency.db.crate_version,
+                    dependency.db.next_id,
+                    dependency.db.items.len()
+                )
+            })
+            .join(";")
+    }
+
     pub fn cpp_checks(&self, source_id: &ItemId) -> Result<CppChecks> {
         let items = self
             .database(&source_id.crate_name)?
@@ -952,14 +1194,58 @@ impl DatabaseClient {
         }
     }
 
+    /// Returns directives specified in the header for the C++ item
+    /// that is the source of the item `id`.
+    pub fn find_directives_for(&self, id: &ItemId) -> Result<Option<&CppDirectives>> {
+        let mut current_item = self.item(id)?;
+        loop {
+            if let Some(directives) = self
+                .database(&current_item.id.crate_name)?
+                .filter_by_source(&Some(current_item.id.clone()))
+                .find_map(|i| i.item.as_directives_item())
+            {
+                return Ok(Some(directives));
+            }
+
+            let new_id = if let Some(id) = &current_item.source_id {
+                id.clone()
+            } else {
+                return Ok(None);
+            };
+            current_item = self.item(&new_id)?;
+        }
+    }
+
+    /// Returns directives specified in the header for the C++ item with `path`.
+    pub fn find_directives_for_cpp_path(&self, path: &CppPath) -> Option<&CppDirectives> {
+        self.all_databases()
+            .flat_map(|db| {
+                db.filter_by_cpp_path(path).flat_map(move |item| {
+                    db.filter_by_source(&Some(item.id))
+                        .filter_map(|i| i.item.as_directives_item())
+                })
+            })
+            .next()
+    }
+
     fn all_databases(&self) -> impl Iterator<Item = &IndexedDatabase> {
-        once(&self.current_database as &_).chain(self.dependencies.iter())
+        once(&self.current_database).chain(self.dependencies.iter().map(|db| &**db))
     }
 
     pub fn all_cpp_items(&self) -> impl Iterator<Item = DbItem<&CppItem>> {
         self.all_databases().flat_map(|d| d.db.cpp_items())
     }
 
+    /// Returns C++ items nested in `parent_path` (e.g. values of an enum)
+    /// from all databases.
+    pub fn cpp_children<'a>(
+        &'a self,
+        parent_path: &'a CppPath,
+    ) -> impl Iterator<Item = DbItem<&'a CppItem>> + 'a {
+        self.all_databases()
+            .flat_map(move |db| db.filter_by_cpp_parent_path(parent_path))
+    }
+
     pub fn all_ffi_items(&self) -> impl Iterator<Item = DbItem<&CppFfiItem>> {
         self.all_databases().flat_map(|d| d.db.ffi_items())
     }
@@ -969,8 +1255,12 @@ impl DatabaseClient {
         cpp_path: &CppPath,
         allow_dependencies: bool,
     ) -> Result<impl Iterator<Item = DbItem<&RustItem>>> {
-        let databases = once(&self.current_database as &_)
-            .chain(self.dependencies.iter().filter(|_| allow_dependencies));
+        let databases = once(&self.current_database).chain(
+            self.dependencies
+                .iter()
+                .filter(|_| allow_dependencies)
+                .map(|db| &**db),
+        );
 
         for db in databases {
             if let Some(cpp_item) = db.filter_by_cpp_path(cpp_path).next() {
@@ -993,8 +1283,9 @@ impl DatabaseClient {
         Ok(&self.database(crate_name)?.db.crate_version)
     }
 
-    pub fn print_item_trace(&self, item_id: &ItemId) -> Result<()> {
-        info!("Sources:");
+    /// Returns the item and all its sources, starting with the item itself.
+    /// The chain ends early if a source is missing.
+    pub fn source_chain(&self, item_id: &ItemId) -> Result<Vec<DbItem<&DatabaseItemData>>> {
         let mut sources = Vec::new();
         let mut item = self.item(item_id)?;
         loop {
@@ -1013,7 +1304,33 @@ impl DatabaseClient {
                 break;
             }
         }
-        for source in sources.iter().rev() {
+        Ok(sources)
+    }
+
+    /// Returns all items (of all databases) derived from the item, directly or indirectly.
+    pub fn descendants(&self, item_id: &ItemId) -> Vec<DbItem<&DatabaseItemData>> {
+        let mut result = Vec::new();
+        for child in self.children(item_id) {
+            let child_id = child.id.clone();
+            result.push(child);
+            result.extend(self.descendants(&child_id));
+        }
+        result
+    }
+
+    /// Returns items (of all databases) directly derived from the item.
+    pub fn children<'a>(
+        &'a self,
+        item_id: &ItemId,
+    ) -> impl Iterator<Item = DbItem<&'a DatabaseItemData>> + 'a {
+        let item_id = Some(item_id.clone());
+        self.all_databases()
+            .flat_map(move |db| db.filter_by_source(&item_id).collect_vec())
+    }
+
+    pub fn print_item_trace(&self, item_id: &ItemId) -> Result<()> {
+        info!("Sources:");
+        for source in self.source_chain(item_id)?.iter().rev() {
             info!("{:?}", source);
         }
         info!("Children:");
@@ -1022,12 +1339,7 @@ impl DatabaseClient {
     }
 
     fn print_item_children(&self, item_id: &ItemId) {
-        let item_id = Some(item_id.clone());
-        let children = self
-            .all_databases()
-            .flat_map(|db| db.filter_by_source(&item_id));
-
-        for child in children {
+        for child in self.children(item_id) {
             info!("{:?}", child);
             self.print_item_children(&child.id);
         }
@@ -0,0 +1,262 @@
+//! Reading and writing database files.
+//!
+//! A database can be stored in two formats:
+//!
+//! - JSON, the format used by older versions of `ritual`. It's also useful for debugging
+//!   (see `export_json` operation).
+//! - Binary, the default format. The file starts with `MAGIC`, followed by
+//!   a bincode-encoded `Header` and the bincode-encoded database.
+//!
+//! Databases of older schema versions are migrated when loading
+//! (see `database_migrations`). The format of a file is detected automatically
+//! when loading.
+
+use crate::database::Database;
+use crate::database_migrations;
+use ritual_common::errors::{bail, Result, ResultExt};
+use ritual_common::file_utils::{open_file, write_file_with_backup};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Bytes at the start of a binary database file.
+pub const MAGIC: &[u8; 8] = b"RITUALDB";
+
+/// Version of serialized representation of the database.
+/// See `database_migrations` for information about changing the representation.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Header of a binary database file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Header {
+    pub schema_version: u32,
+    pub crate_name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatabaseFormat {
+    Json,
+    Binary,
+}
+
+/// Reads header of the database file. Returns `None` if the file is in JSON format.
+pub fn read_header(path: impl AsRef<Path>) -> Result<Option<Header>> {
+    let path = path.as_ref();
+    let mut file = open_file(path)?.into_inner();
+    match read_format(&mut file)? {
+        DatabaseFormat::Json => Ok(None),
+        DatabaseFormat::Binary => {
+            let header = bincode::deserialize_from(&mut file)
+                .with_context(|_| format!("invalid database header: {}", path.display()))?;
+            Ok(Some(header))
+        }
+    }
+}
+
+/// Detects format of the database file.
+pub fn detect_format(path: impl AsRef<Path>) -> Result<DatabaseFormat> {
+    let mut file = open_file(path.as_ref())?.into_inner();
+    read_format(&mut file)
+}
+
+fn read_format(file: &mut impl Read) -> Result<DatabaseFormat> {
+    let mut magic = [0; MAGIC.len()];
+    match file.read_exact(&mut magic) {
+        Ok(()) if &magic == MAGIC => Ok(DatabaseFormat::Binary),
+        Ok(()) => Ok(DatabaseFormat::Json),
+        Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(DatabaseFormat::Json),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Returns path of the same database saved by older versions of `ritual`
+/// that used JSON format and `.json` extension.
+pub fn legacy_path(path: &Path) -> PathBuf {
+    path.with_extension("json")
+}
+
+/// Returns `path` if it exists or its `legacy_path` if only the legacy file exists.
+pub fn existing_path(path: &Path) -> Option<PathBuf> {
+    if path.exists() {
+        return Some(path.to_path_buf());
+    }
+    let legacy_path = legacy_path(path);
+    if legacy_path.exists() {
+        Some(legacy_path)
+    } else {
+        None
+    }
+}
+
+/// Migrates JSON representation of a database and converts it to `Database`.
+fn load_value(mut value: Value, path: &Path) -> Result<Database> {
+    database_migrations::migrate(&mut value)
+        .with_context(|_| format!("failed to migrate database: {}", path.display()))?;
+    Ok(serde_json::from_value(value)
+        .with_context(|_| format!("failed to load database: {}", path.display()))?)
+}
+
+/// Loads a database in any supported format.
+pub fn load(path: impl AsRef<Path>) -> Result<Database> {
+    let path = path.as_ref();
+    let mut file = open_file(path)?.into_inner();
+    match read_format(&mut file)? {
+        DatabaseFormat::Json => {
+            file.seek(SeekFrom::Start(0))?;
+            let value = serde_json::from_reader(file)
+                .with_context(|_| format!("failed to parse file as JSON: {}", path.display()))?;
+            load_value(value, path)
+        }
+        DatabaseFormat::Binary => {
+            let header: Header = bincode::deserialize_from(&mut file)
+                .with_context(|_| format!("invalid database header: {}", path.display()))?;
+            if header.schema_version > SCHEMA_VERSION {
+                bail!(
+                    "database {} has schema version {} that is newer than supported \
+                     by this version of ritual ({}); update ritual or discard the database",
+                    path.display(),
+                    header.schema_version,
+                    SCHEMA_VERSION
+                );
+            }
+            let db: Database = if header.schema_version == SCHEMA_VERSION {
+                bincode::deserialize_from(file)
+                    .with_context(|_| format!("failed to decode database: {}", path.display()))?
+            } else {
+                let value = database_migrations::decode_binary(header.schema_version, &mut file)
+                    .with_context(|_| format!("failed to decode database: {}", path.display()))?;
+                load_value(value, path)?
+            };
+            if db.crate_name() != header.crate_name {
+                bail!(
+                    "crate name mismatch in database {}: {} in header, {} in data",
+                    path.display(),
+                    header.crate_name,
+                    db.crate_name()
+                );
+            }
+            Ok(db)
+        }
+    }
+}
+
+/// Saves the database in the specified format.
+/// If `backup_path` is specified, the previous version of the file is moved to it.
+pub fn save(
+    path: impl AsRef<Path>,
+    db: &Database,
+    format: DatabaseFormat,
+    backup_path: Option<&Path>,
+) -> Result<()> {
+    let path = path.as_ref();
+    write_file_with_backup(path, backup_path, |file| {
+        match format {
+            DatabaseFormat::Json => serde_json::to_writer(file, db)?,
+            DatabaseFormat::Binary => {
+                let header = Header {
+                    schema_version: SCHEMA_VERSION,
+                    crate_name: db.crate_name().to_string(),
+                };
+                file.write_all(MAGIC)?;
+                bincode::serialize_into(&mut *file, &header)?;
+                bincode::serialize_into(file, db)?;
+            }
+        }
+        Ok(())
+    })
+    .with_context(|_| format!("failed to save database: {}", path.display()))?;
+    Ok(())
+}
+
+#[test]
+fn save_and_load() {
+    use crate::cpp_data::{CppItem, CppNamespace, CppPath};
+    use crate::cpp_directives::CppDirectives;
+    use crate::database::{DatabaseClient, DocItem, IndexedDatabase, StepRecord};
+    use ritual_common::ReadOnly;
+
+    let mut db = DatabaseClient::new(
+        IndexedDatabase::new(
+            Database::empty("crate1".to_string()),
+            PathBuf::from("crate1.db"),
+        ),
+        ReadOnly::new(Vec::new()),
+    );
+    let ns_id = db
+        .add_cpp_item_without_hook(
+            None,
+            CppItem::Namespace(CppNamespace {
+                path: CppPath::from_good_str("ns1"),
+            }),
+        )
+        .unwrap()
+        .unwrap();
+    db.add_doc_item(
+        ns_id.clone(),
+        DocItem {
+            anchor: None,
+            html: "<p>Namespace</p>".to_string(),
+            mismatched_declaration: None,
+            url: None,
+            cross_references: vec!["https://example.com/".to_string()],
+        },
+    )
+    .unwrap();
+    db.add_directives_item(
+        ns_id,
+        CppDirectives {
+            skip: true,
+            ..CppDirectives::default()
+        },
+    )
+    .unwrap();
+    db.set_step_record(
+        "cpp_parser",
+        StepRecord {
+            fingerprint: "0123".to_string(),
+            first_id: 1,
+            end_id: 4,
+            item_count: 3,
+            input_files: vec![PathBuf::from("/include/a.h")],
+            input_files_fingerprint: "4567".to_string(),
+        },
+    );
+
+    let dir = tempdir::TempDir::new("ritual_database_file").unwrap();
+    for &format in &[DatabaseFormat::Binary, DatabaseFormat::Json] {
+        let path = dir.path().join(format!("{:?}", format));
+        save(&path, db.data(), format, None).unwrap();
+        assert_eq!(detect_format(&path).unwrap(), format);
+        let header = read_header(&path).unwrap();
+        match format {
+            DatabaseFormat::Binary => assert_eq!(
+                header,
+                Some(Header {
+                    schema_version: SCHEMA_VERSION,
+                    crate_name: "crate1".to_string(),
+                })
+            ),
+            DatabaseFormat::Json => assert_eq!(header, None),
+        }
+        let loaded = load(&path).unwrap();
+        assert_eq!(loaded.crate_name(), "crate1");
+        assert_eq!(loaded.items().count(), 3);
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(db.data()).unwrap()
+        );
+    }
+
+    let path = dir.path().join("invalid");
+    let mut data = MAGIC.to_vec();
+    data.extend(
+        bincode::serialize(&Header {
+            schema_version: SCHEMA_VERSION + 1,
+            crate_name: "crate1".to_string(),
+        })
+        .unwrap(),
+    );
+    std::fs::write(&path, data).unwrap();
+    assert!(load(&path).is_err());
+}
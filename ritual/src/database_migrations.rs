@@ -0,0 +1,436 @@
+//! Migrations of databases created by older versions of `ritual`.
+//!
+//! Each change of serialized representation of `Database` (including `CppItem`,
+//! `CppFfiItem`, `RustItem` and other item types) must increment `SCHEMA_VERSION`
+//! and add a migration to `MIGRATIONS` that converts the JSON representation
+//! of the previous version to the new one.
+//!
+//! Migrations operate on JSON representation of the database. Binary database files
+//! (see `database_file`) are not self-describing and can only be decoded with the types
+//! of their schema version. When `SCHEMA_VERSION` is incremented, the previous version
+//! of the changed types must be kept (e.g. in a `v2` module), and a decoder that
+//! converts the binary representation of the previous version to JSON must be added
+//! to `BINARY_DECODERS`. The result is then converted by `MIGRATIONS`.
+
+use crate::database_file::SCHEMA_VERSION;
+use log::info;
+use ritual_common::errors::{bail, err_msg, format_err, Result, ResultExt};
+use serde_json::{json, Map, Value};
+use std::io::Read;
+
+/// Schema version of databases that don't have `schema_version` field.
+pub const INITIAL_SCHEMA_VERSION: u32 = 1;
+
+/// A conversion of the database from `from_version` to the next version.
+pub struct Migration {
+    pub from_version: u32,
+    pub description: &'static str,
+    pub function: fn(&mut Value) -> Result<()>,
+}
+
+/// All migrations, ordered by `from_version`.
+pub static MIGRATIONS: &[Migration] = &[Migration {
+    from_version: 1,
+    description: "add attributes of C++ functions, underlying types of enums \
+                  and direct calls of FFI functions",
+    function: migrate_from_v1,
+}];
+
+/// Schema version of the first binary databases created by a released version of `ritual`.
+pub const INITIAL_BINARY_SCHEMA_VERSION: u32 = 2;
+
+/// A decoder of binary databases of an older schema version.
+pub struct BinaryDecoder {
+    pub schema_version: u32,
+    /// Decodes the database and returns its JSON representation.
+    pub function: fn(&mut dyn Read) -> Result<Value>,
+}
+
+/// Decoders of binary databases of all older schema versions,
+/// starting with `INITIAL_BINARY_SCHEMA_VERSION`.
+pub static BINARY_DECODERS: &[BinaryDecoder] = &[];
+
+/// Returns content of `value` if it's a serialized non-unit variant `name` of an enum.
+fn variant_mut<'a>(value: &'a mut Value, name: &str) -> Option<&'a mut Value> {
+    value
+        .as_object_mut()
+        .filter(|object| object.len() == 1)
+        .and_then(|object| object.get_mut(name))
+}
+
+fn object_mut<'a>(value: &'a mut Value, what: &str) -> Result<&'a mut Map<String, Value>> {
+    value
+        .as_object_mut()
+        .ok_or_else(|| format_err!("{} must be an object", what))
+}
+
+/// Converts databases created before the following changes:
+///
+/// - `CppFunctionArgument::has_default_value` was replaced with `default_value`;
+/// - `CppFunction` got `is_noexcept`, `is_deleted`, `deprecation`, `has_c_linkage`
+///   and `is_hidden_friend` fields;
+/// - `CppTypeDeclarationKind::Enum` and `RustWrapperTypeKind::EnumWrapper` got
+///   the underlying type of the enum;
+/// - `CppFfiFunction` got `is_direct_call` field.
+///
+/// New fields get values that older versions of `ritual` assumed implicitly
+/// (e.g. all enums were `int`-based). Expressions of default values of arguments
+/// were not recorded, so they are replaced with `…`.
+fn migrate_from_v1(value: &mut Value) -> Result<()> {
+    let items = value
+        .get_mut("items")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| err_msg("database must contain items array"))?;
+    for item in items {
+        let data = item
+            .get_mut("item")
+            .ok_or_else(|| err_msg("item data is missing"))?;
+        if let Some(cpp_item) = variant_mut(data, "CppItem") {
+            if let Some(function) = variant_mut(cpp_item, "Function") {
+                let function = object_mut(function, "C++ function")?;
+                for &name in &[
+                    "is_noexcept",
+                    "is_deleted",
+                    "has_c_linkage",
+                    "is_hidden_friend",
+                ] {
+                    function.insert(name.to_string(), false.into());
+                }
+                function.insert("deprecation".to_string(), Value::Null);
+                let arguments = function
+                    .get_mut("arguments")
+                    .and_then(Value::as_array_mut)
+                    .ok_or_else(|| err_msg("C++ function must contain arguments array"))?;
+                for argument in arguments {
+                    let argument = object_mut(argument, "C++ function argument")?;
+                    let has_default_value = argument
+                        .remove("has_default_value")
+                        .and_then(|value| value.as_bool())
+                        .ok_or_else(|| err_msg("has_default_value is missing"))?;
+                    let default_value = if has_default_value {
+                        json!({ "text": "…", "value": null })
+                    } else {
+                        Value::Null
+                    };
+                    argument.insert("default_value".to_string(), default_value);
+                }
+            }
+            if let Some(declaration) = variant_mut(cpp_item, "Type") {
+                let kind = declaration
+                    .get_mut("kind")
+                    .ok_or_else(|| err_msg("C++ type declaration must contain kind"))?;
+                if *kind == "Enum" {
+                    *kind = json!({
+                        "Enum": {
+                            "underlying_type": { "BuiltInNumeric": "Int" },
+                            "is_scoped": false,
+                        }
+                    });
+                }
+            }
+        }
+        if let Some(function) =
+            variant_mut(data, "FfiItem").and_then(|ffi_item| variant_mut(ffi_item, "Function"))
+        {
+            object_mut(function, "FFI function")?
+                .insert("is_direct_call".to_string(), false.into());
+        }
+        if let Some(rust_struct) =
+            variant_mut(data, "RustItem").and_then(|rust_item| variant_mut(rust_item, "Struct"))
+        {
+            let wrapper_kind = rust_struct
+                .get_mut("kind")
+                .and_then(|kind| variant_mut(kind, "WrapperType"));
+            if let Some(wrapper_kind) = wrapper_kind {
+                if *wrapper_kind == "EnumWrapper" {
+                    *wrapper_kind = json!({
+                        "EnumWrapper": {
+                            "underlying_type": {
+                                "Common": {
+                                    "path": { "parts": ["std", "os", "raw", "c_int"] },
+                                    "generic_arguments": null,
+                                }
+                            }
+                        }
+                    });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns schema version of the serialized database.
+pub fn schema_version(value: &Value) -> Result<u32> {
+    match value.get("schema_version") {
+        None => Ok(INITIAL_SCHEMA_VERSION),
+        Some(version) => version
+            .as_u64()
+            .map(|version| version as u32)
+            .ok_or_else(|| format_err!("invalid schema version: {}", version)),
+    }
+}
+
+/// Decodes the binary representation of a database of an older schema version.
+/// The result should be converted with `migrate`.
+pub fn decode_binary(schema_version: u32, reader: &mut dyn Read) -> Result<Value> {
+    let decoder = BINARY_DECODERS
+        .iter()
+        .find(|decoder| decoder.schema_version == schema_version)
+        .ok_or_else(|| {
+            format_err!(
+                "binary databases of schema version {} are not supported; \
+                 discard the database",
+                schema_version
+            )
+        })?;
+    let mut value = (decoder.function)(reader)?;
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| format_err!("database must be an object"))?;
+    object.insert("schema_version".to_string(), schema_version.into());
+    Ok(value)
+}
+
+/// Converts the serialized database to the current schema version.
+pub fn migrate(value: &mut Value) -> Result<()> {
+    migrate_with(value, MIGRATIONS, SCHEMA_VERSION)
+}
+
+fn migrate_with(value: &mut Value, migrations: &[Migration], target_version: u32) -> Result<()> {
+    let mut version = schema_version(value)?;
+    if version > target_version {
+        bail!(
+            "database schema version ({}) is newer than supported by this version of ritual ({}); \
+             update ritual or discard the database",
+            version,
+            target_version
+        );
+    }
+    while version < target_version {
+        let migration = migrations
+            .iter()
+            .find(|migration| migration.from_version == version)
+            .ok_or_else(|| format_err!("no migration from schema version {}", version))?;
+        info!(
+            "Migrating database from schema version {}: {}",
+            version, migration.description
+        );
+        (migration.function)(value)
+            .with_context(|_| format!("migration failed: {}", migration.description))?;
+        version += 1;
+        let object = value
+            .as_object_mut()
+            .ok_or_else(|| format_err!("database must be an object"))?;
+        object.insert("schema_version".to_string(), version.into());
+    }
+    Ok(())
+}
+
+#[test]
+fn migrations_are_complete() {
+    for version in INITIAL_SCHEMA_VERSION..SCHEMA_VERSION {
+        assert_eq!(
+            MIGRATIONS
+                .iter()
+                .filter(|migration| migration.from_version == version)
+                .count(),
+            1
+        );
+    }
+}
+
+#[test]
+fn binary_decoders_are_complete() {
+    for version in INITIAL_BINARY_SCHEMA_VERSION..SCHEMA_VERSION {
+        assert_eq!(
+            BINARY_DECODERS
+                .iter()
+                .filter(|decoder| decoder.schema_version == version)
+                .count(),
+            1
+        );
+    }
+}
+
+#[test]
+fn migrate_value() {
+    let migrations = [
+        Migration {
+            from_version: 1,
+            description: "rename a to b",
+            function: |value| {
+                let object = value.as_object_mut().unwrap();
+                let a = object.remove("a").unwrap();
+                object.insert("b".to_string(), a);
+                Ok(())
+            },
+        },
+        Migration {
+            from_version: 2,
+            description: "double b",
+            function: |value| {
+                let b = value["b"].as_u64().unwrap();
+                value["b"] = (b * 2).into();
+                Ok(())
+            },
+        },
+    ];
+
+    let mut value = serde_json::json!({ "a": 1 });
+    migrate_with(&mut value, &migrations, 3).unwrap();
+    assert_eq!(value, serde_json::json!({ "b": 2, "schema_version": 3 }));
+
+    let mut value = serde_json::json!({ "b": 1, "schema_version": 2 });
+    migrate_with(&mut value, &migrations, 3).unwrap();
+    assert_eq!(value, serde_json::json!({ "b": 2, "schema_version": 3 }));
+
+    let mut value = serde_json::json!({ "schema_version": 4 });
+    assert!(migrate_with(&mut value, &migrations, 3).is_err());
+
+    let mut value = serde_json::json!({ "a": 1 });
+    assert!(migrate_with(&mut value, &migrations[1..], 3).is_err());
+}
+
+#[test]
+fn migrate_v1_database() {
+    use crate::cpp_data::CppTypeDeclarationKind;
+    use crate::cpp_type::{CppBuiltInNumericType, CppType};
+    use crate::database::Database;
+    use crate::database_file;
+    use crate::rust_info::{RustStructKind, RustWrapperTypeKind};
+    use crate::rust_type::RustType;
+
+    // database created by ritual before schema versions were introduced
+    let v1_database = r#"{
+        "crate_name": "a",
+        "crate_version": "0.1.0",
+        "items": [
+            {
+                "id": { "crate_name": "a", "id": 0 },
+                "source_id": null,
+                "item": { "CppItem": { "Type": {
+                    "path": { "items": [{ "name": "E", "template_arguments": null }] },
+                    "kind": "Enum"
+                } } }
+            },
+            {
+                "id": { "crate_name": "a", "id": 1 },
+                "source_id": null,
+                "item": { "CppItem": { "Function": {
+                    "path": { "items": [{ "name": "f", "template_arguments": null }] },
+                    "member": null,
+                    "operator": null,
+                    "return_type": "Void",
+                    "arguments": [
+                        {
+                            "name": "x",
+                            "argument_type": { "BuiltInNumeric": "Int" },
+                            "has_default_value": true
+                        },
+                        {
+                            "name": "y",
+                            "argument_type": { "BuiltInNumeric": "Int" },
+                            "has_default_value": false
+                        }
+                    ],
+                    "allows_variadic_arguments": false,
+                    "cast": null,
+                    "declaration_code": "void f(int x = 1, int y);"
+                } } }
+            },
+            {
+                "id": { "crate_name": "a", "id": 2 },
+                "source_id": { "crate_name": "a", "id": 1 },
+                "item": { "FfiItem": { "Function": {
+                    "arguments": [{
+                        "name": "x",
+                        "argument_type": {
+                            "original_type": { "BuiltInNumeric": "Int" },
+                            "ffi_type": { "BuiltInNumeric": "Int" },
+                            "conversion": "NoChange"
+                        },
+                        "meaning": { "Argument": 0 }
+                    }],
+                    "return_type": {
+                        "original_type": "Void",
+                        "ffi_type": "Void",
+                        "conversion": "NoChange"
+                    },
+                    "allocation_place": "NotApplicable",
+                    "path": { "items": [{ "name": "ctr_a_ffi_f", "template_arguments": null }] },
+                    "kind": "Function"
+                } } }
+            },
+            {
+                "id": { "crate_name": "a", "id": 3 },
+                "source_id": { "crate_name": "a", "id": 0 },
+                "item": { "RustItem": { "Struct": {
+                    "path": { "parts": ["a", "E"] },
+                    "kind": { "WrapperType": "EnumWrapper" },
+                    "is_public": true,
+                    "qt_receiver_data": null
+                } } }
+            }
+        ],
+        "targets": [],
+        "next_id": 4
+    }"#;
+
+    let check = |db: &Database| {
+        let items = db.items().map(|item| item.item).collect::<Vec<_>>();
+        let enum_kind = &items[0].as_cpp_item().unwrap().as_type_ref().unwrap().kind;
+        assert_eq!(
+            enum_kind,
+            &CppTypeDeclarationKind::Enum {
+                underlying_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+                is_scoped: false,
+            }
+        );
+
+        let function = items[1].as_cpp_item().unwrap().as_function_ref().unwrap();
+        assert_eq!(
+            function.arguments[0].default_value.as_ref().unwrap().text,
+            "…"
+        );
+        assert!(function.arguments[1].default_value.is_none());
+        assert!(!function.is_noexcept && !function.is_deleted && !function.is_hidden_friend);
+        assert!(!function.has_c_linkage && function.deprecation.is_none());
+
+        let ffi_function = items[2].as_ffi_item().unwrap().as_function_ref().unwrap();
+        assert!(!ffi_function.is_direct_call);
+
+        let rust_struct = items[3].as_rust_item().unwrap().as_struct_ref().unwrap();
+        match &rust_struct.kind {
+            RustStructKind::WrapperType(RustWrapperTypeKind::EnumWrapper { underlying_type }) => {
+                match underlying_type {
+                    RustType::Common(common) => {
+                        assert_eq!(common.path.full_name(None), "std::os::raw::c_int")
+                    }
+                    _ => panic!("unexpected underlying type: {:?}", underlying_type),
+                }
+            }
+            kind => panic!("unexpected struct kind: {:?}", kind),
+        }
+    };
+
+    let dir = tempdir::TempDir::new("ritual_database_migrations").unwrap();
+    let json_path = dir.path().join("v1.json");
+    std::fs::write(&json_path, v1_database).unwrap();
+    let db = database_file::load(&json_path).unwrap();
+    assert_eq!(db.schema_version(), SCHEMA_VERSION);
+    check(&db);
+
+    // binary databases of schema version 1 were only created by development versions
+    let binary_path = dir.path().join("v1.db");
+    let mut data = database_file::MAGIC.to_vec();
+    data.extend(
+        bincode::serialize(&database_file::Header {
+            schema_version: 1,
+            crate_name: "a".to_string(),
+        })
+        .unwrap(),
+    );
+    std::fs::write(&binary_path, data).unwrap();
+    assert!(database_file::load(&binary_path).is_err());
+}
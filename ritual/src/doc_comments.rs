@@ -0,0 +1,228 @@
+//! Conversion of Doxygen comments found in C++ headers
+//! to documentation items.
+
+use crate::database::DocItem;
+use itertools::Itertools;
+
+/// Contents of a Doxygen comment split by commands.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct DoxygenComment {
+    /// Text of the `@brief` command, if any
+    pub brief: Option<String>,
+    /// Paragraphs of the detailed description
+    pub details: Vec<String>,
+    /// Names and descriptions of `@param` commands
+    pub params: Vec<(String, String)>,
+    /// Text of the `@return` command, if any
+    pub returns: Option<String>,
+}
+
+/// Target of the text following a command.
+enum Section {
+    Details,
+    Brief,
+    Param(usize),
+    Returns,
+}
+
+impl DoxygenComment {
+    /// Parses raw text of a comment, including comment markers
+    /// (`///`, `//!`, `/** */` or `/*! */`).
+    pub fn parse(raw_comment: &str) -> Self {
+        let mut comment = DoxygenComment::default();
+        let mut section = Section::Details;
+        let mut paragraph = Vec::new();
+        for line in raw_comment.lines().map(strip_comment_markers) {
+            if line.is_empty() {
+                comment.push_text(&section, &mut paragraph);
+                section = Section::Details;
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let first_word = words.next().unwrap_or("");
+            let command = if first_word.starts_with('@') || first_word.starts_with('\\') {
+                Some(&first_word[1..])
+            } else {
+                None
+            };
+            match command {
+                Some("brief") | Some("short") => {
+                    comment.push_text(&section, &mut paragraph);
+                    section = Section::Brief;
+                    paragraph.push(words.join(" "));
+                }
+                Some(param) if param == "param" || param.starts_with("param[") => {
+                    comment.push_text(&section, &mut paragraph);
+                    let name = words.next().unwrap_or("").to_string();
+                    comment.params.push((name, String::new()));
+                    section = Section::Param(comment.params.len() - 1);
+                    paragraph.push(words.join(" "));
+                }
+                Some("return") | Some("returns") | Some("result") => {
+                    comment.push_text(&section, &mut paragraph);
+                    section = Section::Returns;
+                    paragraph.push(words.join(" "));
+                }
+                _ => paragraph.push(line.to_string()),
+            }
+        }
+        comment.push_text(&section, &mut paragraph);
+        comment
+    }
+
+    fn push_text(&mut self, section: &Section, paragraph: &mut Vec<String>) {
+        let text = paragraph.iter().filter(|s| !s.is_empty()).join(" ");
+        paragraph.clear();
+        if text.is_empty() {
+            return;
+        }
+        match *section {
+            Section::Details => self.details.push(text),
+            Section::Brief => self.brief = Some(text),
+            Section::Param(index) => self.params[index].1 = text,
+            Section::Returns => self.returns = Some(text),
+        }
+    }
+
+    /// Returns true if the comment doesn't contain any text.
+    pub fn is_empty(&self) -> bool {
+        self.brief.is_none()
+            && self.details.is_empty()
+            && self.params.is_empty()
+            && self.returns.is_none()
+    }
+
+    /// Formats the comment as HTML. The brief description (or the first paragraph)
+    /// comes first so that it can be used as a summary.
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        for paragraph in self.brief.iter().chain(&self.details) {
+            html += &format!("<p>{}</p>", format_text(paragraph));
+        }
+        if !self.params.is_empty() {
+            html += "<p>Arguments:</p><ul>";
+            for (name, description) in &self.params {
+                html += &format!(
+                    "<li><code>{}</code> - {}</li>",
+                    escape_html(name),
+                    format_text(description)
+                );
+            }
+            html += "</ul>";
+        }
+        if let Some(returns) = &self.returns {
+            html += &format!("<p>Returns: {}</p>", format_text(returns));
+        }
+        html
+    }
+}
+
+/// Removes comment markers and leading decoration from a line of a comment.
+fn strip_comment_markers(line: &str) -> &str {
+    let mut line = line.trim();
+    for prefix in &["///<", "//!<", "///", "//!", "/**<", "/*!<", "/**", "/*!"] {
+        if line.starts_with(prefix) {
+            line = &line[prefix.len()..];
+            break;
+        }
+    }
+    if line.ends_with("*/") {
+        line = &line[..line.len() - 2];
+    }
+    line.trim_start_matches('*').trim()
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Escapes `text` and converts inline commands
+/// (`@p name`, `@c name`, `@a name`) to code spans.
+fn format_text(text: &str) -> String {
+    let mut result = Vec::new();
+    let mut words = text.split_whitespace();
+    while let Some(word) = words.next() {
+        match word {
+            "@p" | "\\p" | "@c" | "\\c" | "@a" | "\\a" => {
+                if let Some(next) = words.next() {
+                    result.push(format!("<code>{}</code>", escape_html(next)));
+                }
+            }
+            _ => result.push(escape_html(word)),
+        }
+    }
+    result.join(" ")
+}
+
+/// Converts a raw Doxygen comment to a documentation item.
+/// Returns `None` if the comment is empty.
+pub fn doc_item_from_comment(raw_comment: &str) -> Option<DocItem> {
+    let comment = DoxygenComment::parse(raw_comment);
+    if comment.is_empty() {
+        return None;
+    }
+    Some(DocItem {
+        anchor: None,
+        html: comment.to_html(),
+        mismatched_declaration: None,
+        url: None,
+        cross_references: Vec::new(),
+    })
+}
+
+#[test]
+fn parse_line_comments() {
+    let comment = DoxygenComment::parse(
+        "/// @brief Resizes the buffer.\n\
+         ///\n\
+         /// Existing data is preserved\n\
+         /// if possible.\n\
+         /// @param size New size\n\
+         ///   in bytes.\n\
+         /// \\param[in] fill Value of @p size new bytes.\n\
+         /// @return True on success.",
+    );
+    assert_eq!(
+        comment,
+        DoxygenComment {
+            brief: Some("Resizes the buffer.".to_string()),
+            details: vec!["Existing data is preserved if possible.".to_string()],
+            params: vec![
+                ("size".to_string(), "New size in bytes.".to_string()),
+                (
+                    "fill".to_string(),
+                    "Value of @p size new bytes.".to_string()
+                ),
+            ],
+            returns: Some("True on success.".to_string()),
+        }
+    );
+    assert_eq!(
+        comment.to_html(),
+        "<p>Resizes the buffer.</p><p>Existing data is preserved if possible.</p>\
+         <p>Arguments:</p><ul><li><code>size</code> - New size in bytes.</li>\
+         <li><code>fill</code> - Value of <code>size</code> new bytes.</li></ul>\
+         <p>Returns: True on success.</p>"
+    );
+}
+
+#[test]
+fn parse_block_comment() {
+    let comment = DoxygenComment::parse(
+        "/**\n\
+         \x20* Returns 1 if a < b.\n\
+         \x20*/",
+    );
+    assert_eq!(comment.brief, None);
+    assert_eq!(comment.details, vec!["Returns 1 if a < b.".to_string()]);
+    assert_eq!(comment.to_html(), "<p>Returns 1 if a &lt; b.</p>");
+
+    assert!(doc_item_from_comment("/** */").is_none());
+}
+
+#[test]
+fn escape_html_special_chars() {
+    assert_eq!(escape_html("Vec<T> & co"), "Vec&lt;T&gt; &amp; co");
+}
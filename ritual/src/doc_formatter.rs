@@ -3,13 +3,16 @@
 
 #![allow(dead_code)]
 
+use crate::cpp_data::{CppItem, CppPath, CppTypeDeclarationKind};
 use crate::cpp_ffi_data::{CppFfiFunctionKind, CppFfiItem, CppFieldAccessorType};
+use crate::cpp_function::CppFunction;
 use crate::cpp_type::CppType;
 use crate::database::{DatabaseClient, DbItem, DocItem};
 use crate::rust_code_generator::rust_type_to_code;
 use crate::rust_info::{
-    RustEnumValue, RustFunction, RustFunctionKind, RustModule, RustModuleKind, RustQtReceiverType,
-    RustSpecialModuleKind, RustStruct, RustStructKind, RustWrapperTypeKind,
+    RustConstant, RustEnumValue, RustFunction, RustFunctionKind, RustModule, RustModuleKind,
+    RustQtReceiverType, RustSpecialModuleKind, RustStruct, RustStructField, RustStructKind,
+    RustTypeAlias, RustWrapperTypeKind,
 };
 use itertools::Itertools;
 use ritual_common::errors::{bail, err_msg, Result};
@@ -115,10 +118,18 @@ pub fn struct_doc(type1: DbItem<&RustStruct>, database: &DatabaseClient) -> Resu
                 .to_cpp_pseudo_code();
 
             match kind {
-                RustWrapperTypeKind::EnumWrapper => {
+                RustWrapperTypeKind::EnumWrapper { .. } => {
+                    let is_scoped = cpp_item
+                        .item
+                        .as_type_ref()
+                        .map_or(false, |type1| match type1.kind {
+                            CppTypeDeclarationKind::Enum { is_scoped, .. } => is_scoped,
+                            _ => false,
+                        });
                     writeln!(
                         output,
-                        "C++ enum: {}.\n",
+                        "C++ {}: {}.\n",
+                        if is_scoped { "enum class" } else { "enum" },
                         wrap_inline_cpp_code(&cpp_type_code)
                     )?;
                 }
@@ -129,8 +140,27 @@ pub fn struct_doc(type1: DbItem<&RustStruct>, database: &DatabaseClient) -> Resu
                         wrap_inline_cpp_code(&cpp_type_code)
                     )?;
                 }
+                RustWrapperTypeKind::CStruct { is_union } => {
+                    writeln!(
+                        output,
+                        "C {}: {}.\n",
+                        if *is_union { "union" } else { "struct" },
+                        wrap_inline_cpp_code(&cpp_type_code)
+                    )?;
+                }
                 RustWrapperTypeKind::MovableClassWrapper { .. } => {
-                    // not supported now
+                    let is_union = cpp_item
+                        .item
+                        .as_type_ref()
+                        .map_or(false, |t| t.kind.is_union());
+                    // movable classes are not supported now
+                    if is_union {
+                        writeln!(
+                            output,
+                            "C++ union: {}.\n",
+                            wrap_inline_cpp_code(&cpp_type_code)
+                        )?;
+                    }
                 }
             }
 
@@ -250,6 +280,112 @@ pub fn enum_value_doc(value: DbItem<&RustEnumValue>, database: &DatabaseClient)
     Ok(doc)
 }
 
+pub fn constant_doc(value: DbItem<&RustConstant>, database: &DatabaseClient) -> Result<String> {
+    let cpp_item = database
+        .source_cpp_item(&value.id)?
+        .ok_or_else(|| err_msg("source cpp item not found"))?;
+
+    let mut doc = format!(
+        "C++ constant: {}",
+        wrap_inline_cpp_code(&cpp_item.item.short_text())
+    );
+    if let Some(doc_item) = database.find_doc_for(&value.id)? {
+        doc = format!("{} ({})", doc_item.item.html, doc);
+    }
+    Ok(doc)
+}
+
+pub fn struct_field_doc(
+    field: DbItem<&RustStructField>,
+    database: &DatabaseClient,
+) -> Result<String> {
+    let cpp_item = database
+        .source_cpp_item(&field.id)?
+        .ok_or_else(|| err_msg("source cpp item not found"))?;
+    let cpp_field = cpp_item
+        .item
+        .as_field_ref()
+        .ok_or_else(|| err_msg("invalid source cpp item type"))?;
+
+    let mut doc = format!("C field: {}", wrap_inline_cpp_code(&cpp_field.short_text()));
+    if let Some(doc_item) = database.find_doc_for(&field.id)? {
+        doc = format!("{} ({})", doc_item.item.html, doc);
+    }
+    Ok(doc)
+}
+
+pub fn type_alias_doc(alias: DbItem<&RustTypeAlias>, database: &DatabaseClient) -> Result<String> {
+    let cpp_item = database
+        .source_cpp_item(&alias.id)?
+        .ok_or_else(|| err_msg("source cpp item not found"))?;
+    let cpp_alias = cpp_item
+        .item
+        .as_type_alias_ref()
+        .ok_or_else(|| err_msg("invalid source cpp item type"))?;
+
+    let mut doc = format!(
+        "Alias for the C++ type {}: {}",
+        wrap_inline_cpp_code(&cpp_alias.target_type.to_cpp_pseudo_code()),
+        wrap_inline_cpp_code(&cpp_alias.short_text())
+    );
+    if let Some(doc_item) = database.find_doc_for(&alias.id)? {
+        doc = format!("{} ({})", doc_item.item.html, doc);
+    }
+    Ok(doc)
+}
+
+/// Returns description of type aliases used in the declaration of `function`.
+/// The parser resolves aliases to their target types, so they are found
+/// by names used in the declaration code.
+fn type_aliases_text(function: &CppFunction, database: &DatabaseClient) -> Result<Option<String>> {
+    let code = match &function.declaration_code {
+        Some(code) => code,
+        None => return Ok(None),
+    };
+    let scope = function.path.parent_parts().unwrap_or(&[]);
+    let mut aliases = Vec::new();
+    let names = code
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+        .map(|name| name.trim_matches(':'))
+        .filter(|name| !name.is_empty() && !name.starts_with(|c: char| c.is_ascii_digit()))
+        .unique();
+    for name in names {
+        let name_path = match name.parse::<CppPath>() {
+            Ok(path) => path,
+            Err(_) => continue,
+        };
+        // unqualified names can refer to aliases in enclosing scopes
+        for scope_len in (0..=scope.len()).rev() {
+            let mut items = scope[..scope_len].to_vec();
+            items.extend(name_path.items().iter().cloned());
+            let cpp_path = CppPath::from_items(items);
+            let rust_alias = match database.find_rust_items_for_cpp_path(&cpp_path, true) {
+                Ok(mut items) => items.find_map(|item| item.item.as_type_alias_ref()),
+                Err(_) => None,
+            };
+            if let Some(rust_alias) = rust_alias {
+                let text = format!(
+                    "{} (`{}`)",
+                    wrap_inline_cpp_code(&cpp_path.to_cpp_pseudo_code()),
+                    rust_alias.path.full_name(Some(database.crate_name()))
+                );
+                if !aliases.contains(&text) {
+                    aliases.push(text);
+                }
+                break;
+            }
+        }
+    }
+    if aliases.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(format!(
+            "The C++ declaration uses type aliases: {}.",
+            aliases.join(", ")
+        )))
+    }
+}
+
 fn format_maybe_link(url: &Option<String>, text: &str) -> String {
     if let Some(url) = url {
         format!("<a href=\"{}\">{}</a>", url, text)
@@ -369,6 +505,7 @@ pub fn function_doc(function: DbItem<&RustFunction>, database: &DatabaseClient)
 
             match &cpp_ffi_function.kind {
                 CppFfiFunctionKind::Function => {
+                    let cpp_item_id = cpp_item.id.clone();
                     let cpp_item = cpp_item
                         .item
                         .as_function_ref()
@@ -378,41 +515,74 @@ pub fn function_doc(function: DbItem<&RustFunction>, database: &DatabaseClient)
                         "Calls C++ function: {}.\n\n",
                         wrap_inline_cpp_code(&cpp_item.short_text())
                     )?;
+                    if let Some(text) = type_aliases_text(cpp_item, database)? {
+                        write!(output, "{}\n\n", text)?;
+                    }
 
-                    // TODO: detect omitted arguments using source_id
-                    /*if let Some(arguments_before_omitting) =
-                        &cpp_function.doc.arguments_before_omitting
-                    {
-                        // TODO: handle singular/plural form
-                        doc.push(format!(
-                            "This version of the function omits some arguments ({}).\n\n",
-                            arguments_before_omitting.len() - cpp_function.arguments.len()
-                        ));
-                    }*/
+                    let source_function = database
+                        .source_cpp_item(&cpp_item_id)?
+                        .and_then(|item| item.item.as_function_ref());
+                    if let Some(source_function) = source_function {
+                        if source_function.arguments.len() > cpp_item.arguments.len() {
+                            let omitted = source_function.arguments[cpp_item.arguments.len()..]
+                                .iter()
+                                .filter_map(|arg| {
+                                    let default_value = arg.default_value.as_ref()?;
+                                    Some(wrap_inline_cpp_code(&format!(
+                                        "{} = {}",
+                                        arg.name, default_value.text
+                                    )))
+                                })
+                                .join(", ");
+                            write!(
+                                output,
+                                "This version of the function omits some arguments \
+                                 and uses their default values: {}.\n\n",
+                                omitted
+                            )?;
+                        }
+                    }
                 }
                 CppFfiFunctionKind::FieldAccessor { accessor_type } => {
-                    let cpp_item = cpp_item
-                        .item
-                        .as_field_ref()
-                        .ok_or_else(|| err_msg("invalid source cpp item type"))?;
-                    let field_text =
-                        wrap_inline_cpp_code(&cpp_item.path.last().to_cpp_pseudo_code());
+                    let (field_text, kind_text) = match &cpp_item.item {
+                        CppItem::ClassField(field) => (
+                            wrap_inline_cpp_code(&field.path.last().to_cpp_pseudo_code()),
+                            "field",
+                        ),
+                        CppItem::GlobalVariable(variable) => (
+                            wrap_inline_cpp_code(&variable.path.to_cpp_pseudo_code()),
+                            "variable",
+                        ),
+                        _ => bail!("invalid source cpp item type"),
+                    };
                     match *accessor_type {
                         CppFieldAccessorType::CopyGetter => {
-                            write!(output, "Returns the value of the {} field.", field_text)?;
+                            write!(
+                                output,
+                                "Returns the value of the {} {}.",
+                                field_text, kind_text
+                            )?;
                         }
                         CppFieldAccessorType::ConstRefGetter => {
-                            write!(output, "Returns a reference to the {} field.", field_text)?;
+                            write!(
+                                output,
+                                "Returns a reference to the {} {}.",
+                                field_text, kind_text
+                            )?;
                         }
                         CppFieldAccessorType::MutRefGetter => {
                             write!(
                                 output,
-                                "Returns a mutable reference to the {} field.",
-                                field_text
+                                "Returns a mutable reference to the {} {}.",
+                                field_text, kind_text
                             )?;
                         }
                         CppFieldAccessorType::Setter => {
-                            write!(output, "Sets the value of the {} field.", field_text)?;
+                            write!(
+                                output,
+                                "Sets the value of the {} {}.",
+                                field_text, kind_text
+                            )?;
                         }
                     };
                 }
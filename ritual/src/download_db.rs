@@ -1,6 +1,6 @@
 // inspired by https://github.com/Xion/cargo-download/
 
-use crate::database::CRATE_DB_FILE_NAME;
+use crate::database::{CRATE_DB_FILE_NAME, LEGACY_CRATE_DB_FILE_NAME};
 use log::{info, trace};
 use reqwest::header::CONTENT_LENGTH;
 use ritual_common::errors::{bail, Result};
@@ -40,11 +40,14 @@ pub fn download_db(crate_name: &str, crate_version: &str, path: impl AsRef<Path>
     let gzip = flate2::read::GzDecoder::new(&bytes[..]);
     let mut archive = tar::Archive::new(gzip);
 
+    // the format of the file is detected when loading it
     for entry in archive.entries()? {
         let mut entry = entry?;
         let entry_path = entry.path()?;
         if entry_path.components().count() == 2
-            && entry_path.components().nth(1).unwrap().as_os_str() == CRATE_DB_FILE_NAME
+            && [CRATE_DB_FILE_NAME, LEGACY_CRATE_DB_FILE_NAME]
+                .iter()
+                .any(|name| entry_path.components().nth(1).unwrap().as_os_str() == *name)
         {
             info!("Unpacking database file");
             entry.unpack(path)?;
@@ -54,7 +57,8 @@ pub fn download_db(crate_name: &str, crate_version: &str, path: impl AsRef<Path>
     }
 
     bail!(
-        "database file ({:?}) not found in crate tarball",
-        CRATE_DB_FILE_NAME
+        "database file ({:?} or {:?}) not found in crate tarball",
+        CRATE_DB_FILE_NAME,
+        LEGACY_CRATE_DB_FILE_NAME
     );
 }
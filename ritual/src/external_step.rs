@@ -0,0 +1,364 @@
+//! Processing steps implemented by external executables.
+//!
+//! An external step is a program that receives a JSON object on stdin:
+//!
+//! ```json
+//! {
+//!     "protocol_version": 1,
+//!     "step": "my_step",
+//!     "crate_name": "my_crate",
+//!     "items": [{ "id": { "crate_name": "my_crate", "id": 1 }, "source_id": null, "item": { "CppItem": ... } }]
+//! }
+//! ```
+//!
+//! `items` contains items of the current crate (only items of the requested kinds
+//! if the step has an item filter) in the same representation as in the database file.
+//! The program must write a JSON object to stdout:
+//!
+//! ```json
+//! {
+//!     "add": [{ "source_id": null, "item": { "CppItem": ... } }],
+//!     "modify": [{ "id": { "crate_name": "my_crate", "id": 1 }, "source_id": null, "item": { "CppItem": ... } }],
+//!     "delete": [{ "crate_name": "my_crate", "id": 2 }]
+//! }
+//! ```
+//!
+//! All fields of the output are optional. Modifications are applied first,
+//! then deletions (that also delete all items derived from the deleted items),
+//! then additions. Added C++ items are subject to the filter hooks of the config.
+//! The program's stderr is passed through, and a non-zero exit code fails the step.
+
+use crate::database::{DatabaseItemData, DbItem, ItemId};
+use crate::processor::ProcessorData;
+use log::{debug, info};
+use ritual_common::errors::{bail, err_msg, format_err, Error, Result, ResultExt};
+use serde_derive::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::thread;
+
+/// Version of the protocol used to communicate with external steps.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Kind of a database item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    CppItem,
+    FfiItem,
+    CppChecksItem,
+    RustItem,
+    DocItem,
+    DirectivesItem,
+}
+
+impl FromStr for ItemKind {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        Ok(
+            serde_json::from_value(serde_json::Value::String(text.to_string()))
+                .with_context(|_| format!("invalid item kind: {}", text))?,
+        )
+    }
+}
+
+impl ItemKind {
+    pub fn of(item: &DatabaseItemData) -> Self {
+        match item {
+            DatabaseItemData::CppItem(_) => ItemKind::CppItem,
+            DatabaseItemData::FfiItem(_) => ItemKind::FfiItem,
+            DatabaseItemData::CppChecksItem(_) => ItemKind::CppChecksItem,
+            DatabaseItemData::RustItem(_) => ItemKind::RustItem,
+            DatabaseItemData::DocItem(_) => ItemKind::DocItem,
+            DatabaseItemData::DirectivesItem(_) => ItemKind::DirectivesItem,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StepInput<'a> {
+    protocol_version: u32,
+    step: &'a str,
+    crate_name: &'a str,
+    items: Vec<DbItem<&'a DatabaseItemData>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NewItem {
+    source_id: Option<ItemId>,
+    item: DatabaseItemData,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StepOutput {
+    #[serde(default)]
+    add: Vec<NewItem>,
+    #[serde(default)]
+    modify: Vec<DbItem<DatabaseItemData>>,
+    #[serde(default)]
+    delete: Vec<ItemId>,
+}
+
+/// A processing step that runs an external program.
+/// See module documentation for the protocol.
+#[derive(Debug, Clone)]
+pub struct ExternalStep {
+    name: String,
+    program: PathBuf,
+    args: Vec<String>,
+    item_kinds: Vec<ItemKind>,
+}
+
+impl ExternalStep {
+    /// Creates a step `name` that runs `program`.
+    pub fn new(name: impl Into<String>, program: impl Into<PathBuf>) -> Self {
+        ExternalStep {
+            name: name.into(),
+            program: program.into(),
+            args: Vec::new(),
+            item_kinds: Vec::new(),
+        }
+    }
+
+    /// Parses a step specified as `name=program arg1 arg2...`.
+    pub fn from_spec(spec: &str) -> Result<Self> {
+        let mut parts = spec.splitn(2, '=');
+        let name = parts.next().unwrap_or("").trim();
+        let command = parts.next().ok_or_else(|| {
+            format_err!("invalid external step (expected name=command): {}", spec)
+        })?;
+        let mut command = command.split_whitespace();
+        let program = command
+            .next()
+            .ok_or_else(|| format_err!("missing program in external step: {}", spec))?;
+        if name.is_empty() {
+            bail!("missing name in external step: {}", spec);
+        }
+        if name.chars().any(char::is_uppercase) {
+            // operations specified in the command line are converted to lowercase
+            bail!("name of external step must be lowercase: {}", spec);
+        }
+        let mut step = ExternalStep::new(name, program);
+        step.add_args(command);
+        Ok(step)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Adds command line arguments of the program.
+    pub fn add_args<Item, Iter>(&mut self, items: Iter)
+    where
+        Item: Into<String>,
+        Iter: IntoIterator<Item = Item>,
+    {
+        for item in items {
+            self.args.push(item.into());
+        }
+    }
+
+    /// Adds a kind of items passed to the program.
+    /// All items are passed if no kinds are added.
+    pub fn add_item_kind(&mut self, kind: ItemKind) {
+        if !self.item_kinds.contains(&kind) {
+            self.item_kinds.push(kind);
+        }
+    }
+
+    fn accepts(&self, item: &DatabaseItemData) -> bool {
+        self.item_kinds.is_empty() || self.item_kinds.contains(&ItemKind::of(item))
+    }
+
+    /// Runs the program and applies its output to the database.
+    pub fn run(&self, data: &mut ProcessorData<'_>) -> Result<()> {
+        let input = StepInput {
+            protocol_version: PROTOCOL_VERSION,
+            step: &self.name,
+            crate_name: data.db.crate_name(),
+            items: data
+                .db
+                .items()
+                .filter(|item| self.accepts(item.item))
+                .collect(),
+        };
+        let input = serde_json::to_vec(&input)?;
+
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit());
+        info!("Running external step {}: {:?}", self.name, command);
+        let mut child = command
+            .spawn()
+            .with_context(|_| format!("failed to run command: {:?}", command))?;
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| err_msg("failed to open stdin of external step"))?;
+        // write in a separate thread so that the program can write its output
+        // before it has read all of the input
+        let writer = thread::spawn(move || stdin.write_all(&input));
+        let output = child
+            .wait_with_output()
+            .with_context(|_| format!("failed to run command: {:?}", command))?;
+        if !output.status.success() {
+            bail!("command failed with {}: {:?}", output.status, command);
+        }
+        writer
+            .join()
+            .map_err(|_| err_msg("failed to write input of external step"))?
+            .with_context(|_| "failed to write input of external step")?;
+
+        let output: StepOutput = serde_json::from_slice(&output.stdout)
+            .with_context(|_| format!("invalid output of external step {}", self.name))?;
+        self.apply(data, output)
+    }
+
+    fn apply(&self, data: &mut ProcessorData<'_>, output: StepOutput) -> Result<()> {
+        debug!(
+            "external step {}: {} added, {} modified, {} deleted",
+            self.name,
+            output.add.len(),
+            output.modify.len(),
+            output.delete.len()
+        );
+        data.db.replace_items(output.modify)?;
+
+        for id in &output.delete {
+            if id.crate_name() != data.db.crate_name() {
+                bail!("can't delete item of dependency: {}", id);
+            }
+            data.db.item(id)?;
+        }
+        data.db
+            .delete_items(|item| output.delete.contains(&item.id));
+
+        for new_item in output.add {
+            if let Some(source_id) = &new_item.source_id {
+                data.db
+                    .item(source_id)
+                    .with_context(|_| "invalid source id of added item")?;
+            }
+            add_item(data, new_item)?;
+        }
+        Ok(())
+    }
+}
+
+fn add_item(data: &mut ProcessorData<'_>, new_item: NewItem) -> Result<()> {
+    let source_id = new_item.source_id;
+    let required_source_id =
+        |id: Option<ItemId>| id.ok_or_else(|| err_msg("source id is required for this item kind"));
+    match new_item.item {
+        DatabaseItemData::CppItem(item) => {
+            data.add_cpp_item(source_id, item)?;
+        }
+        DatabaseItemData::FfiItem(item) => {
+            data.db.add_ffi_item(source_id, item)?;
+        }
+        DatabaseItemData::RustItem(item) => {
+            data.db.add_rust_item(source_id, item)?;
+        }
+        DatabaseItemData::CppChecksItem(item) => {
+            data.db
+                .add_cpp_checks_item(required_source_id(source_id)?, item);
+        }
+        DatabaseItemData::DocItem(item) => {
+            data.db.add_doc_item(required_source_id(source_id)?, item);
+        }
+        DatabaseItemData::DirectivesItem(item) => {
+            data.db
+                .add_directives_item(required_source_id(source_id)?, item);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn parse_spec() {
+    let step = ExternalStep::from_spec("my_step=python3 script.py --verbose").unwrap();
+    assert_eq!(step.name(), "my_step");
+    assert_eq!(step.program, PathBuf::from("python3"));
+    assert_eq!(step.args, vec!["script.py", "--verbose"]);
+
+    assert!(ExternalStep::from_spec("my_step").is_err());
+    assert!(ExternalStep::from_spec("=python3").is_err());
+    assert!(ExternalStep::from_spec("my_step=").is_err());
+    assert!(ExternalStep::from_spec("MyStep=python3").is_err());
+}
+
+#[test]
+fn parse_output() {
+    let output: StepOutput =
+        serde_json::from_str(r#"{ "delete": [{ "crate_name": "a", "id": 2 }], "modify": [] }"#)
+            .unwrap();
+    assert!(output.add.is_empty());
+    assert_eq!(output.delete, vec![ItemId::new("a".into(), 2)]);
+
+    assert!(serde_json::from_str::<StepOutput>(r#"{ "remove": [] }"#).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn run_external_steps() {
+    use crate::config::{Config, CrateProperties};
+    use crate::cpp_data::{CppItem, CppNamespace, CppPath};
+    use crate::processor;
+    use crate::workspace::Workspace;
+
+    let dir = tempdir::TempDir::new("test_external_step").unwrap();
+    let mut workspace = Workspace::new(dir.path().into()).unwrap();
+    let mut config = Config::new(CrateProperties::new("A", "0.0.0"));
+
+    let item = DatabaseItemData::CppItem(CppItem::Namespace(CppNamespace {
+        path: CppPath::from_good_str("ns1"),
+    }));
+    let script = format!(
+        "cat > /dev/null; echo '{{ \"add\": [{{ \"source_id\": null, \"item\": {} }}] }}'",
+        serde_json::to_string(&item).unwrap()
+    );
+    let mut step = ExternalStep::new("add_namespace", "sh");
+    step.add_args(vec!["-c".to_string(), script]);
+    config
+        .processing_steps_mut()
+        .add_external_custom(step)
+        .unwrap();
+
+    let mut step = ExternalStep::new("fail", "sh");
+    step.add_args(vec!["-c", "cat > /dev/null; echo '{}'; exit 3"]);
+    config
+        .processing_steps_mut()
+        .add_external_custom(step)
+        .unwrap();
+
+    // names must be unique
+    assert!(config
+        .processing_steps_mut()
+        .add_external_custom(ExternalStep::new("fail", "true"))
+        .is_err());
+    assert!(config
+        .processing_steps_mut()
+        .add_custom("cpp_parser", |_| Ok(()))
+        .is_err());
+
+    processor::process(&mut workspace, &config, &["add_namespace".into()], None).unwrap();
+    let err = processor::process(&mut workspace, &config, &["fail".into()], None).unwrap_err();
+    assert!(err.to_string().starts_with("command failed"));
+
+    let db = workspace
+        .get_database_client("A", &[], true, false)
+        .unwrap();
+    let namespaces = db
+        .cpp_items()
+        .filter_map(|item| item.item.as_namespace_ref())
+        .map(|namespace| namespace.path.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(namespaces, vec![CppPath::from_good_str("ns1")]);
+}
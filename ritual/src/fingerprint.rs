@@ -0,0 +1,209 @@
+//! Fingerprints of inputs of processing steps.
+//!
+//! When the main procedure is requested, each step's fingerprint is compared
+//! to the one recorded in the database during the previous run, and only outdated
+//! steps are executed. A fingerprint covers the step's name and code version,
+//! the version of `ritual` and the parts of `Config` the step depends on
+//! (including contents of the target headers). Headers included by the target
+//! headers are recorded by the parser and checked separately (see `files_fingerprint`).
+//!
+//! Steps that write files outside of the database (see `UNTRACKED_STEPS`) are
+//! always run because their output can't be verified.
+//!
+//! Code of hooks can't be fingerprinted, so only names of the hooks are taken
+//! into account. Use `ProcessingSteps::set_version` or `discard` operation
+//! after changing behavior of a hook or a custom step.
+
+use crate::config::Config;
+use crate::database::DatabaseClient;
+use ritual_common::errors::{Result, ResultExt};
+use ritual_common::target::current_target;
+use sha2::{Digest, Sha256};
+use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Suffix of steps of the main procedure that are repeated for generated C++ items.
+const STAGE2_SUFFIX: &str = "_stage2";
+
+/// Steps of the main procedure that are run even if their inputs didn't change
+/// because they write files outside of the database.
+pub const UNTRACKED_STEPS: &[&str] = &["crate_writer", "build_crate"];
+
+/// A hasher for step inputs. Fingerprints are stored in the database,
+/// so the hash must not depend on the platform or the Rust version.
+struct Fingerprint(Sha256);
+
+impl Fingerprint {
+    fn new() -> Self {
+        Fingerprint(Sha256::new())
+    }
+
+    fn add_bytes(&mut self, bytes: &[u8]) {
+        self.0.input(&(bytes.len() as u64).to_le_bytes());
+        self.0.input(bytes);
+    }
+
+    fn add(&mut self, label: &str, value: impl Debug) {
+        self.add_bytes(label.as_bytes());
+        self.add_bytes(format!("{:?}", value).as_bytes());
+    }
+
+    /// Adds paths and contents of the file or all files in the directory.
+    fn add_files(&mut self, path: &Path) -> Result<()> {
+        if path.is_dir() {
+            let mut entries = fs::read_dir(path)
+                .with_context(|_| format!("failed to read dir: {}", path.display()))?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<std::io::Result<Vec<_>>>()?;
+            entries.sort();
+            for entry in entries {
+                self.add_files(&entry)?;
+            }
+        } else if path.exists() {
+            self.add_bytes(path.to_string_lossy().as_bytes());
+            let content = fs::read(path)
+                .with_context(|_| format!("failed to read file: {}", path.display()))?;
+            self.add_bytes(&content);
+        } else {
+            self.add("missing", path);
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> String {
+        self.0
+            .result()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+/// Returns fingerprint of paths and contents of `files`.
+pub fn files_fingerprint(files: &[PathBuf]) -> Result<String> {
+    let mut fingerprint = Fingerprint::new();
+    for path in files {
+        fingerprint.add_files(path)?;
+    }
+    Ok(fingerprint.finish())
+}
+
+/// Returns true if the step's output is stored in the database, so the step
+/// can be skipped if its fingerprint didn't change.
+pub fn is_tracked(step_name: &str) -> bool {
+    !UNTRACKED_STEPS.contains(&step_name)
+}
+
+/// Calculates fingerprints of steps of the main procedure.
+pub struct StepFingerprints<'a> {
+    config: &'a Config,
+    headers: Option<String>,
+}
+
+impl<'a> StepFingerprints<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        StepFingerprints {
+            config,
+            headers: None,
+        }
+    }
+
+    fn headers(&mut self) -> Result<String> {
+        if self.headers.is_none() {
+            let mut fingerprint = Fingerprint::new();
+            for path in self.config.target_include_paths() {
+                fingerprint.add_files(path)?;
+            }
+            self.headers = Some(fingerprint.finish());
+        }
+        Ok(self.headers.clone().unwrap())
+    }
+
+    /// Returns fingerprint of the step `name` with code `version`.
+    pub fn get(&mut self, name: &str, version: &str, db: &DatabaseClient) -> Result<String> {
+        let config = self.config;
+        let mut fingerprint = Fingerprint::new();
+        fingerprint.add("ritual", env!("CARGO_PKG_VERSION"));
+        fingerprint.add("name", name);
+        fingerprint.add("version", version);
+
+        let base_name = if name.ends_with(STAGE2_SUFFIX) {
+            &name[..name.len() - STAGE2_SUFFIX.len()]
+        } else {
+            name
+        };
+        match base_name {
+            "cpp_parser" => {
+                fingerprint.add("headers", self.headers()?);
+                fingerprint.add("target", current_target());
+                fingerprint.add("include_directives", config.include_directives());
+                fingerprint.add("target_include_paths", config.target_include_paths());
+                fingerprint.add("cpp_parser_arguments", config.cpp_parser_arguments());
+                fingerprint.add("cpp_build_paths", config.cpp_build_paths());
+                fingerprint.add("cpp_build_config", config.cpp_build_config());
+                fingerprint.add("cpp_lib_version", config.cpp_lib_version());
+                fingerprint.add("parse_macro_constants", config.parse_macro_constants());
+                fingerprint.add("parse_doc_comments", config.parse_doc_comments());
+                fingerprint.add(
+                    "cpp_parser_path_hooks",
+                    config.cpp_parser_path_hooks().names().collect::<Vec<_>>(),
+                );
+                fingerprint.add(
+                    "cpp_item_filter_hooks",
+                    config.cpp_item_filter_hooks().names().collect::<Vec<_>>(),
+                );
+                fingerprint.add(
+                    "after_cpp_parser_hooks",
+                    config.after_cpp_parser_hooks().len(),
+                );
+                fingerprint.add("dependencies", db.dependencies_state());
+            }
+            "cpp_checker" => {
+                fingerprint.add("headers", self.headers()?);
+                fingerprint.add(
+                    "included_headers",
+                    db.step_record("cpp_parser")
+                        .map(|record| &record.input_files_fingerprint),
+                );
+                fingerprint.add("target", current_target());
+                fingerprint.add("include_directives", config.include_directives());
+                fingerprint.add("cpp_build_paths", config.cpp_build_paths());
+                fingerprint.add("cpp_build_config", config.cpp_build_config());
+                fingerprint.add("cpp_lib_version", config.cpp_lib_version());
+                fingerprint.add("cpp_checker_tests", config.cpp_checker_tests());
+                fingerprint.add("plain_c_mode", config.plain_c_mode());
+                fingerprint.add("catch_exceptions", config.catch_exceptions());
+            }
+            "cpp_ffi_generator" => {
+                fingerprint.add("plain_c_mode", config.plain_c_mode());
+                fingerprint.add("catch_exceptions", config.catch_exceptions());
+                fingerprint.add(
+                    "movable_types_hooks",
+                    config.movable_types_hooks().names().collect::<Vec<_>>(),
+                );
+            }
+            "rust_generator" => {
+                fingerprint.add(
+                    "movable_types_hooks",
+                    config.movable_types_hooks().names().collect::<Vec<_>>(),
+                );
+                fingerprint.add(
+                    "rust_path_scope_hooks",
+                    config.rust_path_scope_hooks().names().collect::<Vec<_>>(),
+                );
+                fingerprint.add(
+                    "rust_path_hooks",
+                    config.rust_path_hooks().names().collect::<Vec<_>>(),
+                );
+                fingerprint.add(
+                    "rust_item_hooks",
+                    config.rust_item_hooks().names().collect::<Vec<_>>(),
+                );
+                fingerprint.add("dependencies", db.dependencies_state());
+            }
+            _ => {}
+        }
+        Ok(fingerprint.finish())
+    }
+}
@@ -0,0 +1,317 @@
+//! Implementation of the `report` operation.
+//!
+//! The operation renders the database of the current crate into a static HTML site
+//! in `<workspace>/report/<crate name>`. `index.html` lists all C++ items of the crate,
+//! and each C++ item has its own page with the chain of its sources and all items
+//! derived from it: FFI items, results of C++ checks for each library target
+//! and Rust items.
+
+use crate::database::{DatabaseClient, DatabaseItemData, DbItem, ItemId};
+use crate::doc_comments::escape_html;
+use crate::processor::ProcessorData;
+use itertools::Itertools;
+use log::info;
+use ritual_common::errors::Result;
+use ritual_common::file_utils::{create_dir_all, create_file, remove_dir_all};
+use std::io::Write;
+use std::path::Path;
+
+/// Name of the operation.
+pub const REPORT_OPERATION: &str = "report";
+
+const STYLE: &str = include_str!("../templates/html_logger/style.css");
+const SCRIPT: &str = include_str!("../templates/html_logger/script.js");
+
+fn item_page_name(id: &ItemId) -> String {
+    format!("item_{}.html", id.id())
+}
+
+fn write_page(path: &Path, title: &str, rows: &[String]) -> Result<()> {
+    let mut file = create_file(path)?;
+    write!(
+        file,
+        include_str!("../templates/html_logger/header.html"),
+        title = escape_html(title)
+    )?;
+    for row in rows {
+        writeln!(file, "{}", row)?;
+    }
+    write!(
+        file,
+        "{}",
+        include_str!("../templates/html_logger/footer.html")
+    )?;
+    Ok(())
+}
+
+fn row(header: &str, cells: &[String]) -> String {
+    format!(
+        "<tr><th>{}</th>{}</tr>",
+        header,
+        cells
+            .iter()
+            .map(|cell| format!("<td>{}</td>", cell))
+            .join("")
+    )
+}
+
+fn html_list(items: impl IntoIterator<Item = String>) -> String {
+    let items = items
+        .into_iter()
+        .map(|item| format!("<li>{}</li>", item))
+        .join("");
+    if items.is_empty() {
+        String::new()
+    } else {
+        format!("<ul>{}</ul>", items)
+    }
+}
+
+fn item_text(item: &DatabaseItemData) -> String {
+    match item {
+        DatabaseItemData::CppChecksItem(item) => format!(
+            "{}: {}",
+            item.env.short_text(),
+            if item.is_success { "ok" } else { "failed" }
+        ),
+        _ => item.short_text(),
+    }
+}
+
+/// Returns HTML text of the item, with a link to its page if there is one.
+fn item_html(db: &DatabaseClient, item: &DbItem<&DatabaseItemData>) -> String {
+    let text = escape_html(&item_text(item.item));
+    if item.item.is_cpp_item() && item.id.crate_name() == db.crate_name() {
+        format!("<a href=\"{}\">{}</a>", item_page_name(&item.id), text)
+    } else {
+        format!("{} ({})", text, item.id.crate_name())
+    }
+}
+
+fn descendants_html(db: &DatabaseClient, item_id: &ItemId) -> String {
+    html_list(db.children(item_id).map(|child| {
+        format!(
+            "{}{}",
+            item_html(db, &child),
+            descendants_html(db, &child.id)
+        )
+    }))
+}
+
+fn index_row(db: &DatabaseClient, item: &DbItem<&DatabaseItemData>) -> String {
+    let descendants = db.descendants(&item.id);
+    let texts = |filter: fn(&DatabaseItemData) -> bool| {
+        html_list(
+            descendants
+                .iter()
+                .filter(|item| filter(item.item))
+                .map(|item| escape_html(&item_text(item.item))),
+        )
+    };
+    row(
+        &item.id.id().to_string(),
+        &[
+            item_html(db, item),
+            texts(DatabaseItemData::is_ffi_item),
+            texts(DatabaseItemData::is_cpp_checks_item),
+            texts(DatabaseItemData::is_rust_item),
+        ],
+    )
+}
+
+fn item_rows(db: &DatabaseClient, item: &DbItem<&DatabaseItemData>) -> Result<Vec<String>> {
+    let sources = db.source_chain(&item.id)?;
+    Ok(vec![
+        row("Item", &[escape_html(&item_text(item.item))]),
+        row("ID", &[escape_html(&item.id.to_string())]),
+        row(
+            "Sources",
+            &[html_list(
+                sources.iter().skip(1).map(|source| item_html(db, source)),
+            )],
+        ),
+        row("Derived items", &[descendants_html(db, &item.id)]),
+        row(
+            "Data",
+            &[format!(
+                "<pre>{}</pre>",
+                escape_html(&format!("{:#?}", item.item))
+            )],
+        ),
+    ])
+}
+
+/// Writes HTML report of the current crate's database.
+pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
+    let db = &*data.db;
+    let path = data.workspace.path().join("report").join(db.crate_name());
+    if path.exists() {
+        remove_dir_all(&path)?;
+    }
+    create_dir_all(&path)?;
+    write!(create_file(path.join("style.css"))?, "{}", STYLE)?;
+    write!(create_file(path.join("script.js"))?, "{}", SCRIPT)?;
+
+    let cpp_items = db
+        .items()
+        .filter(|item| item.item.is_cpp_item())
+        .collect_vec();
+    let mut index_rows = vec!["<tr><th>ID</th><th>C++ item</th><th>FFI items</th>\
+         <th>C++ checks</th><th>Rust items</th></tr>"
+        .to_string()];
+    for item in &cpp_items {
+        index_rows.push(index_row(db, item));
+        write_page(
+            &path.join(item_page_name(&item.id)),
+            &item_text(item.item),
+            &item_rows(db, item)?,
+        )?;
+    }
+    write_page(
+        &path.join("index.html"),
+        &format!("Report: {}", db.crate_name()),
+        &index_rows,
+    )?;
+    info!("Report written to {}", path.join("index.html").display());
+    Ok(())
+}
+
+#[test]
+fn html_helpers() {
+    assert_eq!(html_list(Vec::new()), "");
+    assert_eq!(
+        html_list(vec!["a".to_string(), "<b>".to_string()]),
+        "<ul><li>a</li><li><b></li></ul>"
+    );
+    assert_eq!(
+        row("A", &["1".to_string(), "2".to_string()]),
+        "<tr><th>A</th><td>1</td><td>2</td></tr>"
+    );
+}
+
+#[test]
+fn write_report() {
+    use crate::config::{Config, CrateProperties};
+    use crate::cpp_checks::CppChecksItem;
+    use crate::cpp_data::{CppItem, CppNamespace, CppPath};
+    use crate::cpp_ffi_data::{CppFfiFunction, CppFfiFunctionKind, CppFfiItem, CppFfiType};
+    use crate::cpp_function::ReturnValueAllocationPlace;
+    use crate::database::{Database, IndexedDatabase};
+    use crate::rust_info::{RustItem, RustModule, RustModuleKind, RustSpecialModuleKind};
+    use crate::rust_type::RustPath;
+    use crate::workspace::Workspace;
+    use ritual_common::target::{current_target, LibraryTarget};
+    use ritual_common::ReadOnly;
+    use std::fs;
+    use std::path::PathBuf;
+
+    let mut db = DatabaseClient::new(
+        IndexedDatabase::new(
+            Database::empty("crate1".to_string()),
+            PathBuf::from("crate1.json"),
+        ),
+        ReadOnly::new(Vec::new()),
+    );
+    let ns_id = db
+        .add_cpp_item_without_hook(
+            None,
+            CppItem::Namespace(CppNamespace {
+                path: CppPath::from_good_str("ns1"),
+            }),
+        )
+        .unwrap()
+        .unwrap();
+    let ffi_id = db
+        .add_ffi_item(
+            Some(ns_id.clone()),
+            CppFfiItem::Function(CppFfiFunction {
+                arguments: Vec::new(),
+                return_type: CppFfiType::void(),
+                allocation_place: ReturnValueAllocationPlace::NotApplicable,
+                path: CppPath::from_good_str("ctr_crate1_ffi_f"),
+                kind: CppFfiFunctionKind::Function,
+                is_direct_call: false,
+            }),
+        )
+        .unwrap()
+        .unwrap();
+    db.add_cpp_checks_item(
+        ffi_id,
+        CppChecksItem {
+            env: LibraryTarget {
+                target: current_target(),
+                cpp_library_version: Some("1.0".to_string()),
+            },
+            is_success: true,
+        },
+    )
+    .unwrap();
+    db.add_rust_item(
+        None,
+        RustItem::Module(RustModule {
+            is_public: true,
+            path: RustPath::from_good_str("crate1"),
+            kind: RustModuleKind::Special(RustSpecialModuleKind::CrateRoot),
+        }),
+    )
+    .unwrap()
+    .unwrap();
+    db.add_rust_item(
+        Some(ns_id.clone()),
+        RustItem::Module(RustModule {
+            is_public: true,
+            path: RustPath::from_good_str("crate1::ns1"),
+            kind: RustModuleKind::CppNamespace,
+        }),
+    )
+    .unwrap()
+    .unwrap();
+
+    let dir = tempdir::TempDir::new("ritual_html_report").unwrap();
+    let mut workspace = Workspace::new(dir.path().into()).unwrap();
+    let config = Config::new(CrateProperties::new("crate1", "0.1.0"));
+    run(&mut ProcessorData {
+        workspace: &mut workspace,
+        config: &config,
+        db: &mut db,
+    })
+    .unwrap();
+
+    let path = dir.path().join("report").join("crate1");
+    let mut files = fs::read_dir(&path)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect_vec();
+    files.sort();
+    let item_page = item_page_name(&ns_id);
+    assert_eq!(
+        files,
+        vec![
+            "index.html".to_string(),
+            item_page.clone(),
+            "script.js".to_string(),
+            "style.css".to_string(),
+        ]
+    );
+
+    let index = fs::read_to_string(path.join("index.html")).unwrap();
+    assert!(index.contains("<title>Report: crate1</title>"));
+    let expected_row = row(
+        &ns_id.id().to_string(),
+        &[
+            format!("<a href=\"{}\">namespace ns1</a>", item_page),
+            "<ul><li>ctr_crate1_ffi_f</li></ul>".to_string(),
+            format!(
+                "<ul><li>v1.0 on {}: ok</li></ul>",
+                current_target().short_text()
+            ),
+            "<ul><li>mod crate1::ns1</li></ul>".to_string(),
+        ],
+    );
+    assert!(index.contains(&expected_row));
+
+    let page = fs::read_to_string(path.join(&item_page)).unwrap();
+    assert!(page.contains("<tr><th>Item</th><td>namespace ns1</td></tr>"));
+    assert!(page.contains("<li>ctr_crate1_ffi_f (crate1)<ul><li>v1.0 on "));
+    assert!(page.contains("<li>mod crate1::ns1 (crate1)</li>"));
+}
@@ -8,6 +8,7 @@
 
 pub use ritual_common as common;
 
+pub mod api_diff;
 pub mod cli;
 pub mod cluster_api;
 pub mod config;
@@ -16,6 +17,7 @@ pub mod cpp_checker;
 mod cpp_checks;
 mod cpp_code_generator;
 pub mod cpp_data;
+mod cpp_directives;
 pub mod cpp_ffi_data;
 pub mod cpp_ffi_generator;
 pub mod cpp_function;
@@ -26,15 +28,27 @@ mod cpp_operator;
 pub mod cpp_parser;
 pub mod cpp_template_instantiator;
 pub mod cpp_type;
+mod crate_scheduler;
 mod crate_writer;
 pub mod database;
+pub mod database_file;
+pub mod database_migrations;
+mod doc_comments;
 mod doc_formatter;
 mod download_db;
+pub mod external_step;
+mod fingerprint;
+pub mod html_report;
+pub mod plan;
 pub mod processor;
+pub mod project_file;
+pub mod query;
 mod rust_code_generator;
 mod rust_generator;
 pub mod rust_info;
 pub mod rust_type;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_export;
 mod type_allocation_places;
 pub mod workspace;
 
@@ -0,0 +1,255 @@
+//! Implementation of the `plan` operation.
+//!
+//! The operation runs outdated steps from `cpp_parser` to `rust_generator`
+//! on an in-memory copy of the database, the same way the main procedure would,
+//! and compares the result with the saved database of the crate.
+//! Neither the database nor the crate is written.
+
+use crate::config::Config;
+use crate::database::{Database, DatabaseClient, DatabaseItemData, DbItem};
+use crate::fingerprint::StepFingerprints;
+use crate::processor::{self, ProcessorData};
+use crate::workspace::Workspace;
+use log::{debug, info};
+use ritual_common::errors::{Result, ResultExt};
+use serde_derive::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Name of the operation.
+pub const PLAN_OPERATION: &str = "plan";
+
+/// Added, removed and changed items of one kind.
+///
+/// Items are identified by their short text. References to other items of the crate
+/// are ignored when comparing items because item IDs are not preserved between runs.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ItemChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl ItemChanges {
+    fn new(old: BTreeMap<String, Vec<String>>, mut new: BTreeMap<String, Vec<String>>) -> Self {
+        let mut changes = ItemChanges::default();
+        for (key, old_values) in old {
+            match new.remove(&key) {
+                None => changes
+                    .removed
+                    .extend(old_values.iter().map(|_| key.clone())),
+                Some(new_values) => {
+                    if new_values != old_values {
+                        changes.changed.push(key);
+                    }
+                }
+            }
+        }
+        for (key, new_values) in new {
+            changes.added.extend(new_values.iter().map(|_| key.clone()));
+        }
+        changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    fn log_summary(&self, title: &str) {
+        info!(
+            "{}: {} added, {} removed, {} changed",
+            title,
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        );
+        for item in &self.added {
+            debug!("    added: {}", item);
+        }
+        for item in &self.removed {
+            debug!("    removed: {}", item);
+        }
+        for item in &self.changed {
+            debug!("    changed: {}", item);
+        }
+    }
+}
+
+/// Changes that a run of the main procedure would make in the database of a crate.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanReport {
+    pub crate_name: String,
+    pub cpp_items: ItemChanges,
+    pub ffi_items: ItemChanges,
+    pub rust_items: ItemChanges,
+}
+
+impl PlanReport {
+    fn new(old: &Database, new: &Database) -> Result<Self> {
+        Ok(PlanReport {
+            crate_name: old.crate_name().to_string(),
+            cpp_items: ItemChanges::new(
+                item_map(old, DatabaseItemData::is_cpp_item)?,
+                item_map(new, DatabaseItemData::is_cpp_item)?,
+            ),
+            ffi_items: ItemChanges::new(
+                item_map(old, DatabaseItemData::is_ffi_item)?,
+                item_map(new, DatabaseItemData::is_ffi_item)?,
+            ),
+            rust_items: ItemChanges::new(
+                item_map(old, DatabaseItemData::is_rust_item)?,
+                item_map(new, DatabaseItemData::is_rust_item)?,
+            ),
+        })
+    }
+
+    pub fn log_summary(&self) {
+        info!("Plan for crate: {}", self.crate_name);
+        self.cpp_items.log_summary("C++ items");
+        self.ffi_items.log_summary("FFI items");
+        self.rust_items.log_summary("Rust items");
+    }
+}
+
+/// Replaces IDs of items of `crate_name` with `null`.
+fn strip_item_ids(value: &mut Value, crate_name: &str) {
+    let is_item_id = value.as_object().map_or(false, |object| {
+        object.len() == 2
+            && object.contains_key("id")
+            && object.get("crate_name").and_then(Value::as_str) == Some(crate_name)
+    });
+    if is_item_id {
+        *value = Value::Null;
+        return;
+    }
+    match value {
+        Value::Object(object) => {
+            for item in object.values_mut() {
+                strip_item_ids(item, crate_name);
+            }
+        }
+        Value::Array(array) => {
+            for item in array {
+                strip_item_ids(item, crate_name);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn item_map(
+    db: &Database,
+    filter: impl Fn(&DatabaseItemData) -> bool,
+) -> Result<BTreeMap<String, Vec<String>>> {
+    let mut map = BTreeMap::<_, Vec<_>>::new();
+    for DbItem { item, .. } in db.items().filter(|item| filter(item.item)) {
+        let mut value = serde_json::to_value(item)?;
+        strip_item_ids(&mut value, db.crate_name());
+        map.entry(item.short_text())
+            .or_default()
+            .push(value.to_string());
+    }
+    for values in map.values_mut() {
+        values.sort();
+    }
+    Ok(map)
+}
+
+fn run_outdated_steps(
+    workspace: &mut Workspace,
+    config: &Config,
+    db: &mut DatabaseClient,
+    steps: &[String],
+) -> Result<()> {
+    let mut fingerprints = StepFingerprints::new(config);
+    let steps = processor::outdated_steps(config, db, &mut fingerprints, steps)?;
+    if steps.is_empty() {
+        info!("All steps are up to date");
+    }
+    for step in &steps {
+        info!("Running processing step: {}", step);
+        let mut data = ProcessorData {
+            workspace,
+            db,
+            config,
+        };
+        config.processing_steps().run(step, &mut data)?;
+        db.report_counters();
+    }
+    Ok(())
+}
+
+/// Runs outdated steps of the main procedure from `cpp_parser` to `rust_generator`
+/// on a copy of the current data and returns changes relative to the saved database.
+pub fn run(workspace: &mut Workspace, config: &Config) -> Result<PlanReport> {
+    let crate_name = config.crate_properties().name();
+    info!("Planning crate: {}", crate_name);
+    let steps = config
+        .processing_steps()
+        .main_procedure_range("cpp_parser", "rust_generator")?
+        .to_vec();
+
+    let mut db = workspace
+        .get_database_client(
+            crate_name,
+            config.crate_properties().dependencies(),
+            true,
+            true,
+        )
+        .with_context(|_| "failed to load current crate data")?;
+
+    let current_data = db.data().clone();
+    let saved_data = db.replace_data(current_data);
+    db.set_crate_version(config.crate_properties().version().to_string());
+    let steps_result = run_outdated_steps(workspace, config, &mut db, &steps);
+
+    // restore the saved data so that it's not affected by the plan
+    let new_data = db.replace_data(saved_data);
+    db.set_saved();
+    steps_result?;
+
+    let report = PlanReport::new(db.data(), &new_data)?;
+    report.log_summary();
+    Ok(report)
+}
+
+#[test]
+fn item_changes() {
+    let map = |items: &[(&str, &str)]| {
+        let mut map = BTreeMap::<_, Vec<_>>::new();
+        for (key, value) in items {
+            map.entry(key.to_string())
+                .or_default()
+                .push(value.to_string());
+        }
+        map
+    };
+    let changes = ItemChanges::new(
+        map(&[("a", "1"), ("b", "1"), ("c", "1"), ("c", "2")]),
+        map(&[("a", "1"), ("b", "2"), ("d", "1")]),
+    );
+    assert_eq!(
+        changes,
+        ItemChanges {
+            added: vec!["d".to_string()],
+            removed: vec!["c".to_string(), "c".to_string()],
+            changed: vec!["b".to_string()],
+        }
+    );
+}
+
+#[test]
+fn strip_ids() {
+    let mut value = serde_json::json!({
+        "source": { "crate_name": "a", "id": 1 },
+        "other": [{ "crate_name": "b", "id": 2 }],
+    });
+    strip_item_ids(&mut value, "a");
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "source": null,
+            "other": [{ "crate_name": "b", "id": 2 }],
+        })
+    );
+}
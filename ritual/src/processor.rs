@@ -1,11 +1,14 @@
 use crate::config::Config;
 use crate::cpp_checker::{check_cpp_parser_hook, delete_blacklisted_items};
-use crate::cpp_data::CppItem;
-use crate::database::{DatabaseClient, ItemId};
+use crate::cpp_data::{CppItem, CppPath};
+use crate::database::{DatabaseClient, ItemId, StepRecord};
+use crate::database_file::{self, DatabaseFormat};
+use crate::external_step::ExternalStep;
+use crate::fingerprint::{self, StepFingerprints};
 use crate::workspace::Workspace;
 use crate::{
     cpp_casts, cpp_checker, cpp_ffi_generator, cpp_implicit_methods, cpp_omitting_arguments,
-    cpp_parser, cpp_template_instantiator, crate_writer, rust_generator,
+    cpp_parser, cpp_template_instantiator, crate_writer, html_report, rust_generator,
 };
 use itertools::Itertools;
 use log::debug;
@@ -13,6 +16,7 @@ use log::{error, info, trace};
 use regex::Regex;
 use ritual_common::env_var_names::WORKSPACE_TARGET_DIR;
 use ritual_common::errors::{bail, err_msg, format_err, Result, ResultExt};
+use ritual_common::file_utils::create_dir_all;
 use ritual_common::target::LibraryTarget;
 use ritual_common::utils::{run_command, MapIfOk};
 use std::cmp::Ordering;
@@ -20,6 +24,7 @@ use std::collections::{HashMap, HashSet};
 use std::ops::Bound;
 use std::path::PathBuf;
 use std::process::Command;
+use std::sync::PoisonError;
 use std::time::{Duration, Instant};
 use std::{env, fmt};
 
@@ -68,13 +73,16 @@ pub struct ProcessorData<'a> {
 
 struct ProcessingStep {
     name: String,
-    function: Box<dyn Fn(&mut ProcessorData<'_>) -> Result<()>>,
+    /// Version of the step's code (see `ProcessingSteps::set_version`)
+    version: String,
+    function: Box<dyn Fn(&mut ProcessorData<'_>) -> Result<()> + Send>,
 }
 
 impl fmt::Debug for ProcessingStep {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ProcessingStep")
             .field("name", &self.name)
+            .field("version", &self.version)
             .finish()
     }
 }
@@ -127,23 +135,34 @@ impl Default for ProcessingSteps {
         s.push("crate_writer", crate_writer::run);
         s.push("build_crate", build_crate);
 
-        s.add_custom("clear_ffi", |data| {
+        s.push_custom("clear_ffi", |data| {
             data.db.delete_items(|i| i.item.is_ffi_item());
             Ok(())
         });
-        s.add_custom("clear_cpp_checks", |data| {
+        s.push_custom("clear_cpp_checks", |data| {
             data.db.delete_items(|i| i.item.is_cpp_checks_item());
             Ok(())
         });
-        s.add_custom("clear_rust_info", |data| {
+        s.push_custom("clear_rust_info", |data| {
             data.db.delete_items(|i| i.item.is_rust_item());
             Ok(())
         });
-        s.add_custom("show_non_portable", show_non_portable);
-        s.add_custom("migrate", migrate);
-        s.add_custom("delete_orphans", delete_orphans);
-        s.add_custom("delete_blacklisted_items", delete_blacklisted_items);
-        s.add_custom("force_cpp_checker", |data| cpp_checker::run(data, true));
+        s.push_custom("show_non_portable", show_non_portable);
+        s.push_custom("migrate", migrate);
+        s.push_custom("delete_orphans", delete_orphans);
+        s.push_custom("delete_blacklisted_items", delete_blacklisted_items);
+        s.push_custom("force_cpp_checker", |data| cpp_checker::run(data, true));
+        s.push_custom(html_report::REPORT_OPERATION, html_report::run);
+        s.push_custom("export_json", export_json);
+        #[cfg(feature = "sqlite")]
+        s.push_custom(
+            crate::sqlite_export::EXPORT_SQLITE_OPERATION,
+            crate::sqlite_export::run,
+        );
+        #[cfg(not(feature = "sqlite"))]
+        s.push_custom("export_sqlite", |_| {
+            bail!("export_sqlite operation requires `sqlite` feature of ritual")
+        });
         s
     }
 }
@@ -153,8 +172,9 @@ impl ProcessingSteps {
         &mut self,
         after: &[&str],
         name: &str,
-        func: impl Fn(&mut ProcessorData<'_>) -> Result<()> + 'static,
+        func: impl Fn(&mut ProcessorData<'_>) -> Result<()> + Send + 'static,
     ) -> Result<()> {
+        self.check_new_name(name)?;
         let indexes = after.iter().map_if_ok(|s| {
             self.main_procedure
                 .iter()
@@ -174,28 +194,104 @@ impl ProcessingSteps {
     pub fn push(
         &mut self,
         name: &str,
-        func: impl Fn(&mut ProcessorData<'_>) -> Result<()> + 'static,
+        func: impl Fn(&mut ProcessorData<'_>) -> Result<()> + Send + 'static,
     ) {
         self.main_procedure.push(name.to_string());
         self.all_steps.push(ProcessingStep::new(name, func));
     }
 
+    /// Adds a step that is not part of the main procedure and is only run
+    /// when requested explicitly. Returns an error if a step with the same `name` already exists.
     pub fn add_custom(
         &mut self,
         name: &str,
-        func: impl Fn(&mut ProcessorData<'_>) -> Result<()> + 'static,
+        func: impl Fn(&mut ProcessorData<'_>) -> Result<()> + Send + 'static,
+    ) -> Result<()> {
+        self.check_new_name(name)?;
+        self.push_custom(name, func);
+        Ok(())
+    }
+
+    fn push_custom(
+        &mut self,
+        name: &str,
+        func: impl Fn(&mut ProcessorData<'_>) -> Result<()> + Send + 'static,
     ) {
         self.all_steps.push(ProcessingStep::new(name, func));
     }
+
+    fn check_new_name(&self, name: &str) -> Result<()> {
+        if self.all_steps.iter().any(|step| step.name == name) {
+            bail!("step already exists: {}", name);
+        }
+        Ok(())
+    }
+
+    /// Adds an external step to the main procedure after the specified steps.
+    /// See `external_step` module for more information.
+    pub fn add_external_after(&mut self, after: &[&str], step: ExternalStep) -> Result<()> {
+        let name = step.name().to_string();
+        let version = format!("{:?}", step);
+        self.add_after(after, &name, move |data| step.run(data))?;
+        self.set_version(&name, version)
+    }
+
+    /// Adds an external step that is only run when requested explicitly.
+    /// See `external_step` module for more information.
+    pub fn add_external_custom(&mut self, step: ExternalStep) -> Result<()> {
+        let name = step.name().to_string();
+        self.add_custom(&name, move |data| step.run(data))
+    }
+
+    /// Sets version of the step's code. When the main procedure is requested,
+    /// steps are skipped if their inputs didn't change since the last run,
+    /// so the version should be changed whenever behavior of a custom step changes.
+    pub fn set_version(&mut self, name: &str, version: impl Into<String>) -> Result<()> {
+        let step = self
+            .all_steps
+            .iter_mut()
+            .find(|step| step.name == name)
+            .ok_or_else(|| format_err!("requested step not found: {}", name))?;
+        step.version = version.into();
+        Ok(())
+    }
+
+    /// Returns names of the main procedure steps from `first` to `last` (inclusive).
+    pub fn main_procedure_range(&self, first: &str, last: &str) -> Result<&[String]> {
+        let index = |name: &str| {
+            self.main_procedure
+                .iter()
+                .position(|s| s == name)
+                .ok_or_else(|| format_err!("requested step not found: {}", name))
+        };
+        let range = self
+            .main_procedure
+            .get(index(first)?..=index(last)?)
+            .ok_or_else(|| err_msg("invalid steps range"))?;
+        Ok(range)
+    }
+
+    /// Runs the step `name`.
+    pub fn run(&self, name: &str, data: &mut ProcessorData<'_>) -> Result<()> {
+        (self.step(name)?.function)(data)
+    }
+
+    fn step(&self, name: &str) -> Result<&ProcessingStep> {
+        self.all_steps
+            .iter()
+            .find(|step| step.name == name)
+            .ok_or_else(|| format_err!("requested step not found: {}", name))
+    }
 }
 
 impl ProcessingStep {
-    pub fn new<S: Into<String>, F: 'static + Fn(&mut ProcessorData<'_>) -> Result<()>>(
+    pub fn new<S: Into<String>, F: 'static + Send + Fn(&mut ProcessorData<'_>) -> Result<()>>(
         name: S,
         function: F,
     ) -> Self {
         ProcessingStep {
             name: name.into(),
+            version: String::new(),
             function: Box::new(function),
         }
     }
@@ -304,6 +400,16 @@ fn migrate(data: &mut ProcessorData<'_>) -> Result<()> {
     Ok(())
 }
 
+/// Writes the database of the current crate in JSON format for debugging.
+fn export_json(data: &mut ProcessorData<'_>) -> Result<()> {
+    let dir = data.workspace.path().join("export");
+    create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.json", data.db.crate_name()));
+    database_file::save(&path, data.db.data(), DatabaseFormat::Json, None)?;
+    info!("Database exported to {}", path.display());
+    Ok(())
+}
+
 fn delete_orphans(data: &mut ProcessorData<'_>) -> Result<()> {
     let mut ids = HashSet::new();
     for item in data.db.items() {
@@ -403,8 +509,10 @@ pub fn process(
             .iter()
             .any(|step| &step.name == step_name)
         {
-            return Ok(vec![step_name.clone()]);
+            return Ok((false, vec![step_name.clone()]));
         }
+        // only outdated steps are run if the whole main procedure is requested
+        let is_incremental = step_name == "main";
 
         let range = parse_steps_spec(step_name)?;
         let start_index = match range.0 {
@@ -426,14 +534,24 @@ pub fn process(
         if range.is_empty() {
             bail!("empty steps range");
         }
-        Ok(range)
+        Ok((is_incremental, range))
     })?;
 
-    for step_range in step_ranges {
+    let mut fingerprints = StepFingerprints::new(config);
+
+    for (is_incremental, mut step_range) in step_ranges {
         if steps_result.is_err() {
             break;
         }
 
+        if is_incremental {
+            step_range = outdated_steps(config, &mut db_client, &mut fingerprints, &step_range)?;
+            if step_range.is_empty() {
+                info!("All steps are up to date");
+                continue;
+            }
+        }
+
         for step_name in step_range {
             let step = config
                 .processing_steps()
@@ -442,10 +560,31 @@ pub fn process(
                 .find(|item| item.name == step_name)
                 .expect("step name must be valid (checked above)");
 
+            let is_main_step = config
+                .processing_steps()
+                .main_procedure
+                .contains(&step.name);
+            let fingerprint = if is_main_step {
+                db_client.remove_step_record(&step.name);
+                Some(fingerprints.get(&step.name, &step.version, &db_client)?)
+            } else {
+                None
+            };
+            let first_id = db_client.next_id();
+            db_client.take_step_input_files();
+
             if step.name == "crate_writer" {
                 workspace.save_database(&mut db_client)?;
             }
 
+            // crates processed concurrently share the output directory
+            let output_lock = workspace.output_lock();
+            let _output_guard = if fingerprint::UNTRACKED_STEPS.contains(&step.name.as_str()) {
+                Some(output_lock.lock().unwrap_or_else(PoisonError::into_inner))
+            } else {
+                None
+            };
+
             info!("Running processing step: {}", &step.name);
 
             let mut data = ProcessorData {
@@ -465,6 +604,24 @@ pub fn process(
             let elapsed = started_time.elapsed();
             trace!("Step '{}' completed in {:?}", step.name, elapsed);
 
+            if let Some(fingerprint) = fingerprint {
+                let end_id = db_client.next_id();
+                let item_count = db_client.item_count_in_range(first_id, end_id);
+                let input_files = db_client.take_step_input_files();
+                let input_files_fingerprint = fingerprint::files_fingerprint(&input_files)?;
+                db_client.set_step_record(
+                    &step.name,
+                    StepRecord {
+                        fingerprint,
+                        first_id,
+                        end_id,
+                        item_count,
+                        input_files,
+                        input_files_fingerprint,
+                    },
+                );
+            }
+
             db_client.report_counters();
 
             if elapsed > Duration::from_secs(15) {
@@ -478,6 +635,60 @@ pub fn process(
     steps_result
 }
 
+/// Finds the first step in `steps` that is outdated, deletes all items created by it
+/// and the following steps, and returns the steps that need to be run.
+/// A step is outdated if its inputs changed, if it was run before the previous step,
+/// if any of the items it created were deleted, or if it has no record at all.
+/// Steps that write files outside of the database are always outdated.
+///
+/// If a step has no record, it's unknown which items it created, so only items
+/// of recorded following steps are deleted.
+pub(crate) fn outdated_steps(
+    config: &Config,
+    db: &mut DatabaseClient,
+    fingerprints: &mut StepFingerprints<'_>,
+    steps: &[String],
+) -> Result<Vec<String>> {
+    let mut valid_end_id = None;
+    for (index, name) in steps.iter().enumerate() {
+        let step = config.processing_steps().step(name)?;
+        let fingerprint = fingerprints.get(name, &step.version, db)?;
+        let is_valid = match db.step_record(name) {
+            Some(record) if fingerprint::is_tracked(name) => {
+                record.fingerprint == fingerprint
+                    && valid_end_id.map_or(true, |end_id| record.first_id >= end_id)
+                    && db.item_count_in_range(record.first_id, record.end_id) == record.item_count
+                    && fingerprint::files_fingerprint(&record.input_files)?
+                        == record.input_files_fingerprint
+            }
+            _ => false,
+        };
+        if is_valid {
+            valid_end_id = db.step_record(name).map(|record| record.end_id);
+            continue;
+        }
+
+        info!("Step is outdated: {}", name);
+        let has_record = db.step_record(name).is_some();
+        let outdated_ranges = steps[index..]
+            .iter()
+            .filter_map(|name| db.step_record(name))
+            .map(|record| record.first_id..record.end_id)
+            .collect_vec();
+        db.delete_items(|item| {
+            (has_record && valid_end_id.map_or(true, |end_id| item.id.id() >= end_id))
+                || outdated_ranges
+                    .iter()
+                    .any(|range| range.contains(&item.id.id()))
+        });
+        for name in &steps[index..] {
+            db.remove_step_record(name);
+        }
+        return Ok(steps[index..].to_vec());
+    }
+    Ok(Vec::new())
+}
+
 fn parse_steps_spec(text: &str) -> Result<(Bound<String>, Bound<String>)> {
     if text == "main" {
         return Ok((Bound::Unbounded, Bound::Unbounded));
@@ -597,19 +808,127 @@ impl ProcessorData<'_> {
         source_id: Option<ItemId>,
         data: CppItem,
     ) -> Result<Option<ItemId>> {
-        if let Some(hook) = self.config.cpp_item_filter_hook() {
-            if !hook(&data)? {
-                debug!("blacklisted cpp item: {}", data.short_text());
-                return Ok(None);
-            }
+        if !self.config.check_cpp_item(&data)? {
+            debug!("blacklisted cpp item: {}", data.short_text());
+            return Ok(None);
         }
-        if let Some(hook) = self.config.cpp_parser_path_hook() {
-            if !check_cpp_parser_hook(&data, &hook)? {
-                debug!("blacklisted cpp item: {}", data.short_text());
-                return Ok(None);
-            }
+        if !self.config.cpp_parser_path_hooks().is_empty()
+            && !check_cpp_parser_hook(&data, &|path: &CppPath| {
+                self.config.check_cpp_parser_path(path)
+            })?
+        {
+            debug!("blacklisted cpp item: {}", data.short_text());
+            return Ok(None);
         }
 
         self.db.add_cpp_item_without_hook(source_id, data)
     }
 }
+
+#[test]
+fn test_outdated_steps() {
+    use crate::config::CrateProperties;
+    use crate::cpp_data::CppNamespace;
+    use crate::database::{Database, IndexedDatabase};
+    use ritual_common::ReadOnly;
+    use std::iter::once;
+
+    fn run_step(
+        db: &mut DatabaseClient,
+        fingerprints: &mut StepFingerprints<'_>,
+        name: &str,
+    ) -> ItemId {
+        let first_id = db.next_id();
+        let id = db
+            .add_cpp_item_without_hook(
+                None,
+                CppItem::Namespace(CppNamespace {
+                    path: CppPath::from_good_str(&format!("ns{}", first_id)),
+                }),
+            )
+            .unwrap()
+            .unwrap();
+        let end_id = db.next_id();
+        let record = StepRecord {
+            fingerprint: fingerprints.get(name, "", db).unwrap(),
+            first_id,
+            end_id,
+            item_count: db.item_count_in_range(first_id, end_id),
+            input_files: Vec::new(),
+            input_files_fingerprint: fingerprint::files_fingerprint(&[]).unwrap(),
+        };
+        db.set_step_record(name, record);
+        id
+    }
+
+    let config = Config::new(CrateProperties::new("outdated_steps_test", "0.1.0"));
+    let mut db = DatabaseClient::new(
+        IndexedDatabase::new(
+            Database::empty("outdated_steps_test".into()),
+            PathBuf::from("outdated_steps_test.json"),
+        ),
+        ReadOnly::new(Vec::new()),
+    );
+    let mut fingerprints = StepFingerprints::new(&config);
+    let steps = vec![
+        "cpp_parser".to_string(),
+        "cpp_casts".to_string(),
+        "rust_generator".to_string(),
+    ];
+    assert_eq!(
+        outdated_steps(&config, &mut db, &mut fingerprints, &steps).unwrap(),
+        steps
+    );
+
+    let mut ids = Vec::new();
+    for name in &steps {
+        ids.push(run_step(&mut db, &mut fingerprints, name));
+    }
+    assert!(outdated_steps(&config, &mut db, &mut fingerprints, &steps)
+        .unwrap()
+        .is_empty());
+
+    // deleting output of a step invalidates it and all following steps
+    db.delete_items(|item| item.id == ids[2]);
+    assert_eq!(
+        outdated_steps(&config, &mut db, &mut fingerprints, &steps).unwrap(),
+        &steps[2..]
+    );
+    run_step(&mut db, &mut fingerprints, &steps[2]);
+
+    // running a step again invalidates the following steps
+    run_step(&mut db, &mut fingerprints, &steps[1]);
+    assert_eq!(
+        outdated_steps(&config, &mut db, &mut fingerprints, &steps).unwrap(),
+        &steps[2..]
+    );
+    // items created by the previous run of the outdated step are deleted
+    assert!(db.item(&ids[0]).is_ok());
+    assert_eq!(db.cpp_items().count(), 3);
+    run_step(&mut db, &mut fingerprints, &steps[2]);
+
+    // a step without a record is rerun, but items of unknown origin are kept
+    db.remove_step_record(&steps[0]);
+    assert_eq!(
+        outdated_steps(&config, &mut db, &mut fingerprints, &steps).unwrap(),
+        steps
+    );
+    assert!(db.item(&ids[0]).is_ok());
+    assert_eq!(db.cpp_items().count(), 2);
+    for name in &steps {
+        run_step(&mut db, &mut fingerprints, name);
+    }
+
+    // steps that write files are always run
+    let steps_with_writer = steps
+        .iter()
+        .cloned()
+        .chain(once("crate_writer".to_string()))
+        .collect_vec();
+    run_step(&mut db, &mut fingerprints, "crate_writer");
+    assert_eq!(
+        outdated_steps(&config, &mut db, &mut fingerprints, &steps_with_writer).unwrap(),
+        &steps_with_writer[3..]
+    );
+    assert_eq!(db.cpp_items().count(), 5);
+}
@@ -0,0 +1,546 @@
+//! Declarative project configuration loaded from a `ritual.toml` file.
+//!
+//! A project file describes one or more crates:
+//!
+//! ```toml
+//! [[crate]]
+//! name = "mylib"
+//! cpp_lib_version = "1.2"
+//! crate_template = "crate_templates/mylib"
+//! include_directives = ["mylib.h"]
+//! target_include_paths = ["/opt/mylib/include"]
+//! include_paths = ["/opt/mylib/include"]
+//! lib_paths = ["/opt/mylib/lib"]
+//! parser_arguments = ["-DMYLIB_NO_DEPRECATED"]
+//! blacklist = ["mylib::detail", "mylib::Buffer::data_unchecked"]
+//! dependencies = ["mylib_core"]
+//!
+//! [crate.rename]
+//! "mylib::basic_buffer" = "BasicBuffer"
+//!
+//! [crate.package]
+//! description = "Bindings for mylib"
+//!
+//! [[crate.build]]
+//! linked_libs = ["mylib"]
+//! library_type = "Shared"
+//!
+//! [[crate.build]]
+//! condition = { Not = { Env = "Msvc" } }
+//! compiler_flags = ["-fPIC", "-std=c++11"]
+//!
+//! [[crate.external_step]]
+//! name = "fix_names"
+//! program = "scripts/fix_names.py"
+//! after = ["cpp_parser"]
+//! items = ["cpp_item"]
+//! ```
+//!
+//! Relative paths are resolved against the directory containing the project file.
+//! Build conditions use the same representation as `ritual_common::target::Condition`
+//! in JSON (e.g. `"True"`, `{ OS = "Linux" }`, `{ And = [...] }`).
+//! External steps without `after` are only run when requested in `--operations`
+//! (see `ritual::external_step` for the protocol).
+
+use crate::config::{
+    Config, CrateDependencyKind, CrateDependencySource, CrateProperties, GlobalConfig,
+};
+use crate::cpp_data::CppPath;
+use crate::external_step::ExternalStep;
+use crate::rust_info::NameType;
+use crate::rust_type::RustPath;
+use ritual_common::cpp_build_config::{CppBuildConfigData, CppBuildPaths, CppLibraryType};
+use ritual_common::cpp_lib_builder::CMakeVar;
+use ritual_common::errors::{bail, format_err, Result, ResultExt};
+use ritual_common::file_utils::{canonicalize, file_to_string};
+use ritual_common::string_utils::CaseOperations;
+use ritual_common::target::Condition;
+use ritual_common::toml;
+use serde_derive::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Default name of the project file.
+pub const PROJECT_FILE_NAME: &str = "ritual.toml";
+
+/// Name of the C++ parser path hook created for the blacklist.
+pub const BLACKLIST_HOOK_NAME: &str = "project_file_blacklist";
+
+/// Name of the Rust path hook created for the rename map.
+pub const RENAME_HOOK_NAME: &str = "project_file_rename";
+
+/// Contents of a project file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectFile {
+    #[serde(rename = "crate")]
+    crates: Vec<ProjectCrate>,
+    /// Directory of the project file
+    #[serde(skip)]
+    base_dir: PathBuf,
+}
+
+/// Configuration of a crate in the project file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectCrate {
+    /// Name of the generated crate
+    name: String,
+    /// Version of the C++ library (see `Config::set_cpp_lib_version`)
+    cpp_lib_version: Option<String>,
+    /// See `Config::set_crate_template_path`
+    crate_template: Option<PathBuf>,
+    /// See `Config::add_include_directive`
+    #[serde(default)]
+    include_directives: Vec<PathBuf>,
+    /// See `Config::add_target_include_path`
+    #[serde(default)]
+    target_include_paths: Vec<PathBuf>,
+    /// Include paths used while processing the library
+    #[serde(default)]
+    include_paths: Vec<PathBuf>,
+    /// Library paths used while processing the library
+    #[serde(default)]
+    lib_paths: Vec<PathBuf>,
+    /// Framework paths used while processing the library
+    #[serde(default)]
+    framework_paths: Vec<PathBuf>,
+    /// See `Config::add_cpp_parser_argument`
+    #[serde(default)]
+    parser_arguments: Vec<String>,
+    /// C++ paths skipped by the parser, including all items nested in them
+    #[serde(default)]
+    blacklist: Vec<String>,
+    /// Rust paths (relative to the crate root) of C++ items
+    #[serde(default)]
+    rename: BTreeMap<String, String>,
+    /// Other crates of the project used by this crate
+    #[serde(default)]
+    dependencies: Vec<String>,
+    /// Items of `CppBuildConfig`
+    #[serde(default)]
+    build: Vec<ProjectBuildConfig>,
+    /// Processing steps implemented by external programs
+    #[serde(default, rename = "external_step")]
+    external_steps: Vec<ProjectExternalStep>,
+    /// Extra fields of the `[package]` section of the generated `Cargo.toml`
+    #[serde(default)]
+    package: toml::value::Table,
+    #[serde(default)]
+    parse_macro_constants: bool,
+    #[serde(default)]
+    parse_doc_comments: bool,
+    #[serde(default)]
+    plain_c_mode: bool,
+    #[serde(default)]
+    catch_exceptions: bool,
+}
+
+/// An item of `CppBuildConfig` in the project file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProjectBuildConfig {
+    /// Condition of the item. The item is always used if the condition is not specified.
+    condition: Option<toml::Value>,
+    #[serde(default)]
+    linked_libs: Vec<String>,
+    #[serde(default)]
+    linked_frameworks: Vec<String>,
+    #[serde(default)]
+    compiler_flags: Vec<String>,
+    /// `"Shared"` or `"Static"`
+    library_type: Option<String>,
+    #[serde(default)]
+    cmake_vars: BTreeMap<String, String>,
+}
+
+/// An external processing step in the project file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProjectExternalStep {
+    name: String,
+    program: PathBuf,
+    #[serde(default)]
+    args: Vec<String>,
+    /// Steps of the main procedure after which the step is run
+    after: Option<Vec<String>>,
+    /// Kinds of items passed to the step (e.g. `"cpp_item"`)
+    #[serde(default)]
+    items: Vec<String>,
+}
+
+impl ProjectExternalStep {
+    fn step(&self, base_dir: &Path) -> Result<ExternalStep> {
+        // bare program names are looked up in `PATH`
+        let program = if self.program.components().count() > 1 {
+            base_dir.join(&self.program)
+        } else {
+            self.program.clone()
+        };
+        let mut step = ExternalStep::new(self.name.as_str(), program);
+        step.add_args(&self.args);
+        for item in &self.items {
+            step.add_item_kind(item.parse()?);
+        }
+        Ok(step)
+    }
+}
+
+impl ProjectBuildConfig {
+    fn condition(&self) -> Result<Condition> {
+        if let Some(condition) = &self.condition {
+            // `toml` can't deserialize enums with data, so the value is converted to JSON
+            let value = serde_json::to_value(condition)?;
+            Ok(serde_json::from_value(value)
+                .with_context(|_| format!("invalid build condition: {}", condition))?)
+        } else {
+            Ok(Condition::True)
+        }
+    }
+
+    fn data(&self) -> Result<CppBuildConfigData> {
+        let mut data = CppBuildConfigData::new();
+        for lib in &self.linked_libs {
+            data.add_linked_lib(lib.as_str());
+        }
+        for framework in &self.linked_frameworks {
+            data.add_linked_framework(framework.as_str());
+        }
+        data.add_compiler_flags(self.compiler_flags.iter().map(String::as_str));
+        match self.library_type.as_ref().map(String::as_str) {
+            Some("Shared") => data.set_library_type(CppLibraryType::Shared),
+            Some("Static") => data.set_library_type(CppLibraryType::Static),
+            Some(other) => bail!("invalid library type: {}", other),
+            None => {}
+        }
+        for (name, value) in &self.cmake_vars {
+            data.add_cmake_var(CMakeVar::new(name.as_str(), value.as_str()));
+        }
+        Ok(data)
+    }
+}
+
+impl ProjectFile {
+    /// Loads a project file from `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = canonicalize(path)?;
+        let base_dir = path
+            .parent()
+            .ok_or_else(|| format_err!("no parent directory for {}", path.display()))?;
+        Ok(Self::parse(&file_to_string(&path)?, base_dir)
+            .with_context(|_| format!("failed to load project file: {}", path.display()))?)
+    }
+
+    /// Parses contents of a project file. Relative paths are resolved against `base_dir`.
+    pub fn parse(text: &str, base_dir: impl Into<PathBuf>) -> Result<Self> {
+        let mut project: ProjectFile = toml::from_str(text)?;
+        project.base_dir = base_dir.into();
+        for (index, crate_data) in project.crates.iter().enumerate() {
+            if project.crates[..index]
+                .iter()
+                .any(|other| other.name == crate_data.name)
+            {
+                bail!("duplicate crate in project file: {}", crate_data.name);
+            }
+            for dependency in &crate_data.dependencies {
+                if !project.crates.iter().any(|c| &c.name == dependency) {
+                    bail!("unknown dependency of {}: {}", crate_data.name, dependency);
+                }
+            }
+            for item in &crate_data.build {
+                item.condition()?;
+                item.data()?;
+            }
+            for item in &crate_data.external_steps {
+                item.step(&project.base_dir)?;
+            }
+        }
+        Ok(project)
+    }
+
+    /// Returns all crates of the project.
+    pub fn crates(&self) -> &[ProjectCrate] {
+        &self.crates
+    }
+
+    /// Returns configuration of the crate with `name`.
+    pub fn crate_data(&self, name: &str) -> Result<&ProjectCrate> {
+        self.crates
+            .iter()
+            .find(|c| c.name == name)
+            .ok_or_else(|| format_err!("crate not found in project file: {}", name))
+    }
+
+    fn resolve_path(&self, path: &Path) -> PathBuf {
+        self.base_dir.join(path)
+    }
+
+    /// Creates a `Config` for a crate of this project.
+    ///
+    /// If the crate has a blacklist or a rename map, a C++ parser path hook named
+    /// `BLACKLIST_HOOK_NAME` or a Rust path hook named `RENAME_HOOK_NAME` is added.
+    /// Custom hooks can be added alongside them, and `ProjectCrate::is_blacklisted`
+    /// and `ProjectCrate::renamed_path` can be used to combine the project file
+    /// with extra logic.
+    pub fn create_config(&self, mut crate_properties: CrateProperties) -> Result<Config> {
+        let crate_data = self.crate_data(crate_properties.name())?.clone();
+
+        if !crate_data.package.is_empty() {
+            let mut custom_fields = crate_properties.custom_fields().clone();
+            custom_fields.insert(
+                "package".to_string(),
+                toml::Value::Table(crate_data.package.clone()),
+            );
+            crate_properties.set_custom_fields(custom_fields);
+        }
+        for dependency in &crate_data.dependencies {
+            crate_properties.add_dependency(
+                dependency.as_str(),
+                CrateDependencyKind::Ritual,
+                CrateDependencySource::CurrentWorkspace,
+            )?;
+        }
+
+        let mut config = Config::new(crate_properties);
+        if let Some(version) = &crate_data.cpp_lib_version {
+            config.set_cpp_lib_version(version.as_str());
+        }
+        if let Some(path) = &crate_data.crate_template {
+            config.set_crate_template_path(self.resolve_path(path));
+        }
+        for directive in &crate_data.include_directives {
+            config.add_include_directive(directive);
+        }
+        for path in &crate_data.target_include_paths {
+            config.add_target_include_path(self.resolve_path(path));
+        }
+        let mut paths = CppBuildPaths::new();
+        for path in &crate_data.include_paths {
+            paths.add_include_path(self.resolve_path(path));
+        }
+        for path in &crate_data.lib_paths {
+            paths.add_lib_path(self.resolve_path(path));
+        }
+        for path in &crate_data.framework_paths {
+            paths.add_framework_path(self.resolve_path(path));
+        }
+        config.set_cpp_build_paths(paths);
+        config.add_cpp_parser_arguments(&crate_data.parser_arguments);
+        for item in &crate_data.build {
+            config
+                .cpp_build_config_mut()
+                .add(item.condition()?, item.data()?);
+        }
+        config.set_parse_macro_constants(crate_data.parse_macro_constants);
+        config.set_parse_doc_comments(crate_data.parse_doc_comments);
+        config.set_plain_c_mode(crate_data.plain_c_mode);
+        config.set_catch_exceptions(crate_data.catch_exceptions);
+        for item in &crate_data.external_steps {
+            let step = item.step(&self.base_dir)?;
+            if let Some(after) = &item.after {
+                let after = after.iter().map(String::as_str).collect::<Vec<_>>();
+                config
+                    .processing_steps_mut()
+                    .add_external_after(&after, step)?;
+            } else {
+                config.processing_steps_mut().add_external_custom(step)?;
+            }
+        }
+
+        if !crate_data.blacklist.is_empty() {
+            let crate_data = crate_data.clone();
+            config.add_cpp_parser_path_hook(BLACKLIST_HOOK_NAME, move |path| {
+                Ok(!crate_data.is_blacklisted(path))
+            })?;
+        }
+        if !crate_data.rename.is_empty() {
+            config.add_rust_path_hook(RENAME_HOOK_NAME, move |path, name_type, _data| {
+                Ok(crate_data.renamed_path(path, &name_type))
+            })?;
+        }
+        Ok(config)
+    }
+
+    /// Creates a `GlobalConfig` that processes crates of this project.
+    pub fn into_global_config(self) -> GlobalConfig {
+        self.into_global_config_with(|_| Ok(()))
+    }
+
+    /// Creates a `GlobalConfig` that processes crates of this project.
+    /// `customize` is called for each created `Config` and can be used
+    /// to add hooks and processing steps on top of the project file.
+    pub fn into_global_config_with(
+        self,
+        mut customize: impl FnMut(&mut Config) -> Result<()> + 'static,
+    ) -> GlobalConfig {
+        let mut config = GlobalConfig::new();
+        config.set_all_crate_names(self.crates.iter().map(|c| c.name.clone()).collect());
+        config.set_create_config_hook(move |crate_properties| {
+            let mut config = self.create_config(crate_properties)?;
+            customize(&mut config)?;
+            Ok(config)
+        });
+        config
+    }
+}
+
+impl ProjectCrate {
+    /// Name of the crate
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns true if `path` or one of its parents is in the blacklist.
+    pub fn is_blacklisted(&self, path: &CppPath) -> bool {
+        let text = path.to_templateless_string();
+        self.blacklist.iter().any(|item| {
+            text == *item
+                || (text.starts_with(item.as_str()) && text[item.len()..].starts_with("::"))
+        })
+    }
+
+    /// Returns the Rust path specified in the rename map for `path`.
+    /// Modules of renamed types use the snake case version of the type's name.
+    pub fn renamed_path(&self, path: &CppPath, name_type: &NameType<'_>) -> Option<RustPath> {
+        let rust_path = self.rename.get(&path.to_templateless_string())?;
+        let mut rust_path = RustPath::from_good_str(&format!("{}::{}", self.name, rust_path));
+        match name_type {
+            NameType::Type { .. }
+            | NameType::EnumValue
+            | NameType::Constant
+            | NameType::ApiFunction(_) => Some(rust_path),
+            NameType::Module { .. } => {
+                *rust_path.last_mut() = rust_path.last().to_snake_case();
+                Some(rust_path)
+            }
+            NameType::FfiFunction
+            | NameType::SizedItem
+            | NameType::ReceiverFunction { .. }
+            | NameType::QtSlotWrapper { .. }
+            | NameType::QtSignalWrapper { .. } => None,
+        }
+    }
+}
+
+#[test]
+fn parse_project_file() {
+    let project = ProjectFile::parse(
+        r#"
+        [[crate]]
+        name = "mylib_core"
+        include_directives = ["core.h"]
+
+        [[crate]]
+        name = "mylib"
+        cpp_lib_version = "1.2"
+        include_directives = ["mylib.h"]
+        target_include_paths = ["include"]
+        parser_arguments = ["-DNO_DEPRECATED"]
+        blacklist = ["mylib::detail"]
+        dependencies = ["mylib_core"]
+        plain_c_mode = true
+
+        [crate.rename]
+        "mylib::basic_buffer" = "buffer::BasicBuffer"
+
+        [[crate.build]]
+        linked_libs = ["mylib"]
+        library_type = "Shared"
+
+        [[crate.build]]
+        condition = { Not = { Env = "Msvc" } }
+        compiler_flags = ["-fPIC"]
+
+        [[crate.external_step]]
+        name = "fix_names"
+        program = "scripts/fix_names.py"
+        after = ["cpp_parser"]
+        items = ["cpp_item"]
+        "#,
+        "/project",
+    )
+    .unwrap();
+    assert_eq!(project.crates().len(), 2);
+
+    let crate_data = project.crate_data("mylib").unwrap();
+    assert!(crate_data.is_blacklisted(&CppPath::from_good_str("mylib::detail")));
+    assert!(crate_data.is_blacklisted(&CppPath::from_good_str("mylib::detail::Impl")));
+    assert!(!crate_data.is_blacklisted(&CppPath::from_good_str("mylib::details")));
+    assert_eq!(
+        crate_data.renamed_path(
+            &CppPath::from_good_str("mylib::basic_buffer"),
+            &NameType::Module {
+                is_from_other_crate: false
+            }
+        ),
+        Some(RustPath::from_good_str("mylib::buffer::basic_buffer"))
+    );
+
+    let mut config = project
+        .create_config(CrateProperties::new("mylib", "0.1.0"))
+        .unwrap();
+    assert_eq!(config.cpp_lib_version(), Some("1.2"));
+    assert_eq!(config.include_directives(), &[PathBuf::from("mylib.h")]);
+    assert_eq!(
+        config.target_include_paths(),
+        &[PathBuf::from("/project/include")]
+    );
+    assert_eq!(
+        config.cpp_parser_arguments(),
+        &["-DNO_DEPRECATED".to_string()]
+    );
+    assert!(config.plain_c_mode());
+    assert_eq!(
+        config.crate_properties().dependencies()[0].name(),
+        "mylib_core"
+    );
+    assert!(!config
+        .check_cpp_parser_path(&CppPath::from_good_str("mylib::detail::Impl"))
+        .unwrap());
+    assert!(config
+        .check_cpp_parser_path(&CppPath::from_good_str("mylib::Buffer"))
+        .unwrap());
+    assert_eq!(
+        config.rust_path_hooks().names().collect::<Vec<_>>(),
+        vec![RENAME_HOOK_NAME]
+    );
+
+    // hooks set by the user are combined with the project file hooks
+    config.set_cpp_parser_path_hook(|path| Ok(path.last().name != "Hidden"));
+    config.set_rust_path_hook(|_path, _name_type, _data| Ok(None));
+    assert!(!config
+        .check_cpp_parser_path(&CppPath::from_good_str("mylib::Hidden"))
+        .unwrap());
+    assert!(!config
+        .check_cpp_parser_path(&CppPath::from_good_str("mylib::detail::Impl"))
+        .unwrap());
+    assert!(config
+        .check_cpp_parser_path(&CppPath::from_good_str("mylib::Buffer"))
+        .unwrap());
+    assert_eq!(config.rust_path_hooks().names().count(), 2);
+
+    assert!(project
+        .create_config(CrateProperties::new("other", "0.1.0"))
+        .is_err());
+}
+
+#[test]
+fn invalid_project_file() {
+    assert!(ProjectFile::parse("[[crate]]\nname = \"a\"\nunknown = 1", "/").is_err());
+    assert!(ProjectFile::parse("[[crate]]\nname = \"a\"\ndependencies = [\"b\"]", "/").is_err());
+    assert!(ProjectFile::parse(
+        "[[crate]]\nname = \"a\"\n[[crate.build]]\ncondition = \"Sometimes\"",
+        "/"
+    )
+    .is_err());
+    assert!(ProjectFile::parse(
+        "[[crate]]\nname = \"a\"\n[[crate.build]]\nlibrary_type = \"Dynamic\"",
+        "/"
+    )
+    .is_err());
+    assert!(ProjectFile::parse(
+        "[[crate]]\nname = \"a\"\n[[crate.external_step]]\nname = \"b\"\nprogram = \"c\"\nitems = [\"unknown\"]",
+        "/"
+    )
+    .is_err());
+}
@@ -0,0 +1,220 @@
+//! Implementation of the `query` operation.
+//!
+//! The operation searches items of the current crate and logs each match
+//! with its sources, results of C++ checks, documentation and resulting Rust items.
+
+use crate::database::{DatabaseClient, DatabaseItemData, DbItem, DocItem, ItemId};
+use crate::external_step::ItemKind;
+use crate::processor::ProcessorData;
+use itertools::Itertools;
+use log::info;
+use regex::Regex;
+use ritual_common::errors::{bail, Error, Result};
+use serde_derive::Serialize;
+use std::str::FromStr;
+
+/// Name of the operation.
+pub const QUERY_OPERATION: &str = "query";
+
+/// Status of C++ checks of an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// Checks passed for at least one library target
+    Passed,
+    /// Checks failed for all checked library targets
+    Failed,
+    /// The item has no checks
+    Unchecked,
+}
+
+impl FromStr for CheckStatus {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        Ok(match text {
+            "passed" => CheckStatus::Passed,
+            "failed" => CheckStatus::Failed,
+            "unchecked" => CheckStatus::Unchecked,
+            _ => bail!("invalid check status: {}", text),
+        })
+    }
+}
+
+/// Result of C++ checks for one library target.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub target: String,
+    pub is_success: bool,
+}
+
+/// An item found by the query.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryMatch {
+    pub id: ItemId,
+    pub kind: ItemKind,
+    pub text: String,
+    /// Sources of the item, starting with its direct source
+    pub sources: Vec<String>,
+    pub cpp_checks: Vec<CheckResult>,
+    pub doc: Option<DocItem>,
+    pub rust_paths: Vec<String>,
+}
+
+impl QueryMatch {
+    fn check_status(&self) -> CheckStatus {
+        if self.cpp_checks.iter().any(|check| check.is_success) {
+            CheckStatus::Passed
+        } else if self.cpp_checks.is_empty() {
+            CheckStatus::Unchecked
+        } else {
+            CheckStatus::Failed
+        }
+    }
+
+    fn log(&self, db: &DatabaseClient) -> Result<()> {
+        info!("{}: {} ({:?})", self.id, self.text, self.kind);
+        for check in &self.cpp_checks {
+            let status = if check.is_success { "ok" } else { "failed" };
+            info!("    check: {}: {}", check.target, status);
+        }
+        if let Some(doc) = &self.doc {
+            info!(
+                "    doc: {}",
+                doc.url.as_ref().map_or("(no url)", String::as_str)
+            );
+        }
+        for path in &self.rust_paths {
+            info!("    rust: {}", path);
+        }
+        db.print_item_trace(&self.id)
+    }
+}
+
+/// Conditions of the query. Items must match all specified conditions.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub cpp_path: Option<Regex>,
+    pub rust_path: Option<Regex>,
+    pub kind: Option<ItemKind>,
+    pub check_status: Option<CheckStatus>,
+}
+
+impl Query {
+    fn matches_paths(&self, item: &DatabaseItemData) -> bool {
+        if let Some(regex) = &self.cpp_path {
+            let path = item.as_cpp_item().and_then(|item| item.path());
+            if !path.map_or(false, |path| regex.is_match(&path.to_cpp_pseudo_code())) {
+                return false;
+            }
+        }
+        if let Some(regex) = &self.rust_path {
+            let path = item.as_rust_item().and_then(|item| item.path());
+            if !path.map_or(false, |path| regex.is_match(&path.full_name(None))) {
+                return false;
+            }
+        }
+        if let Some(kind) = self.kind {
+            if ItemKind::of(item) != kind {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Searches items of the current crate and logs the matches.
+    pub fn run(&self, data: &ProcessorData<'_>) -> Result<Vec<QueryMatch>> {
+        let db = &*data.db;
+        info!("Query results for crate {}:", db.crate_name());
+        let mut matches = Vec::new();
+        for item in db.items() {
+            if !self.matches_paths(item.item) {
+                continue;
+            }
+            let query_match = query_match(db, &item)?;
+            if let Some(status) = self.check_status {
+                if query_match.check_status() != status {
+                    continue;
+                }
+            }
+            query_match.log(db)?;
+            matches.push(query_match);
+        }
+        info!("{} items found", matches.len());
+        Ok(matches)
+    }
+}
+
+fn query_match(db: &DatabaseClient, item: &DbItem<&DatabaseItemData>) -> Result<QueryMatch> {
+    let sources = db.source_chain(&item.id)?;
+
+    // checks of the nearest FFI item or, for C++ items, of all derived FFI items
+    let ffi_ids = match sources.iter().find(|item| item.item.is_ffi_item()) {
+        Some(ffi_item) => vec![ffi_item.id.clone()],
+        None => db
+            .descendants(&item.id)
+            .into_iter()
+            .filter(|item| item.item.is_ffi_item())
+            .map(|item| item.id)
+            .collect(),
+    };
+
+    // Rust items generated directly from the C++ item and from its FFI items
+    let mut rust_items = Vec::new();
+    if let Some(rust_item) = item.item.as_rust_item() {
+        rust_items.push(rust_item);
+    }
+    if let Some(path) = item.item.as_cpp_item().and_then(|item| item.path()) {
+        rust_items.extend(
+            db.find_rust_items_for_cpp_path(path, false)?
+                .map(|item| item.item),
+        );
+    }
+    let mut cpp_checks = Vec::new();
+    for ffi_id in &ffi_ids {
+        for child in db.children(ffi_id) {
+            if let Some(check) = child.item.as_cpp_checks_item() {
+                cpp_checks.push(CheckResult {
+                    target: check.env.short_text(),
+                    is_success: check.is_success,
+                });
+            }
+            if !item.item.is_rust_item() {
+                rust_items.extend(child.item.as_rust_item());
+            }
+        }
+    }
+    let rust_paths = rust_items
+        .into_iter()
+        .filter_map(|item| item.path())
+        .map(|path| path.full_name(None))
+        .unique()
+        .collect();
+
+    Ok(QueryMatch {
+        id: item.id.clone(),
+        kind: ItemKind::of(item.item),
+        text: item.item.short_text(),
+        sources: sources
+            .iter()
+            .skip(1)
+            .map(|source| format!("{}: {}", source.id, source.item.short_text()))
+            .collect(),
+        cpp_checks,
+        doc: db.find_doc_for(&item.id)?.map(|doc| doc.item.clone()),
+        rust_paths,
+    })
+}
+
+#[test]
+fn parse_check_status() {
+    assert_eq!(
+        "passed".parse::<CheckStatus>().unwrap(),
+        CheckStatus::Passed
+    );
+    assert_eq!(
+        "unchecked".parse::<CheckStatus>().unwrap(),
+        CheckStatus::Unchecked
+    );
+    assert!("ok".parse::<CheckStatus>().is_err());
+    assert_eq!("rust_item".parse::<ItemKind>().unwrap(), ItemKind::RustItem);
+}
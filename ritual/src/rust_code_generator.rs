@@ -7,10 +7,10 @@ use crate::database::{DatabaseClient, DbItem, ItemId};
 use crate::doc_formatter;
 use crate::rust_generator::qt_core_path;
 use crate::rust_info::{
-    RustEnumValue, RustExtraImpl, RustExtraImplKind, RustFfiWrapperData, RustFunction,
-    RustFunctionArgument, RustFunctionKind, RustItem, RustModule, RustModuleKind,
+    RustConstant, RustEnumValue, RustExtraImpl, RustExtraImplKind, RustFfiWrapperData,
+    RustFunction, RustFunctionArgument, RustFunctionKind, RustItem, RustModule, RustModuleKind,
     RustQtReceiverType, RustSpecialModuleKind, RustStruct, RustStructKind, RustTraitImpl,
-    RustWrapperTypeKind,
+    RustTypeAlias, RustWrapperTypeKind,
 };
 use crate::rust_type::{
     RustClosureToCallbackConversion, RustCommonType, RustFinalType, RustPath,
@@ -326,6 +326,16 @@ impl Generator<'_> {
                 item.map(|i| i.as_extra_impl_ref().unwrap()),
                 &condition_texts,
             ),
+            RustItem::Constant(_) => {
+                self.generate_constant(item.map(|i| i.as_constant_ref().unwrap()))
+            }
+            RustItem::TypeAlias(_) => {
+                self.generate_type_alias(item.map(|i| i.as_type_alias_ref().unwrap()))
+            }
+            RustItem::StructField(_) => {
+                // fields are generated as part of their struct
+                Ok(())
+            }
             RustItem::Reexport(reexport) => {
                 writeln!(
                     self,
@@ -467,12 +477,13 @@ impl Generator<'_> {
         };
         match &rust_struct.item.kind {
             RustStructKind::WrapperType(kind) => match kind {
-                RustWrapperTypeKind::EnumWrapper => {
+                RustWrapperTypeKind::EnumWrapper { underlying_type } => {
                     writeln!(
                         self,
                         include_str!("../templates/crate/enum_wrapper.rs.in"),
                         vis = visibility,
-                        name = rust_struct.item.path.last()
+                        name = rust_struct.item.path.last(),
+                        underlying_type = self.rust_type_to_code(underlying_type)
                     )?;
                 }
                 RustWrapperTypeKind::ImmovableClassWrapper => {
@@ -484,6 +495,14 @@ impl Generator<'_> {
                         rust_struct.item.path.last()
                     )?;
                 }
+                RustWrapperTypeKind::CStruct { is_union } => {
+                    self.generate_c_struct_fields(
+                        &rust_struct.id,
+                        &rust_struct.item.path,
+                        *is_union,
+                        visibility,
+                    )?;
+                }
                 RustWrapperTypeKind::MovableClassWrapper { sized_type_path } => {
                     writeln!(self, "#[repr(transparent)]")?;
                     writeln!(
@@ -504,11 +523,11 @@ impl Generator<'_> {
             }
         }
 
+        // fields are generated as part of the struct declaration
         if self
             .current_database
             .rust_children(&rust_struct.item.path)
-            .next()
-            .is_some()
+            .any(|item| item.item.as_struct_field_ref().is_none())
         {
             let struct_type = RustType::Common(RustCommonType {
                 path: rust_struct.item.path.clone(),
@@ -524,6 +543,74 @@ impl Generator<'_> {
         Ok(())
     }
 
+    fn generate_c_struct_fields(
+        &mut self,
+        id: &ItemId,
+        path: &RustPath,
+        is_union: bool,
+        visibility: &str,
+    ) -> Result<()> {
+        // fields must be declared in the same order as in the C type,
+        // and their source C++ items are stored in that order
+        let fields = self
+            .current_database
+            .rust_children(path)
+            .filter_map(|item| item.filter_map(|item| item.as_struct_field_ref()))
+            .sorted_by_key(|field| field.source_id.clone())
+            .collect_vec();
+
+        // a missing field would result in a different layout
+        let cpp_path = &self
+            .current_database
+            .source_cpp_item(id)?
+            .and_then(|item| item.item.as_type_ref())
+            .ok_or_else(|| err_msg("source C type not found"))?
+            .path;
+        let cpp_field_count = self
+            .current_database
+            .cpp_items()
+            .filter_map(|item| item.item.as_field_ref())
+            .filter(|field| field.path.parent().ok().as_ref() == Some(cpp_path))
+            .count();
+        if fields.len() != cpp_field_count {
+            bail!(
+                "only {} of {} fields of {} were generated",
+                fields.len(),
+                cpp_field_count,
+                cpp_path.to_cpp_pseudo_code()
+            );
+        }
+
+        writeln!(self, "#[repr(C)]")?;
+        writeln!(self, "#[derive(Clone, Copy)]")?;
+        writeln!(
+            self,
+            "{}{} {} {{",
+            visibility,
+            if is_union { "union" } else { "struct" },
+            path.last()
+        )?;
+        for field in fields {
+            write!(
+                self,
+                "{}",
+                format_doc(&doc_formatter::struct_field_doc(
+                    field.clone(),
+                    self.current_database
+                )?)
+            )?;
+            writeln!(
+                self,
+                "pub {}: {},",
+                field.item.path.last(),
+                self.rust_type_to_code(&field.item.field_type)
+            )?;
+        }
+        writeln!(self, "}}")?;
+        writeln!(self)?;
+        Ok(())
+    }
+
     fn generate_enum_value(&mut self, value: DbItem<&RustEnumValue>) -> Result<()> {
         write!(
             self,
@@ -540,13 +627,57 @@ impl Generator<'_> {
                 .parent()
                 .expect("enum value must have parent"),
         );
+        // large values of unsigned 64-bit enums are stored as negative numbers,
+        // so the cast restores the original value
+        let value_code = if value.item.value < 0 {
+            format!("{}_i64 as _", value.item.value)
+        } else {
+            value.item.value.to_string()
+        };
         writeln!(self, "#[allow(non_upper_case_globals)]")?;
         writeln!(
             self,
             "pub const {value_name}: {struct_path} = {struct_path}({value});",
             value_name = value.item.path.last(),
             struct_path = struct_path,
-            value = value.item.value
+            value = value_code
+        )?;
+        Ok(())
+    }
+
+    fn generate_constant(&mut self, constant: DbItem<&RustConstant>) -> Result<()> {
+        write!(
+            self,
+            "{}",
+            format_doc(&doc_formatter::constant_doc(
+                constant.clone(),
+                self.current_database
+            )?)
+        )?;
+        writeln!(
+            self,
+            "pub const {}: {} = {};",
+            constant.item.path.last(),
+            self.rust_type_to_code(&constant.item.value_type),
+            constant.item.value
+        )?;
+        Ok(())
+    }
+
+    fn generate_type_alias(&mut self, alias: DbItem<&RustTypeAlias>) -> Result<()> {
+        write!(
+            self,
+            "{}",
+            format_doc(&doc_formatter::type_alias_doc(
+                alias.clone(),
+                self.current_database
+            )?)
+        )?;
+        writeln!(
+            self,
+            "pub type {} = {};",
+            alias.item.path.last(),
+            self.rust_type_to_code(&alias.item.target)
         )?;
         Ok(())
     }
@@ -693,6 +824,9 @@ impl Generator<'_> {
             RustToFfiTypeConversion::ClosureToCallback { .. } => {
                 bail!("ClosureToCallback is not convertable from FFI type");
             }
+            RustToFfiTypeConversion::ResultFromException(_) => {
+                bail!("ResultFromException must be handled by the FFI call generator");
+            }
         };
         Ok(code1 + &code2)
     }
@@ -783,6 +917,9 @@ impl Generator<'_> {
             RustToFfiTypeConversion::ClosureToCallback { .. } => {
                 "Some(ffi_callback::<T>), Some(deleter::<T>), data".to_string()
             }
+            RustToFfiTypeConversion::ResultFromException(_) => {
+                bail!("ResultFromException is not possible to use in argument position");
+            }
         };
         Ok(code)
     }
@@ -881,6 +1018,19 @@ impl Generator<'_> {
 
         let mut maybe_result_var_name = None;
 
+        // the value is checked for a caught exception before
+        // converting it to the API type
+        let (return_type, catches_exception) =
+            if let RustToFfiTypeConversion::ResultFromException(conversion) =
+                return_type.conversion()
+            {
+                let value_type =
+                    RustFinalType::new(return_type.ffi_type().clone(), (**conversion).clone())?;
+                (value_type, true)
+            } else {
+                (return_type.clone(), false)
+            };
+
         let ffi_item = self
             .current_database
             .source_ffi_item(id)?
@@ -933,6 +1083,22 @@ impl Generator<'_> {
             final_args[i] = Some(format!("&mut {}", return_var_name));
             maybe_result_var_name = Some(return_var_name);
         }
+        let exception_ffi_index = ffi_item
+            .arguments
+            .iter()
+            .position(|arg| arg.meaning == CppFfiArgumentMeaning::Exception);
+
+        let mut exception_var_name = "exception".to_string();
+        if let Some(i) = exception_ffi_index {
+            let mut ii = 1;
+            while arguments.iter().any(|x| x.name == exception_var_name) {
+                ii += 1;
+                exception_var_name = format!("exception{}", ii);
+            }
+            final_args.resize(i + 1, None);
+            final_args[i] = Some(format!("&mut {}", exception_var_name));
+        }
+
         let final_args = final_args
             .into_iter()
             .map_if_ok(|x| x.ok_or_else(|| err_msg("ffi argument is missing")))?;
@@ -954,7 +1120,36 @@ impl Generator<'_> {
             result.push(format!("{}\n}}", name));
         }
         let code = result.join("");
-        if maybe_result_var_name.is_none() {
+        if catches_exception {
+            if exception_ffi_index.is_none() {
+                bail!("FFI function has no exception argument");
+            }
+            let value_code = if maybe_result_var_name.is_some() {
+                "ffi_result".to_string()
+            } else if return_type.conversion() == &RustToFfiTypeConversion::UnitToAnything {
+                "()".to_string()
+            } else {
+                self.convert_type_from_ffi(
+                    &return_type,
+                    "ffi_result".to_string(),
+                    in_unsafe_context,
+                    false,
+                )?
+            };
+            // `ffi_result` may be uninitialized if an exception was caught
+            Ok(format!(
+                "let mut {e} = ::cpp_core::CppExceptionInfo::new();\n\
+                 let ffi_result = {{ {code} }};\n\
+                 if let Err(err) = {e}.to_result() {{\n\
+                 ::std::mem::forget(ffi_result);\n\
+                 return Err(err);\n\
+                 }}\n\
+                 Ok({value})",
+                e = exception_var_name,
+                code = code,
+                value = value_code,
+            ))
+        } else if maybe_result_var_name.is_none() {
             self.convert_type_from_ffi(&return_type, code, in_unsafe_context, true)
         } else {
             Ok(code)
@@ -1100,15 +1295,31 @@ impl Generator<'_> {
             format!("<{}>", generic_args.join(", "))
         };
 
+        // `#[deprecated]` has no effect on trait impl items
+        let deprecated_attribute = if body.is_some() && !is_in_trait_context {
+            self.current_database
+                .source_cpp_item(&func.id)?
+                .and_then(|item| item.item.as_function_ref())
+                .and_then(|function| function.deprecation.as_ref())
+                .map(|deprecation| match &deprecation.message {
+                    Some(message) => format!("#[deprecated(note = {:?})]\n", message),
+                    None => "#[deprecated]\n".to_string(),
+                })
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+
         // TODO: move condition texts to doc parser
         let doc = doc_formatter::function_doc(func.clone(), self.current_database)?
             + &condition_texts.doc_text;
         writeln!(
             self,
-            "{doc}{maybe_inline}{condition}{maybe_pub}{maybe_unsafe} \
+            "{doc}{maybe_inline}{deprecated}{condition}{maybe_pub}{maybe_unsafe} \
              fn {name}{generic_args_text}({args}){return_type} \
              {maybe_body}\n\n",
             doc = format_doc(&doc),
+            deprecated = deprecated_attribute,
             maybe_inline = if body.is_some() {
                 "#[inline(always)]\n"
             } else {
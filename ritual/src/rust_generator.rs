@@ -1,28 +1,31 @@
 use crate::config::CrateDependencyKind;
 use crate::cpp_checks::CppChecks;
 use crate::cpp_data::{
-    inherits, CppItem, CppPath, CppPathItem, CppTypeDeclaration, CppTypeDeclarationKind,
+    inherits, CppConstantValue, CppItem, CppPath, CppPathItem, CppTypeDeclaration,
+    CppTypeDeclarationKind,
 };
 use crate::cpp_ffi_data::{
     CppCast, CppFfiArgumentMeaning, CppFfiFunction, CppFfiFunctionKind, CppFfiItem, CppFfiType,
     CppFieldAccessorType, CppToFfiTypeConversion,
 };
-use crate::cpp_ffi_generator::ffi_type;
+use crate::cpp_ffi_generator::{ffi_type, is_movable};
 use crate::cpp_function::{CppFunction, CppOperator, ReturnValueAllocationPlace};
 use crate::cpp_type::{
     is_qflags, CppBuiltInNumericType, CppFunctionPointerType, CppPointerLikeTypeKind,
-    CppSpecificNumericType, CppSpecificNumericTypeKind, CppType, CppTypeRole,
+    CppSpecificNumericType, CppSpecificNumericTypeKind, CppTemplateArgumentValue, CppType,
+    CppTypeRole,
 };
 use crate::database::{DbItem, ItemId, ItemWithSource};
 use crate::processor::ProcessorData;
+use crate::rust_code_generator::rust_type_to_code;
 use crate::rust_info::{
-    NameType, RustEnumValue, RustExtraImpl, RustExtraImplKind, RustFfiWrapperData,
+    NameType, RustConstant, RustEnumValue, RustExtraImpl, RustExtraImplKind, RustFfiWrapperData,
     RustFlagEnumImpl, RustFunction, RustFunctionArgument, RustFunctionCaptionStrategy,
     RustFunctionKind, RustFunctionSelfArgKind, RustItem, RustModule, RustModuleKind, RustPathScope,
     RustQtReceiverData, RustQtReceiverImpl, RustQtReceiverType, RustReexport, RustReexportSource,
-    RustSignalOrSlotGetter, RustSizedType, RustSpecialModuleKind, RustStruct, RustStructKind,
-    RustTraitAssociatedType, RustTraitImpl, RustTraitImplExtraKind, RustTypeCaptionStrategy,
-    RustWrapperTypeKind, UnnamedRustFunction,
+    RustSignalOrSlotGetter, RustSizedType, RustSpecialModuleKind, RustStruct, RustStructField,
+    RustStructKind, RustTraitAssociatedType, RustTraitImpl, RustTraitImplExtraKind, RustTypeAlias,
+    RustTypeCaptionStrategy, RustWrapperTypeKind, UnnamedRustFunction,
 };
 use crate::rust_type::{
     RustClosureToCallbackConversion, RustCommonType, RustFinalType, RustFunctionPointerType,
@@ -31,7 +34,7 @@ use crate::rust_type::{
 use itertools::Itertools;
 use log::{debug, trace};
 use ritual_common::errors::{bail, err_msg, format_err, print_trace, Result};
-use ritual_common::string_utils::CaseOperations;
+use ritual_common::string_utils::{ends_with_digit, CaseOperations};
 use ritual_common::utils::MapIfOk;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::iter::Iterator;
@@ -45,6 +48,50 @@ pub fn qt_core_path(crate_name: &str) -> RustPath {
     }
 }
 
+/// Checks if `cpp_type` is a pointer (possibly multi-level) to a class.
+fn is_pointer_to_class(cpp_type: &CppType) -> bool {
+    match cpp_type {
+        CppType::PointerLike { target, .. } => match target.deref() {
+            CppType::Class(_) => true,
+            target => is_pointer_to_class(target),
+        },
+        _ => false,
+    }
+}
+
+/// Checks if `item` is a Rust type generated with the same fields as the C type.
+fn is_c_struct_wrapper(item: &RustItem) -> bool {
+    match item {
+        RustItem::Struct(RustStruct {
+            kind: RustStructKind::WrapperType(RustWrapperTypeKind::CStruct { .. }),
+            ..
+        }) => true,
+        _ => false,
+    }
+}
+
+/// Checks if values of the integer type `rust_type` can be converted to `c_int`
+/// without losing bits.
+fn fits_in_c_int(rust_type: &RustType) -> bool {
+    match rust_type {
+        RustType::Primitive(name) => {
+            ["i8", "u8", "i16", "u16", "i32", "u32"].contains(&name.as_str())
+        }
+        RustType::Common(RustCommonType { path, .. }) => [
+            "std::os::raw::c_char",
+            "std::os::raw::c_schar",
+            "std::os::raw::c_uchar",
+            "std::os::raw::c_short",
+            "std::os::raw::c_ushort",
+            "std::os::raw::c_int",
+            "std::os::raw::c_uint",
+        ]
+        .iter()
+        .any(|name| path == name),
+        _ => false,
+    }
+}
+
 /// Adds "_" to a string if it is a reserved word in Rust
 fn sanitize_rust_identifier(name: &str, is_module: bool) -> String {
     match name {
@@ -72,6 +119,152 @@ fn sanitize_rust_identifier_test() {
     assert_eq!(&sanitize_rust_identifier("lib", true), "lib_");
 }
 
+/// Returns Rust code of the value of a constant of `cpp_type`.
+/// `value_type` is the Rust type of the constant.
+fn constant_value_code(
+    cpp_type: &CppType,
+    value_type: &RustType,
+    value: &CppConstantValue,
+) -> String {
+    let is_unsigned = match cpp_type {
+        CppType::BuiltInNumeric(numeric) => numeric.is_unsigned_integer(),
+        CppType::SpecificNumeric(CppSpecificNumericType {
+            kind: CppSpecificNumericTypeKind::Integer { is_signed },
+            ..
+        }) => !is_signed,
+        _ => false,
+    };
+    match value {
+        CppConstantValue::Integer(value)
+            if cpp_type == &CppType::BuiltInNumeric(CppBuiltInNumericType::Bool) =>
+        {
+            (*value != 0).to_string()
+        }
+        CppConstantValue::Integer(value) if *value < 0 && is_unsigned => {
+            // the cast truncates the value to the width of the type on the target platform
+            format!("{}i64 as {}", value, rust_type_to_code(value_type, None))
+        }
+        CppConstantValue::Integer(value) => value.to_string(),
+        CppConstantValue::Float(value) if value.is_finite() => format!("{:?}", value),
+        CppConstantValue::Float(value) => {
+            let is_f32 = match cpp_type {
+                CppType::BuiltInNumeric(CppBuiltInNumericType::Float) => true,
+                CppType::SpecificNumeric(CppSpecificNumericType { bits, .. }) => *bits == 32,
+                _ => false,
+            };
+            let name = if value.is_nan() {
+                "NAN"
+            } else if value.is_sign_positive() {
+                "INFINITY"
+            } else {
+                "NEG_INFINITY"
+            };
+            format!("::std::{}::{}", if is_f32 { "f32" } else { "f64" }, name)
+        }
+        CppConstantValue::String(value) => format!("{:?}", value),
+    }
+}
+
+#[test]
+fn is_pointer_to_class_test() {
+    let class = CppType::Class(CppPath::from_good_str("Node"));
+    let int = CppType::BuiltInNumeric(CppBuiltInNumericType::Int);
+    assert!(!is_pointer_to_class(&class));
+    assert!(is_pointer_to_class(&CppType::new_pointer(
+        false,
+        class.clone()
+    )));
+    assert!(is_pointer_to_class(&CppType::new_pointer(
+        true,
+        CppType::new_pointer(false, class)
+    )));
+    assert!(!is_pointer_to_class(&CppType::new_pointer(false, int)));
+}
+
+#[test]
+fn fits_in_c_int_test() {
+    let raw_type = |name: &str| {
+        RustType::Common(RustCommonType {
+            path: RustPath::from_good_str("std::os::raw").join(name),
+            generic_arguments: None,
+        })
+    };
+    assert!(fits_in_c_int(&raw_type("c_int")));
+    assert!(fits_in_c_int(&raw_type("c_uint")));
+    assert!(fits_in_c_int(&RustType::Primitive("u16".to_string())));
+    assert!(!fits_in_c_int(&raw_type("c_long")));
+    assert!(!fits_in_c_int(&raw_type("c_ulonglong")));
+    assert!(!fits_in_c_int(&RustType::Primitive("u64".to_string())));
+}
+
+#[test]
+fn constant_value_code_test() {
+    let raw_type = |name: &str| {
+        RustType::Common(RustCommonType {
+            path: RustPath::from_good_str("std::os::raw").join(name),
+            generic_arguments: None,
+        })
+    };
+    let double = CppType::BuiltInNumeric(CppBuiltInNumericType::Double);
+    let float = CppType::BuiltInNumeric(CppBuiltInNumericType::Float);
+    assert_eq!(
+        constant_value_code(
+            &double,
+            &raw_type("c_double"),
+            &CppConstantValue::Float(1.5)
+        ),
+        "1.5"
+    );
+    assert_eq!(
+        constant_value_code(
+            &double,
+            &raw_type("c_double"),
+            &CppConstantValue::Float(std::f64::INFINITY)
+        ),
+        "::std::f64::INFINITY"
+    );
+    assert_eq!(
+        constant_value_code(
+            &float,
+            &raw_type("c_float"),
+            &CppConstantValue::Float(std::f64::NEG_INFINITY)
+        ),
+        "::std::f32::NEG_INFINITY"
+    );
+    assert_eq!(
+        constant_value_code(
+            &float,
+            &raw_type("c_float"),
+            &CppConstantValue::Float(std::f64::NAN)
+        ),
+        "::std::f32::NAN"
+    );
+    assert_eq!(
+        constant_value_code(
+            &CppType::BuiltInNumeric(CppBuiltInNumericType::Bool),
+            &RustType::bool(),
+            &CppConstantValue::Integer(1)
+        ),
+        "true"
+    );
+    assert_eq!(
+        constant_value_code(
+            &CppType::BuiltInNumeric(CppBuiltInNumericType::UInt),
+            &raw_type("c_uint"),
+            &CppConstantValue::Integer(-1)
+        ),
+        "-1i64 as ::std::os::raw::c_uint"
+    );
+    assert_eq!(
+        constant_value_code(
+            &CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+            &raw_type("c_int"),
+            &CppConstantValue::Integer(-1)
+        ),
+        "-1"
+    );
+}
+
 #[derive(Debug)]
 struct FunctionWithDesiredPath {
     function: UnnamedRustFunction,
@@ -547,6 +740,9 @@ fn check_trait_impl_uniqueness(
 struct State<'b, 'a> {
     data: &'b mut ProcessorData<'a>,
     special_module_paths: HashMap<RustSpecialModuleKind, RustPath>,
+    /// If true, C types with fields that can't be converted to Rust
+    /// are generated as opaque types.
+    allow_opaque_c_structs: bool,
 }
 
 impl State<'_, '_> {
@@ -678,7 +874,9 @@ impl State<'_, '_> {
                     generic_arguments: Some(vec![pointer]),
                 })
             }
-            CppType::TemplateParameter { .. } => bail!("invalid cpp type"),
+            CppType::TemplateParameter { .. } | CppType::TemplateArgumentValue(_) => {
+                bail!("invalid cpp type")
+            }
         };
 
         Ok(rust_type)
@@ -902,7 +1100,18 @@ impl State<'_, '_> {
     fn generate_ffi_function(&self, data: &CppFfiFunction) -> Result<RustFunction> {
         let mut args = Vec::new();
         for (ffi_index, arg) in data.arguments.iter().enumerate() {
-            let rust_type = self.ffi_type_to_rust_ffi_type(arg.argument_type.ffi_type())?;
+            let rust_type = if arg.meaning == CppFfiArgumentMeaning::Exception {
+                RustType::PointerLike {
+                    kind: RustPointerLikeTypeKind::Pointer,
+                    is_const: false,
+                    target: Box::new(RustType::Common(RustCommonType {
+                        path: RustPath::from_good_str("cpp_core::CppExceptionInfo"),
+                        generic_arguments: None,
+                    })),
+                }
+            } else {
+                self.ffi_type_to_rust_ffi_type(arg.argument_type.ffi_type())?
+            };
             args.push(RustFunctionArgument {
                 name: sanitize_rust_identifier(&arg.name, false),
                 argument_type: RustFinalType::new(rust_type, RustToFfiTypeConversion::None)?,
@@ -1304,7 +1513,9 @@ impl State<'_, '_> {
 
         let mut arguments = Vec::new();
         for (arg_index, arg) in function.arguments.iter().enumerate() {
-            if arg.meaning != CppFfiArgumentMeaning::ReturnValue {
+            if arg.meaning != CppFfiArgumentMeaning::ReturnValue
+                && arg.meaning != CppFfiArgumentMeaning::Exception
+            {
                 let arg_type = self.rust_final_type(
                     &arg.argument_type,
                     &arg.meaning,
@@ -1347,6 +1558,20 @@ impl State<'_, '_> {
                 Some(checks),
             )?
         };
+        let is_nullable = self
+            .data
+            .db
+            .find_directives_for(&item.id)?
+            .map_or(false, |directives| directives.nullable);
+        if let RustToFfiTypeConversion::UtilsPtrToPtr {} = return_type.conversion() {
+            if is_nullable {
+                // null value is represented as `None`, so other values can be returned as `Ref`
+                return_type = RustFinalType::new(
+                    return_type.ffi_type().clone(),
+                    RustToFfiTypeConversion::OptionUtilsRefToPtr {},
+                )?;
+            }
+        }
         if return_type.api_type().is_ref() && return_type.api_type().lifetime().is_none() {
             let mut found = false;
             for arg in &arguments {
@@ -1381,6 +1606,18 @@ impl State<'_, '_> {
                 return_type = return_type.with_lifetime(return_lifetime)?;
             }
         }
+        if function
+            .arguments
+            .iter()
+            .any(|arg| arg.meaning == CppFfiArgumentMeaning::Exception)
+        {
+            return_type = RustFinalType::new(
+                return_type.ffi_type().clone(),
+                RustToFfiTypeConversion::ResultFromException(Box::new(
+                    return_type.conversion().clone(),
+                )),
+            )?;
+        }
 
         let mut unnamed_function = UnnamedRustFunction {
             is_public: true,
@@ -1490,6 +1727,60 @@ impl State<'_, '_> {
         Ok(results)
     }
 
+    /// Returns true if the C struct or union `cpp_path` should be generated
+    /// as a Rust type with the same fields. Only C types with a known layout
+    /// (i.e. with parsed fields that can all be converted to Rust) are generated this way.
+    /// Returns an error if a field can't be converted (e.g. because its type
+    /// is not processed yet), unless `allow_opaque_c_structs` is set.
+    fn is_c_struct(&self, cpp_path: &CppPath) -> Result<bool> {
+        if !self.data.config.plain_c_mode() {
+            return Ok(false);
+        }
+        let fields = self
+            .data
+            .db
+            .cpp_items()
+            .filter_map(|item| item.item.as_field_ref())
+            .filter(|field| field.path.parent().ok().as_ref() == Some(cpp_path))
+            .collect_vec();
+        if fields.is_empty() {
+            return Ok(false);
+        }
+        for field in fields {
+            // pointers to other C types don't depend on their layout and may refer
+            // to types that are processed later (including the type itself)
+            if is_pointer_to_class(&field.field_type) {
+                continue;
+            }
+            // structs stored by value must also be generated with their fields
+            if let CppType::Class(path) = &field.field_type {
+                if let Ok(wrapper) = self.find_wrapper_type(path) {
+                    if !is_c_struct_wrapper(wrapper.item) {
+                        debug!(
+                            "generating opaque type for {} because {} is opaque",
+                            cpp_path.to_cpp_pseudo_code(),
+                            path.to_cpp_pseudo_code()
+                        );
+                        return Ok(false);
+                    }
+                }
+            }
+            if let Err(err) = self.ffi_type_to_rust_ffi_type(&field.field_type) {
+                if !self.allow_opaque_c_structs {
+                    return Err(err);
+                }
+                debug!(
+                    "generating opaque type for {} because of field {}: {}",
+                    cpp_path.to_cpp_pseudo_code(),
+                    field.path.to_cpp_pseudo_code(),
+                    err
+                );
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
     fn find_wrapper_type(&self, cpp_path: &CppPath) -> Result<DbItem<&RustItem>> {
         self.data
             .db
@@ -1506,10 +1797,8 @@ impl State<'_, '_> {
         name_type: NameType<'_>,
     ) -> Result<RustPathScope> {
         trace!("get_path_scope({:?}, {:?})", parent_path, name_type);
-        if let Some(hook) = self.data.config.rust_path_scope_hook() {
-            if let Some(strategy) = hook(parent_path)? {
-                return Ok(strategy);
-            }
+        if let Some(strategy) = self.data.config.rust_path_scope_from_hooks(parent_path)? {
+            return Ok(strategy);
         }
 
         let allow_module_for_nested;
@@ -1570,6 +1859,22 @@ impl State<'_, '_> {
         })
     }
 
+    /// Returns scope for a constant in the global namespace. Macro constants are placed
+    /// into the module of the namespace of their header.
+    fn macro_constant_scope(&self, cpp_path: &CppPath) -> Result<RustPathScope> {
+        let namespace = self
+            .data
+            .db
+            .cpp_items()
+            .filter_map(|item| item.item.as_macro_constant_ref())
+            .find(|item| &item.path == cpp_path)
+            .and_then(|item| item.namespace.as_ref());
+        match namespace {
+            Some(namespace) => self.get_path_scope(namespace, NameType::Constant),
+            None => Ok(self.default_path_scope()),
+        }
+    }
+
     fn default_path_scope(&self) -> RustPathScope {
         RustPathScope {
             path: RustPath {
@@ -1579,9 +1884,39 @@ impl State<'_, '_> {
         }
     }
 
+    /// Returns caption for a non-type template argument. Enum values are represented
+    /// by the name of the enum variant if it's known.
+    fn template_argument_value_caption(&self, value: &CppTemplateArgumentValue) -> String {
+        if let CppType::Enum { path } = &*value.value_type {
+            let variant = self
+                .data
+                .db
+                .cpp_children(path)
+                .filter_map(|item| item.item.as_enum_value_ref())
+                .find(|item| item.value == value.value);
+            if let Some(variant) = variant {
+                return variant.path.last().name.clone();
+            }
+        }
+        CppType::TemplateArgumentValue(value.clone()).ascii_caption()
+    }
+
     fn type_list_caption(&self, types: &[CppType], context: &RustPath) -> Result<String> {
         let mut captions = Vec::new();
         for arg in types {
+            if let CppType::TemplateArgumentValue(value) = arg {
+                let caption = self.template_argument_value_caption(value);
+                // adjacent numbers would be merged in class case ("I324"),
+                // so they are separated as in "I32X4"
+                if caption.starts_with(|c: char| c.is_digit(10))
+                    && captions.last().map_or(false, ends_with_digit)
+                {
+                    captions.push(format!("x{}", caption));
+                } else {
+                    captions.push(caption);
+                }
+                continue;
+            }
             let rust_type = self.rust_final_type(
                 &ffi_type(arg, CppTypeRole::NotReturnType)?,
                 &CppFfiArgumentMeaning::Argument(0),
@@ -1649,11 +1984,13 @@ impl State<'_, '_> {
                 }
             }
             CppFfiFunctionKind::FieldAccessor { accessor_type } => {
-                let field = cpp_item
-                    .as_field_ref()
-                    .ok_or_else(|| err_msg("invalid source cpp item type"))?;
+                let path = match cpp_item {
+                    CppItem::ClassField(field) => &field.path,
+                    CppItem::GlobalVariable(variable) => &variable.path,
+                    _ => bail!("invalid source cpp item type"),
+                };
 
-                let name = &field.path.last().name;
+                let name = &path.last().name;
                 let function_name = match accessor_type {
                     CppFieldAccessorType::CopyGetter | CppFieldAccessorType::ConstRefGetter => {
                         name.to_string()
@@ -1687,10 +2024,12 @@ impl State<'_, '_> {
     }
 
     fn generate_rust_path(&self, cpp_path: &CppPath, name_type: NameType<'_>) -> Result<RustPath> {
-        if let Some(hook) = self.data.config.rust_path_hook() {
-            if let Some(path) = hook(cpp_path, name_type.clone(), &self.data)? {
-                return Ok(path);
-            }
+        if let Some(path) = self
+            .data
+            .config
+            .rust_path_from_hooks(cpp_path, &name_type, &self.data)?
+        {
+            return Ok(path);
         }
         let scope = match &name_type {
             NameType::FfiFunction => RustPathScope {
@@ -1708,6 +2047,7 @@ impl State<'_, '_> {
             NameType::Type { .. }
             | NameType::Module { .. }
             | NameType::EnumValue
+            | NameType::Constant
             | NameType::ApiFunction { .. }
             | NameType::ReceiverFunction { .. } => {
                 if let Ok(parent) = cpp_path.parent() {
@@ -1732,12 +2072,23 @@ impl State<'_, '_> {
                     } else {
                         self.default_path_scope()
                     }
+                } else if name_type == NameType::Constant {
+                    self.macro_constant_scope(cpp_path)?
                 } else {
                     self.default_path_scope()
                 }
             }
         };
 
+        let rename = self.directive_rename(cpp_path, &name_type)?;
+        let last_name = || -> Result<String> {
+            if let Some(name) = &rename {
+                Ok(name.clone())
+            } else {
+                self.cpp_path_item_to_name(cpp_path.last(), &scope.path, &name_type)
+            }
+        };
+
         let full_last_name = match &name_type {
             NameType::SizedItem => cpp_path
                 .items()
@@ -1745,7 +2096,9 @@ impl State<'_, '_> {
                 .map_if_ok(|item| self.cpp_path_item_to_name(item, &scope.path, &name_type))?
                 .join("_"),
             NameType::ApiFunction(function) => {
-                let s = if let Some(last_name_override) =
+                let s = if let Some(name) = &rename {
+                    name.clone()
+                } else if let Some(last_name_override) =
                     self.special_function_rust_name(function.clone(), &scope.path)?
                 {
                     last_name_override
@@ -1780,13 +2133,11 @@ impl State<'_, '_> {
                     self.cpp_path_item_to_name(&path_item, &scope.path, &name_type)?
                         .to_class_case()
                 } else {
-                    self.cpp_path_item_to_name(&cpp_path.last(), &scope.path, &name_type)?
-                        .to_class_case()
+                    last_name()?.to_class_case()
                 }
             }
-            NameType::Module { .. } => self
-                .cpp_path_item_to_name(&cpp_path.last(), &scope.path, &name_type)?
-                .to_snake_case(),
+            NameType::Module { .. } => last_name()?.to_snake_case(),
+            NameType::Constant => last_name()?.to_upper_case_words(),
             NameType::FfiFunction => cpp_path.last().name.clone(),
             NameType::QtSlotWrapper { signal_arguments } => {
                 if signal_arguments.is_empty() {
@@ -1824,6 +2175,28 @@ impl State<'_, '_> {
         }
     }
 
+    /// Returns the name specified with the `ritual:rename` directive
+    /// for the item named by `cpp_path` and `name_type`.
+    fn directive_rename(
+        &self,
+        cpp_path: &CppPath,
+        name_type: &NameType<'_>,
+    ) -> Result<Option<String>> {
+        let directives = match name_type {
+            NameType::ApiFunction(function) => self.data.db.find_directives_for(&function.id)?,
+            NameType::Type { .. }
+            | NameType::Module { .. }
+            | NameType::EnumValue
+            | NameType::Constant => self.data.db.find_directives_for_cpp_path(cpp_path),
+            NameType::FfiFunction
+            | NameType::SizedItem
+            | NameType::ReceiverFunction { .. }
+            | NameType::QtSlotWrapper { .. }
+            | NameType::QtSignalWrapper { .. } => None,
+        };
+        Ok(directives.and_then(|directives| directives.rename.clone()))
+    }
+
     fn process_ffi_item(
         &self,
         ffi_item: DbItem<&CppFfiItem>,
@@ -1856,6 +2229,23 @@ impl State<'_, '_> {
             if !argument.is_template_parameter() {
                 if let CppType::Enum { path } = &argument {
                     let rust_type = self.find_wrapper_type(path)?;
+                    if let RustItem::Struct(RustStruct {
+                        kind:
+                            RustStructKind::WrapperType(RustWrapperTypeKind::EnumWrapper {
+                                underlying_type,
+                            }),
+                        ..
+                    }) = rust_type.item
+                    {
+                        // `QFlags` stores values as `int`
+                        if !fits_in_c_int(underlying_type) {
+                            bail!(
+                                "QFlags can't store values of {} with underlying type {}",
+                                path.to_cpp_pseudo_code(),
+                                rust_type_to_code(underlying_type, None)
+                            );
+                        }
+                    }
                     let rust_type_path = rust_type
                         .item
                         .path()
@@ -1906,10 +2296,14 @@ impl State<'_, '_> {
 
         let mut rust_items = Vec::new();
 
-        let is_movable = false;
+        let is_movable = is_movable(self.data.db, data);
 
         let wrapper_kind;
-        if is_movable {
+        if self.is_c_struct(&data.path)? {
+            wrapper_kind = RustWrapperTypeKind::CStruct {
+                is_union: data.kind.is_union(),
+            };
+        } else if is_movable {
             let internal_path = self.generate_rust_path(&data.path, NameType::SizedItem)?;
 
             if internal_path == public_path {
@@ -2031,11 +2425,13 @@ impl State<'_, '_> {
                 });
                 Ok(vec![rust_item])
             }
-            CppItem::Type(data) => match data.kind {
-                CppTypeDeclarationKind::Class { .. } => {
+            CppItem::Type(data) => match &data.kind {
+                CppTypeDeclarationKind::Class { .. } | CppTypeDeclarationKind::Union { .. } => {
                     self.process_cpp_class(cpp_item.map(|v| v.as_type_ref().unwrap()))
                 }
-                CppTypeDeclarationKind::Enum => {
+                CppTypeDeclarationKind::Enum {
+                    underlying_type, ..
+                } => {
                     let rust_path = self.generate_rust_path(
                         &data.path,
                         NameType::Type {
@@ -2044,7 +2440,9 @@ impl State<'_, '_> {
                     )?;
                     let rust_item = RustItem::Struct(RustStruct {
                         path: rust_path,
-                        kind: RustStructKind::WrapperType(RustWrapperTypeKind::EnumWrapper),
+                        kind: RustStructKind::WrapperType(RustWrapperTypeKind::EnumWrapper {
+                            underlying_type: self.ffi_type_to_rust_ffi_type(underlying_type)?,
+                        }),
                         is_public: true,
                         qt_receiver_data: None,
                     });
@@ -2156,13 +2554,97 @@ impl State<'_, '_> {
                 };
                 Ok(vec![RustItem::Function(rust_function)])
             }
-            CppItem::ClassField(_) | CppItem::ClassBase(_) => {
+            CppItem::GlobalVariable(variable) => {
+                if let Some(value) = &variable.value {
+                    let rust_item =
+                        self.process_constant(&variable.path, &variable.variable_type, value)?;
+                    Ok(vec![rust_item])
+                } else {
+                    // variables without a known value only have accessors
+                    Ok(Vec::new())
+                }
+            }
+            CppItem::MacroConstant(constant) => {
+                let rust_item =
+                    self.process_constant(&constant.path, &constant.value_type, &constant.value)?;
+                Ok(vec![rust_item])
+            }
+            CppItem::TypeAlias(alias) => {
+                let path = self.generate_rust_path(
+                    &alias.path,
+                    NameType::Type {
+                        is_from_other_crate: false,
+                    },
+                )?;
+                // the alias refers to the type used in the Rust API (e.g. `Ref<T>` for references)
+                let target_type = ffi_type(&alias.target_type, CppTypeRole::ReturnType)?;
+                let target = self
+                    .rust_final_type(
+                        &target_type,
+                        &CppFfiArgumentMeaning::ReturnValue,
+                        ReturnValueAllocationPlace::Stack,
+                        None,
+                    )?
+                    .api_type()
+                    .clone();
+                Ok(vec![RustItem::TypeAlias(RustTypeAlias { path, target })])
+            }
+            CppItem::ClassField(field) => {
+                if !self.data.config.plain_c_mode() {
+                    // only need to process FFI items
+                    return Ok(Vec::new());
+                }
+                let class_path = field.path.parent()?;
+                let struct_item = self.find_wrapper_type(&class_path)?;
+                if !is_c_struct_wrapper(struct_item.item) {
+                    // the type is opaque
+                    return Ok(Vec::new());
+                }
+                let struct_path = struct_item
+                    .item
+                    .path()
+                    .ok_or_else(|| err_msg("RustDatabaseItem for class has no path"))?;
+                let name = sanitize_rust_identifier(&field.path.last().name.to_snake_case(), false);
+                let rust_item = RustItem::StructField(RustStructField {
+                    path: struct_path.join(name),
+                    field_type: self.ffi_type_to_rust_ffi_type(&field.field_type)?,
+                });
+                Ok(vec![rust_item])
+            }
+            CppItem::ClassBase(_) => {
                 // only need to process FFI items
                 Ok(Vec::new())
             }
         }
     }
 
+    fn process_constant(
+        &self,
+        cpp_path: &CppPath,
+        cpp_type: &CppType,
+        value: &CppConstantValue,
+    ) -> Result<RustItem> {
+        let path = self.generate_rust_path(cpp_path, NameType::Constant)?;
+        let value_type = if let CppConstantValue::String(_) = value {
+            RustType::PointerLike {
+                kind: RustPointerLikeTypeKind::Reference {
+                    lifetime: Some("static".into()),
+                },
+                is_const: true,
+                target: Box::new(RustType::Primitive("str".into())),
+            }
+        } else {
+            let ffi_type = ffi_type(cpp_type, CppTypeRole::NotReturnType)?;
+            self.ffi_type_to_rust_ffi_type(ffi_type.ffi_type())?
+        };
+        let value = constant_value_code(cpp_type, &value_type, value);
+        Ok(RustItem::Constant(RustConstant {
+            path,
+            value_type,
+            value,
+        }))
+    }
+
     fn generate_crate_reexport(&mut self, crate_name: &str) -> Result<()> {
         let path = RustPath::from_parts(vec![
             self.data.config.crate_properties().name().to_string(),
@@ -2198,9 +2680,9 @@ impl State<'_, '_> {
         source_id: Option<ItemId>,
         mut item: RustItem,
     ) -> Result<Option<ItemId>> {
-        if let Some(hook) = self.data.config.rust_item_hook() {
-            hook(&mut item, &self.data)?;
-        }
+        self.data
+            .config
+            .apply_rust_item_hooks(&mut item, &self.data)?;
         self.data.db.add_rust_item(source_id, item)
     }
 
@@ -2260,7 +2742,12 @@ impl State<'_, '_> {
             }
 
             if !any_processed {
-                break;
+                if self.allow_opaque_c_structs {
+                    break;
+                }
+                // fields of remaining C types can't be converted,
+                // so they are generated as opaque types
+                self.allow_opaque_c_structs = true;
             }
         }
 
@@ -2410,6 +2897,7 @@ pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
     let mut state = State {
         data,
         special_module_paths: HashMap::new(),
+        allow_opaque_c_structs: false,
     };
     for &module in &[
         RustSpecialModuleKind::CrateRoot,
@@ -29,9 +29,19 @@ pub struct RustQtSlotWrapper {
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum RustWrapperTypeKind {
-    EnumWrapper,
+    EnumWrapper {
+        /// FFI type corresponding to the underlying type of the C++ enum
+        underlying_type: RustType,
+    },
     ImmovableClassWrapper,
-    MovableClassWrapper { sized_type_path: RustPath },
+    /// `#[repr(C)]` struct or union with the same fields as the C type
+    /// (only used in plain C mode)
+    CStruct {
+        is_union: bool,
+    },
+    MovableClassWrapper {
+        sized_type_path: RustPath,
+    },
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
@@ -556,6 +566,32 @@ pub struct RustReexport {
     pub source: RustReexportSource,
 }
 
+/// Rust constant corresponding to a C++ compile-time constant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RustConstant {
+    pub path: RustPath,
+    /// Type of the constant
+    pub value_type: RustType,
+    /// Rust code of the value
+    pub value: String,
+}
+
+/// Rust type alias corresponding to a C++ typedef or `using` declaration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RustTypeAlias {
+    pub path: RustPath,
+    /// Type the alias refers to
+    pub target: RustType,
+}
+
+/// Field of a Rust struct generated for a C struct or union
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RustStructField {
+    pub path: RustPath,
+    /// Type of the field
+    pub field_type: RustType,
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RustItem {
@@ -566,6 +602,9 @@ pub enum RustItem {
     ExtraImpl(RustExtraImpl),
     Function(RustFunction),
     Reexport(RustReexport),
+    Constant(RustConstant),
+    TypeAlias(RustTypeAlias),
+    StructField(RustStructField),
 }
 
 impl RustItem {
@@ -576,6 +615,9 @@ impl RustItem {
             RustItem::EnumValue(data) => Some(&data.path),
             RustItem::Function(data) => Some(&data.path),
             RustItem::Reexport(data) => Some(&data.path),
+            RustItem::Constant(data) => Some(&data.path),
+            RustItem::TypeAlias(data) => Some(&data.path),
+            RustItem::StructField(data) => Some(&data.path),
             RustItem::TraitImpl(_) | RustItem::ExtraImpl(_) => None,
         }
     }
@@ -634,6 +676,27 @@ impl RustItem {
             None
         }
     }
+    pub fn as_constant_ref(&self) -> Option<&RustConstant> {
+        if let RustItem::Constant(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
+    pub fn as_type_alias_ref(&self) -> Option<&RustTypeAlias> {
+        if let RustItem::TypeAlias(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
+    pub fn as_struct_field_ref(&self) -> Option<&RustStructField> {
+        if let RustItem::StructField(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
     pub fn as_function_ref(&self) -> Option<&RustFunction> {
         if let RustItem::Function(value) = self {
             Some(value)
@@ -735,6 +798,27 @@ impl RustItem {
                     false
                 }
             }
+            RustItem::Constant(_) => {
+                if let RustItem::Constant(_) = other {
+                    true
+                } else {
+                    false
+                }
+            }
+            RustItem::TypeAlias(_) => {
+                if let RustItem::TypeAlias(_) = other {
+                    true
+                } else {
+                    false
+                }
+            }
+            RustItem::StructField(_) => {
+                if let RustItem::StructField(_) = other {
+                    true
+                } else {
+                    false
+                }
+            }
         }
     }
 
@@ -799,6 +883,17 @@ impl RustItem {
                 data.path.full_name(None),
                 data.target.last()
             ),
+            RustItem::Constant(data) => format!("const {}", data.path.full_name(None)),
+            RustItem::TypeAlias(data) => format!(
+                "type {} = {}",
+                data.path.full_name(None),
+                rust_type_to_code(&data.target, None)
+            ),
+            RustItem::StructField(data) => format!(
+                "field {}: {}",
+                data.path.full_name(None),
+                rust_type_to_code(&data.field_type, None)
+            ),
         }
     }
 }
@@ -826,6 +921,7 @@ pub enum NameType<'a> {
         is_from_other_crate: bool,
     },
     EnumValue,
+    Constant,
     Module {
         is_from_other_crate: bool,
     },
@@ -167,6 +167,9 @@ pub enum RustToFfiTypeConversion {
     RefTo(Box<RustToFfiTypeConversion>),
     ImplCastInto(Box<RustToFfiTypeConversion>),
     ClosureToCallback(Box<RustClosureToCallbackConversion>),
+    /// Rust public type is wrapped in `Result<_, cpp_core::CppException>`.
+    /// The error is received through the FFI function's exception argument.
+    ResultFromException(Box<RustToFfiTypeConversion>),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -305,6 +308,19 @@ impl RustFinalType {
             RustToFfiTypeConversion::ClosureToCallback { .. } => {
                 RustType::GenericParameter("T".into())
             }
+            RustToFfiTypeConversion::ResultFromException(conversion) => {
+                let intermediate = RustFinalType::new(ffi_type.clone(), (**conversion).clone())?;
+                RustType::Common(RustCommonType {
+                    path: RustPath::from_good_str("std::result::Result"),
+                    generic_arguments: Some(vec![
+                        intermediate.api_type,
+                        RustType::Common(RustCommonType {
+                            path: RustPath::from_good_str("cpp_core::CppException"),
+                            generic_arguments: None,
+                        }),
+                    ]),
+                })
+            }
         };
         Ok(RustFinalType {
             api_type,
@@ -0,0 +1,372 @@
+//! Implementation of the `export_sqlite` operation.
+//!
+//! The operation writes the database of the current crate to
+//! `<workspace>/export/<crate name>.sqlite`. All items are listed in `items` table
+//! with their sources, and each item kind has its own table (see `SCHEMA`).
+//! `data` columns contain JSON representation of the items, so fields
+//! without a dedicated column can be accessed with `json_extract`.
+//!
+//! Example: FFI items that failed on some library targets but not on all of them:
+//!
+//! ```sql
+//! SELECT ffi_items.path, library_targets.text FROM cpp_checks
+//! JOIN ffi_items ON ffi_items.id = cpp_checks.ffi_item_id
+//! JOIN library_targets ON library_targets.id = cpp_checks.library_target_id
+//! WHERE NOT cpp_checks.is_success AND ffi_items.id IN
+//!     (SELECT ffi_item_id FROM cpp_checks WHERE is_success);
+//! ```
+
+use crate::database::{Database, DatabaseItemData};
+use crate::external_step::ItemKind;
+use crate::processor::ProcessorData;
+use crate::rust_info::RustItem;
+use log::info;
+use ritual_common::errors::{bail, Result, ResultExt};
+use ritual_common::file_utils::{create_dir_all, remove_file};
+use ritual_common::target::LibraryTarget;
+use rusqlite::{params, Connection, Transaction};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+
+/// Name of the operation.
+pub const EXPORT_SQLITE_OPERATION: &str = "export_sqlite";
+
+/// Tables of the exported database. Item IDs are stored as text (e.g. `"moqt_core#12"`).
+/// `source_id` of an item may refer to an item of another crate.
+/// `library_targets` contains targets of the crate and targets of its C++ checks.
+const SCHEMA: &str = "
+CREATE TABLE crate (
+    name TEXT NOT NULL,
+    version TEXT NOT NULL,
+    schema_version INTEGER NOT NULL
+);
+CREATE TABLE items (
+    id TEXT PRIMARY KEY,
+    source_id TEXT,
+    kind TEXT NOT NULL,
+    text TEXT NOT NULL
+);
+CREATE INDEX items_source_id ON items(source_id);
+CREATE TABLE library_targets (
+    id INTEGER PRIMARY KEY,
+    target TEXT NOT NULL,
+    cpp_library_version TEXT,
+    text TEXT NOT NULL
+);
+CREATE TABLE cpp_items (
+    id TEXT PRIMARY KEY,
+    source_id TEXT,
+    kind TEXT NOT NULL,
+    path TEXT,
+    parent_path TEXT,
+    data TEXT NOT NULL
+);
+CREATE TABLE ffi_items (
+    id TEXT PRIMARY KEY,
+    source_id TEXT,
+    kind TEXT NOT NULL,
+    path TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE cpp_checks (
+    id TEXT PRIMARY KEY,
+    ffi_item_id TEXT,
+    library_target_id INTEGER NOT NULL,
+    is_success INTEGER NOT NULL
+);
+CREATE INDEX cpp_checks_ffi_item_id ON cpp_checks(ffi_item_id);
+CREATE TABLE rust_items (
+    id TEXT PRIMARY KEY,
+    source_id TEXT,
+    kind TEXT NOT NULL,
+    path TEXT,
+    is_public INTEGER,
+    data TEXT NOT NULL
+);
+CREATE TABLE doc_items (
+    id TEXT PRIMARY KEY,
+    source_id TEXT,
+    url TEXT,
+    anchor TEXT,
+    mismatched_declaration TEXT,
+    html TEXT NOT NULL
+);
+CREATE TABLE directives (
+    id TEXT PRIMARY KEY,
+    source_id TEXT,
+    skip INTEGER NOT NULL,
+    rename TEXT,
+    movable INTEGER NOT NULL,
+    nullable INTEGER NOT NULL
+);
+";
+
+/// Returns name of the enum variant of `value`.
+fn variant_name(value: &impl Serialize) -> Result<String> {
+    match serde_json::to_value(value)? {
+        Value::String(name) => Ok(name),
+        Value::Object(map) if map.len() == 1 => Ok(map.into_iter().next().unwrap().0),
+        value => bail!("expected an enum variant, got: {}", value),
+    }
+}
+
+fn is_public(item: &RustItem) -> Option<bool> {
+    match item {
+        RustItem::Module(data) => Some(data.is_public),
+        RustItem::Struct(data) => Some(data.is_public),
+        RustItem::Function(data) => Some(data.is_public),
+        _ => None,
+    }
+}
+
+fn library_target_id(targets: &mut Vec<LibraryTarget>, target: &LibraryTarget) -> i64 {
+    let index = match targets.iter().position(|t| t == target) {
+        Some(index) => index,
+        None => {
+            targets.push(target.clone());
+            targets.len() - 1
+        }
+    };
+    index as i64 + 1
+}
+
+fn write_items(transaction: &Transaction<'_>, db: &Database) -> Result<()> {
+    transaction.execute(
+        "INSERT INTO crate VALUES (?, ?, ?)",
+        params![db.crate_name(), db.crate_version(), db.schema_version()],
+    )?;
+
+    let mut items = transaction.prepare("INSERT INTO items VALUES (?, ?, ?, ?)")?;
+    let mut cpp_items = transaction.prepare("INSERT INTO cpp_items VALUES (?, ?, ?, ?, ?, ?)")?;
+    let mut ffi_items = transaction.prepare("INSERT INTO ffi_items VALUES (?, ?, ?, ?, ?)")?;
+    let mut cpp_checks = transaction.prepare("INSERT INTO cpp_checks VALUES (?, ?, ?, ?)")?;
+    let mut rust_items = transaction.prepare("INSERT INTO rust_items VALUES (?, ?, ?, ?, ?, ?)")?;
+    let mut doc_items = transaction.prepare("INSERT INTO doc_items VALUES (?, ?, ?, ?, ?, ?)")?;
+    let mut directives = transaction.prepare("INSERT INTO directives VALUES (?, ?, ?, ?, ?, ?)")?;
+
+    let mut targets = db.environments().to_vec();
+    for item in db.items() {
+        let id = item.id.to_string();
+        let source_id = item.source_id.as_ref().map(|id| id.to_string());
+        items.execute(params![
+            id,
+            source_id,
+            variant_name(&ItemKind::of(item.item))?,
+            item.item.short_text(),
+        ])?;
+
+        match item.item {
+            DatabaseItemData::CppItem(cpp_item) => {
+                let path = cpp_item.path();
+                cpp_items.execute(params![
+                    id,
+                    source_id,
+                    variant_name(cpp_item)?,
+                    path.map(|path| path.to_cpp_pseudo_code()),
+                    path.filter(|path| path.has_parent())
+                        .map(|path| path.parent())
+                        .transpose()?
+                        .map(|path| path.to_cpp_pseudo_code()),
+                    serde_json::to_string(cpp_item)?,
+                ])?;
+            }
+            DatabaseItemData::FfiItem(ffi_item) => {
+                ffi_items.execute(params![
+                    id,
+                    source_id,
+                    variant_name(ffi_item)?,
+                    ffi_item.path().to_cpp_pseudo_code(),
+                    serde_json::to_string(ffi_item)?,
+                ])?;
+            }
+            DatabaseItemData::CppChecksItem(check) => {
+                cpp_checks.execute(params![
+                    id,
+                    source_id,
+                    library_target_id(&mut targets, &check.env),
+                    check.is_success,
+                ])?;
+            }
+            DatabaseItemData::RustItem(rust_item) => {
+                rust_items.execute(params![
+                    id,
+                    source_id,
+                    variant_name(rust_item)?,
+                    rust_item.path().map(|path| path.full_name(None)),
+                    is_public(rust_item),
+                    serde_json::to_string(rust_item)?,
+                ])?;
+            }
+            DatabaseItemData::DocItem(doc) => {
+                doc_items.execute(params![
+                    id,
+                    source_id,
+                    doc.url,
+                    doc.anchor,
+                    doc.mismatched_declaration,
+                    doc.html,
+                ])?;
+            }
+            DatabaseItemData::DirectivesItem(item) => {
+                directives.execute(params![
+                    id,
+                    source_id,
+                    item.skip,
+                    item.rename,
+                    item.movable,
+                    item.nullable,
+                ])?;
+            }
+        }
+    }
+
+    let mut library_targets =
+        transaction.prepare("INSERT INTO library_targets VALUES (?, ?, ?, ?)")?;
+    for (index, target) in targets.iter().enumerate() {
+        library_targets.execute(params![
+            index as i64 + 1,
+            target.target.short_text(),
+            target.cpp_library_version,
+            target.short_text(),
+        ])?;
+    }
+    Ok(())
+}
+
+/// Writes `db` to a new SQLite database at `path`.
+pub fn export(db: &Database, path: &Path) -> Result<()> {
+    if path.exists() {
+        remove_file(path)?;
+    }
+    let mut connection = Connection::open(path)?;
+    connection.execute_batch(SCHEMA)?;
+    let transaction = connection.transaction()?;
+    write_items(&transaction, db)?;
+    transaction.commit()?;
+    Ok(())
+}
+
+pub fn run(data: &mut ProcessorData<'_>) -> Result<()> {
+    let dir = data.workspace.path().join("export");
+    create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.sqlite", data.db.crate_name()));
+    export(data.db.data(), &path)
+        .with_context(|_| format!("failed to export database to {}", path.display()))?;
+    info!("Database exported to {}", path.display());
+    Ok(())
+}
+
+#[test]
+fn export_empty_database() {
+    let dir = tempdir::TempDir::new("ritual_sqlite_export").unwrap();
+    let path = dir.path().join("crate1.sqlite");
+    let db = Database::empty("crate1".to_string());
+    export(&db, &path).unwrap();
+    // the file is replaced on repeated export
+    export(&db, &path).unwrap();
+
+    let connection = Connection::open(&path).unwrap();
+    let name: String = connection
+        .query_row("SELECT name FROM crate", params![], |row| row.get(0))
+        .unwrap();
+    assert_eq!(name, "crate1");
+    let count: i64 = connection
+        .query_row("SELECT COUNT(*) FROM items", params![], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn export_populated_database() {
+    use crate::cpp_data::{CppItem, CppNamespace, CppPath};
+    use crate::cpp_directives::CppDirectives;
+    use crate::database::{DatabaseClient, DocItem, IndexedDatabase};
+    use ritual_common::ReadOnly;
+    use std::path::PathBuf;
+
+    let mut db = DatabaseClient::new(
+        IndexedDatabase::new(
+            Database::empty("crate1".to_string()),
+            PathBuf::from("crate1.json"),
+        ),
+        ReadOnly::new(Vec::new()),
+    );
+    let ns_id = db
+        .add_cpp_item_without_hook(
+            None,
+            CppItem::Namespace(CppNamespace {
+                path: CppPath::from_good_str("ns1::ns2"),
+            }),
+        )
+        .unwrap()
+        .unwrap();
+    db.add_doc_item(
+        ns_id.clone(),
+        DocItem {
+            anchor: Some("ns2".to_string()),
+            html: "<p>Namespace</p>".to_string(),
+            mismatched_declaration: None,
+            url: Some("https://example.com/ns2.html".to_string()),
+            cross_references: Vec::new(),
+        },
+    )
+    .unwrap();
+    db.add_directives_item(
+        ns_id.clone(),
+        CppDirectives {
+            rename: Some("renamed".to_string()),
+            movable: true,
+            ..CppDirectives::default()
+        },
+    )
+    .unwrap();
+
+    let dir = tempdir::TempDir::new("ritual_sqlite_export").unwrap();
+    let path = dir.path().join("crate1.sqlite");
+    export(db.data(), &path).unwrap();
+
+    let connection = Connection::open(&path).unwrap();
+    let count: i64 = connection
+        .query_row("SELECT COUNT(*) FROM items", params![], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 3);
+    let (kind, path, parent_path): (String, String, String) = connection
+        .query_row(
+            "SELECT kind, path, parent_path FROM cpp_items WHERE id = ?",
+            params![ns_id.to_string()],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!(kind, "Namespace");
+    assert_eq!(path, "ns1::ns2");
+    assert_eq!(parent_path, "ns1");
+    let (anchor, html): (String, String) = connection
+        .query_row(
+            "SELECT anchor, html FROM doc_items WHERE source_id = ?",
+            params![ns_id.to_string()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(anchor, "ns2");
+    assert_eq!(html, "<p>Namespace</p>");
+    let (skip, rename, movable): (bool, String, bool) = connection
+        .query_row(
+            "SELECT skip, rename, movable FROM directives WHERE source_id = ?",
+            params![ns_id.to_string()],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert!(!skip);
+    assert_eq!(rename, "renamed");
+    assert!(movable);
+}
+
+#[test]
+fn enum_variant_names() {
+    assert_eq!(
+        variant_name(&ItemKind::CppChecksItem).unwrap(),
+        "cpp_checks_item"
+    );
+    assert!(variant_name(&Some(1)).is_err());
+}
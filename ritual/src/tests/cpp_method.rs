@@ -40,6 +40,11 @@ pub fn empty_regular_method() -> CppFunction {
         return_type: CppType::Void,
         arguments: vec![],
         allows_variadic_arguments: false,
+        is_noexcept: false,
+        is_deleted: false,
+        deprecation: None,
+        has_c_linkage: false,
+        is_hidden_friend: false,
         operator: None,
         declaration_code: None,
         cast: None,
@@ -61,7 +66,7 @@ fn argument_types_equal2() {
     method1.arguments.push(CppFunctionArgument {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "arg1".to_string(),
-        has_default_value: false,
+        default_value: None,
     });
     assert!(!method1.argument_types_equal(&method2));
     assert!(!method2.argument_types_equal(&method1));
@@ -74,12 +79,12 @@ fn argument_types_equal3() {
     method1.arguments.push(CppFunctionArgument {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "arg1".to_string(),
-        has_default_value: false,
+        default_value: None,
     });
     method2.arguments.push(CppFunctionArgument {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "x".to_string(),
-        has_default_value: false,
+        default_value: None,
     });
     assert!(method1.argument_types_equal(&method2));
     assert!(method2.argument_types_equal(&method1));
@@ -92,12 +97,15 @@ fn argument_types_equal4() {
     method1.arguments.push(CppFunctionArgument {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "arg1".to_string(),
-        has_default_value: false,
+        default_value: None,
     });
     method2.arguments.push(CppFunctionArgument {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "arg1".to_string(),
-        has_default_value: true,
+        default_value: Some(CppArgumentDefaultValue {
+            text: "1".to_string(),
+            value: Some(CppConstantValue::Integer(1)),
+        }),
     });
     assert!(method1.argument_types_equal(&method2));
     assert!(method2.argument_types_equal(&method1));
@@ -110,14 +118,14 @@ fn argument_types_equal5() {
     method1.arguments.push(CppFunctionArgument {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "arg1".to_string(),
-        has_default_value: false,
+        default_value: None,
     });
     method2.arguments.push(CppFunctionArgument {
         argument_type: CppType::Enum {
             path: CppPath::from_good_str("Enum1"),
         },
         name: "arg1".to_string(),
-        has_default_value: false,
+        default_value: None,
     });
     assert!(!method1.argument_types_equal(&method2));
     assert!(!method2.argument_types_equal(&method1));
@@ -130,7 +138,7 @@ fn argument_types_equal6() {
     method1.arguments.push(CppFunctionArgument {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "arg1".to_string(),
-        has_default_value: false,
+        default_value: None,
     });
     method2.arguments.push(CppFunctionArgument {
         argument_type: CppType::new_pointer(
@@ -138,7 +146,7 @@ fn argument_types_equal6() {
             CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         ),
         name: "arg1".to_string(),
-        has_default_value: false,
+        default_value: None,
     });
     assert!(!method1.argument_types_equal(&method2));
     assert!(!method2.argument_types_equal(&method1));
@@ -150,7 +158,7 @@ fn argument_types_equal7() {
     let int = CppFunctionArgument {
         argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
         name: "arg1".to_string(),
-        has_default_value: false,
+        default_value: None,
     };
     let mut method2 = empty_regular_method();
     method1.arguments.push(int.clone());
@@ -205,7 +213,7 @@ fn c_signature_simple_func() {
             path: CppPath::from_good_str("Enum1"),
         },
         name: "arg1".to_string(),
-        has_default_value: false,
+        default_value: None,
     });
     let r = to_ffi(&method1, None);
     assert!(r.arguments.len() == 1);
@@ -235,7 +243,7 @@ fn c_signature_method_with_this() {
     method1.arguments.push(CppFunctionArgument {
         argument_type: CppType::Class(CppPath::from_good_str("MyClass2")),
         name: "my_arg".to_string(),
-        has_default_value: false,
+        default_value: None,
     });
 
     assert!(!method1.is_constructor());
@@ -287,7 +295,7 @@ fn c_signature_static_method() {
             path: CppPath::from_good_str("Enum1"),
         },
         name: "arg1".to_string(),
-        has_default_value: false,
+        default_value: None,
     });
     let r = to_ffi(&method1, None);
     assert!(r.arguments.len() == 1);
@@ -321,7 +329,10 @@ fn c_signature_constructor() {
             },
         ),
         name: "arg1".to_string(),
-        has_default_value: true,
+        default_value: Some(CppArgumentDefaultValue {
+            text: "Enum1::Value1".to_string(),
+            value: None,
+        }),
     });
 
     assert!(method1.is_constructor());
@@ -462,7 +473,7 @@ fn c_signature_method_returning_class() {
     method1.arguments.push(CppFunctionArgument {
         argument_type: CppType::Class(CppPath::from_good_str("MyClass2")),
         name: "my_arg".to_string(),
-        has_default_value: false,
+        default_value: None,
     });
     let r_stack = to_ffi(&method1, Some(CppPath::from_good_str("MyClass3")));
     assert!(r_stack.arguments.len() == 3);
@@ -612,15 +623,23 @@ fn short_text1() {
             CppFunctionArgument {
                 argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
                 name: "arg1".to_string(),
-                has_default_value: false,
+                default_value: None,
             },
             CppFunctionArgument {
                 argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Double),
                 name: "arg2".to_string(),
-                has_default_value: true,
+                default_value: Some(CppArgumentDefaultValue {
+                    text: "0.5".to_string(),
+                    value: Some(CppConstantValue::Float(0.5)),
+                }),
             },
         ],
         allows_variadic_arguments: false,
+        is_noexcept: false,
+        is_deleted: false,
+        deprecation: None,
+        has_c_linkage: false,
+        is_hidden_friend: false,
         cast: None,
         declaration_code: None,
     };
@@ -1,5 +1,6 @@
 use crate::config::{Config, CrateProperties};
 use crate::cpp_data::*;
+use crate::cpp_directives::CppDirectives;
 use crate::cpp_function::*;
 use crate::cpp_operator::CppOperator;
 use crate::cpp_type::*;
@@ -17,9 +18,20 @@ struct ParserCppData {
     methods: Vec<CppFunction>,
     enum_values: Vec<CppEnumValue>,
     namespaces: Vec<CppPath>,
+    global_variables: Vec<CppGlobalVariable>,
+    type_aliases: Vec<CppTypeAlias>,
+    directives: Vec<(CppPath, CppDirectives)>,
+    docs: Vec<(CppPath, String)>,
 }
 
 fn run_parser(code: &'static str) -> ParserCppData {
+    run_parser_with_config(code, |_| {})
+}
+
+fn run_parser_with_config(
+    code: &'static str,
+    configure: impl FnOnce(&mut Config),
+) -> ParserCppData {
     let dir = tempdir::TempDir::new("test_cpp_parser_run").unwrap();
 
     let mut workspace = Workspace::new(dir.path().into()).unwrap();
@@ -40,6 +52,7 @@ fn run_parser(code: &'static str) -> ParserCppData {
     config.add_include_directive(include_name);
     config.set_cpp_build_paths(paths);
     config.add_target_include_path(include_file_path);
+    configure(&mut config);
 
     processor::process(&mut workspace, &config, &["cpp_parser".into()], None).unwrap();
 
@@ -78,6 +91,30 @@ fn run_parser(code: &'static str) -> ParserCppData {
             .filter_map(|item| item.item.as_namespace_ref())
             .map(|ns| ns.path.clone())
             .collect(),
+        global_variables: database
+            .cpp_items()
+            .filter_map(|item| item.item.as_global_variable_ref())
+            .cloned()
+            .collect(),
+        type_aliases: database
+            .cpp_items()
+            .filter_map(|item| item.item.as_type_alias_ref())
+            .cloned()
+            .collect(),
+        directives: database
+            .cpp_items()
+            .filter_map(|item| {
+                let directives = database.find_directives_for(&item.id).unwrap()?;
+                Some((item.item.path()?.clone(), directives.clone()))
+            })
+            .collect(),
+        docs: database
+            .cpp_items()
+            .filter_map(|item| {
+                let doc = database.find_doc_for(&item.id).unwrap()?;
+                Some((item.item.path()?.clone(), doc.item.html.clone()))
+            })
+            .collect(),
     }
 }
 
@@ -96,9 +133,14 @@ fn simple_func() {
             arguments: vec![CppFunctionArgument {
                 name: "x".to_string(),
                 argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
-                has_default_value: false,
+                default_value: None,
             }],
             allows_variadic_arguments: false,
+            is_noexcept: false,
+            is_deleted: false,
+            deprecation: None,
+            has_c_linkage: false,
+            is_hidden_friend: false,
             cast: None,
             declaration_code: Some("int func1 ( int x )".to_string()),
         }
@@ -126,15 +168,53 @@ fn simple_func_with_default_value() {
             arguments: vec![CppFunctionArgument {
                 name: "x".to_string(),
                 argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
-                has_default_value: true,
+                default_value: Some(CppArgumentDefaultValue {
+                    text: "42".to_string(),
+                    value: Some(CppConstantValue::Integer(42)),
+                }),
             }],
             allows_variadic_arguments: false,
+            is_noexcept: false,
+            is_deleted: false,
+            deprecation: None,
+            has_c_linkage: false,
+            is_hidden_friend: false,
             cast: None,
             declaration_code: Some("bool func1 ( int x = 42 )".to_string()),
         }
     );
 }
 
+#[test]
+fn func_with_complex_default_values() {
+    let data = run_parser(
+        "
+        template<typename K, typename V>
+        class QMap {
+        public:
+            QMap();
+        };
+        int add(int a, double b);
+        void func1(QMap<int, int> x = QMap<int, int>(), int y = add(1, 2.0), int z = -1);
+        ",
+    );
+    let function = data
+        .methods
+        .iter()
+        .find(|method| method.path == CppPath::from_good_str("func1"))
+        .unwrap();
+    let texts = function
+        .arguments
+        .iter()
+        .map(|argument| argument.default_value.as_ref().unwrap().text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(texts, vec!["QMap<int, int>()", "add(1, 2.0)", "-1"]);
+    assert_eq!(
+        function.arguments[2].default_value.as_ref().unwrap().value,
+        Some(CppConstantValue::Integer(-1))
+    );
+}
+
 #[test]
 fn functions_with_class_arg() {
     let data = run_parser(
@@ -191,9 +271,14 @@ fn functions_with_class_arg() {
             arguments: vec![CppFunctionArgument {
                 name: "x".to_string(),
                 argument_type: CppType::Class(CppPath::from_good_str("Magic")),
-                has_default_value: false,
+                default_value: None,
             }],
             allows_variadic_arguments: false,
+            is_noexcept: false,
+            is_deleted: false,
+            deprecation: None,
+            has_c_linkage: false,
+            is_hidden_friend: false,
             cast: None,
             declaration_code: Some("bool func1 ( Magic x )".to_string()),
         }
@@ -211,9 +296,14 @@ fn functions_with_class_arg() {
                     false,
                     CppType::Class(CppPath::from_good_str("Magic"))
                 ),
-                has_default_value: false,
+                default_value: None,
             }],
             allows_variadic_arguments: false,
+            is_noexcept: false,
+            is_deleted: false,
+            deprecation: None,
+            has_c_linkage: false,
+            is_hidden_friend: false,
             cast: None,
             declaration_code: Some("bool func1 ( Magic * x )".to_string()),
         }
@@ -231,9 +321,14 @@ fn functions_with_class_arg() {
                     true,
                     CppType::Class(CppPath::from_good_str("Magic"))
                 ),
-                has_default_value: false,
+                default_value: None,
             }],
             allows_variadic_arguments: false,
+            is_noexcept: false,
+            is_deleted: false,
+            deprecation: None,
+            has_c_linkage: false,
+            is_hidden_friend: false,
             cast: None,
             declaration_code: Some("bool func2 ( const Magic & )".to_string()),
         }
@@ -284,9 +379,14 @@ fn variadic_func() {
                     true,
                     CppType::BuiltInNumeric(CppBuiltInNumericType::Char)
                 ),
-                has_default_value: false,
+                default_value: None,
             }],
             allows_variadic_arguments: true,
+            is_noexcept: false,
+            is_deleted: false,
+            deprecation: None,
+            has_c_linkage: false,
+            is_hidden_friend: false,
             cast: None,
             declaration_code: Some("int my_printf ( const char * format , ... )".to_string()),
         }
@@ -331,9 +431,14 @@ fn free_template_func() {
                     index: 0,
                     name: "T".into(),
                 }),
-                has_default_value: false,
+                default_value: None,
             }],
             allows_variadic_arguments: false,
+            is_noexcept: false,
+            is_deleted: false,
+            deprecation: None,
+            has_c_linkage: false,
+            is_hidden_friend: false,
             cast: None,
             declaration_code: Some("template < typename T > T abs ( T value )".to_string()),
         }
@@ -366,15 +471,20 @@ fn free_func_operator_sub() {
                     CppFunctionArgument {
                         name: "a".to_string(),
                         argument_type: CppType::Class(CppPath::from_good_str("C1")),
-                        has_default_value: false,
+                        default_value: None,
                     },
                     CppFunctionArgument {
                         name: "b".to_string(),
                         argument_type: CppType::Class(CppPath::from_good_str("C1")),
-                        has_default_value: false,
+                        default_value: None,
                     },
                 ],
                 allows_variadic_arguments: false,
+                is_noexcept: false,
+                is_deleted: false,
+                deprecation: None,
+                has_c_linkage: false,
+                is_hidden_friend: false,
                 cast: None,
                 declaration_code: Some("C1 operator - ( C1 a , C1 b )".to_string()),
             }
@@ -421,9 +531,14 @@ fn simple_class_method() {
             arguments: vec![CppFunctionArgument {
                 name: "x".to_string(),
                 argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
-                has_default_value: false,
+                default_value: None,
             }],
             allows_variadic_arguments: false,
+            is_noexcept: false,
+            is_deleted: false,
+            deprecation: None,
+            has_c_linkage: false,
+            is_hidden_friend: false,
             cast: None,
             declaration_code: Some("int func1 ( int x )".to_string()),
         }
@@ -615,9 +730,14 @@ fn template_class_method() {
             arguments: vec![CppFunctionArgument {
                 name: "index".to_string(),
                 argument_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
-                has_default_value: false,
+                default_value: None,
             }],
             allows_variadic_arguments: false,
+            is_noexcept: false,
+            is_deleted: false,
+            deprecation: None,
+            has_c_linkage: false,
+            is_hidden_friend: false,
             cast: None,
             declaration_code: Some("T get ( int index )".to_string()),
         }
@@ -757,7 +877,13 @@ fn simple_enum() {
     );
     assert_eq!(data.types.len(), 1);
     assert_eq!(data.types[0].path, CppPath::from_good_str("Enum1"));
-    assert_eq!(data.types[0].kind, CppTypeDeclarationKind::Enum);
+    assert_eq!(
+        data.types[0].kind,
+        CppTypeDeclarationKind::Enum {
+            underlying_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+            is_scoped: false,
+        }
+    );
     assert_eq!(
         data.enum_values,
         vec![
@@ -788,7 +914,13 @@ fn simple_enum2() {
     );
     assert_eq!(data.types.len(), 1);
     assert_eq!(data.types[0].path, CppPath::from_good_str("ns1::Enum1"));
-    assert_eq!(data.types[0].kind, CppTypeDeclarationKind::Enum);
+    assert_eq!(
+        data.types[0].kind,
+        CppTypeDeclarationKind::Enum {
+            underlying_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+            is_scoped: false,
+        }
+    );
     assert_eq!(
         data.enum_values,
         vec![
@@ -1159,7 +1291,7 @@ fn function_pointer() {
             allows_variadic_arguments: false,
         })
     );
-    assert_eq!(arg0.has_default_value, false);
+    assert_eq!(arg0.default_value, None);
 
     let arg1 = &function.arguments[1];
     assert_eq!(arg1.name, "data");
@@ -1167,7 +1299,7 @@ fn function_pointer() {
         arg1.argument_type,
         CppType::new_pointer(false, CppType::Void)
     );
-    assert_eq!(arg1.has_default_value, false);
+    assert_eq!(arg1.default_value, None);
 }
 
 #[test]
@@ -1220,3 +1352,580 @@ fn elaborated_enum_type() {
         }
     );
 }
+
+#[test]
+fn global_variables() {
+    let data = run_parser(
+        "
+    namespace ns {
+        extern const int kDefaultPort;
+        constexpr double PI = 3.5;
+        extern int counter;
+        constexpr unsigned long long kAllBits = ~0ull;
+    }
+    struct S { static int x; };
+    ",
+    );
+    assert_eq!(data.fields.len(), 1);
+    assert_eq!(data.global_variables.len(), 4);
+    assert_eq!(
+        data.global_variables[0],
+        CppGlobalVariable {
+            path: CppPath::from_good_str("ns::kDefaultPort"),
+            variable_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+            is_const: true,
+            value: None,
+        }
+    );
+    assert_eq!(
+        data.global_variables[1],
+        CppGlobalVariable {
+            path: CppPath::from_good_str("ns::PI"),
+            variable_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Double),
+            is_const: true,
+            value: Some(CppConstantValue::Float(3.5)),
+        }
+    );
+    assert_eq!(
+        data.global_variables[2],
+        CppGlobalVariable {
+            path: CppPath::from_good_str("ns::counter"),
+            variable_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+            is_const: false,
+            value: None,
+        }
+    );
+    assert_eq!(
+        data.global_variables[3],
+        CppGlobalVariable {
+            path: CppPath::from_good_str("ns::kAllBits"),
+            variable_type: CppType::BuiltInNumeric(CppBuiltInNumericType::ULongLong),
+            is_const: true,
+            value: Some(CppConstantValue::Integer(-1)),
+        }
+    );
+}
+
+#[test]
+fn type_aliases() {
+    let data = run_parser(
+        "
+    class Q1 {};
+    typedef Q1 Q1Alias;
+    typedef struct Q2 {} Q2;
+    namespace ns {
+        using Handle = unsigned long long;
+        template<typename T> class Ptr { public: typedef T* pointer; };
+    }
+    ",
+    );
+    assert_eq!(data.type_aliases.len(), 2);
+    assert_eq!(
+        data.type_aliases[0],
+        CppTypeAlias {
+            path: CppPath::from_good_str("Q1Alias"),
+            target_type: CppType::Class(CppPath::from_good_str("Q1")),
+        }
+    );
+    assert_eq!(
+        data.type_aliases[1],
+        CppTypeAlias {
+            path: CppPath::from_good_str("ns::Handle"),
+            target_type: CppType::BuiltInNumeric(CppBuiltInNumericType::ULongLong),
+        }
+    );
+}
+
+#[test]
+fn unions() {
+    let data = run_parser(
+        "
+    union Value {
+        int i;
+        double d;
+    };
+    Value make_value();
+    ",
+    );
+    assert_eq!(data.types.len(), 1);
+    assert_eq!(data.types[0].path, CppPath::from_good_str("Value"));
+    assert_eq!(
+        data.types[0].kind,
+        CppTypeDeclarationKind::Union {
+            is_trivially_copyable: true
+        }
+    );
+    assert!(data.types[0].kind.is_class());
+
+    assert_eq!(data.fields.len(), 2);
+    assert_eq!(data.fields[0].path, CppPath::from_good_str("Value::i"));
+    assert_eq!(
+        data.fields[0].field_type,
+        CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+    );
+    assert_eq!(data.fields[1].path, CppPath::from_good_str("Value::d"));
+    assert_eq!(
+        data.fields[1].field_type,
+        CppType::BuiltInNumeric(CppBuiltInNumericType::Double),
+    );
+
+    assert_eq!(data.methods.len(), 1);
+    assert_eq!(
+        data.methods[0].return_type,
+        CppType::Class(CppPath::from_good_str("Value"))
+    );
+}
+
+#[test]
+fn non_trivial_unions() {
+    let data = run_parser(
+        "
+    struct Text {
+        Text(const Text& other);
+    };
+    union Value {
+        Value();
+        int i;
+        Text t;
+    };
+    ",
+    );
+    assert_eq!(data.types.len(), 2);
+    assert_eq!(data.types[1].path, CppPath::from_good_str("Value"));
+    assert_eq!(
+        data.types[1].kind,
+        CppTypeDeclarationKind::Union {
+            is_trivially_copyable: false
+        }
+    );
+}
+
+#[test]
+fn noexcept_functions() {
+    let data = run_parser(
+        "
+    void f1();
+    void f2() noexcept;
+    void f3() throw();
+    void f4() noexcept(false);
+    void f5() noexcept(true);
+    void f6() noexcept(sizeof(int) > 2);
+    ",
+    );
+    assert_eq!(data.methods.len(), 6);
+    assert_eq!(data.methods[0].path, CppPath::from_good_str("f1"));
+    assert!(!data.methods[0].is_noexcept);
+    assert_eq!(data.methods[1].path, CppPath::from_good_str("f2"));
+    assert!(data.methods[1].is_noexcept);
+    assert_eq!(data.methods[2].path, CppPath::from_good_str("f3"));
+    assert!(data.methods[2].is_noexcept);
+    assert_eq!(data.methods[3].path, CppPath::from_good_str("f4"));
+    assert!(!data.methods[3].is_noexcept);
+    // conditions are not evaluated
+    assert_eq!(data.methods[4].path, CppPath::from_good_str("f5"));
+    assert!(!data.methods[4].is_noexcept);
+    assert_eq!(data.methods[5].path, CppPath::from_good_str("f6"));
+    assert!(!data.methods[5].is_noexcept);
+}
+
+#[test]
+fn deleted_and_deprecated_functions() {
+    let data = run_parser(
+        "
+    class A {
+    public:
+        A(const A& other) = delete;
+        [[deprecated(\"use g() instead\")]] void f();
+        [[deprecated]] void f2();
+        void g();
+    };
+    ",
+    );
+    assert_eq!(data.methods.len(), 4);
+    assert_eq!(data.methods[0].path, CppPath::from_good_str("A::A"));
+    assert!(data.methods[0].is_deleted);
+    assert_eq!(data.methods[0].deprecation, None);
+
+    assert_eq!(data.methods[1].path, CppPath::from_good_str("A::f"));
+    assert!(!data.methods[1].is_deleted);
+    assert_eq!(
+        data.methods[1].deprecation,
+        Some(CppDeprecation {
+            message: Some("use g() instead".to_string()),
+        })
+    );
+
+    assert_eq!(data.methods[2].path, CppPath::from_good_str("A::f2"));
+    assert_eq!(
+        data.methods[2].deprecation,
+        Some(CppDeprecation { message: None })
+    );
+
+    assert_eq!(data.methods[3].path, CppPath::from_good_str("A::g"));
+    assert!(!data.methods[3].is_deleted);
+    assert_eq!(data.methods[3].deprecation, None);
+}
+
+#[test]
+fn default_argument_values() {
+    let data = run_parser(
+        "
+    enum E { E1, E2 };
+    class A {
+    public:
+        A(int x = 5, double y = 0.5, E e = E2, const char* s = \"a, b\");
+        A(int x, const A& other = A(1, 2.0));
+    };
+    ",
+    );
+    assert_eq!(data.methods.len(), 2);
+    let args = &data.methods[0].arguments;
+    assert_eq!(args.len(), 4);
+    assert_eq!(
+        args[0].default_value,
+        Some(CppArgumentDefaultValue {
+            text: "5".to_string(),
+            value: Some(CppConstantValue::Integer(5)),
+        })
+    );
+    assert_eq!(
+        args[1].default_value,
+        Some(CppArgumentDefaultValue {
+            text: "0.5".to_string(),
+            value: Some(CppConstantValue::Float(0.5)),
+        })
+    );
+    let default_e = args[2].default_value.as_ref().unwrap();
+    assert_eq!(default_e.text, "E2");
+    assert_eq!(default_e.value, Some(CppConstantValue::Integer(1)));
+    assert_eq!(args[3].default_value.as_ref().unwrap().text, "\"a, b\"");
+
+    let args = &data.methods[1].arguments;
+    assert_eq!(args.len(), 2);
+    assert!(!args[0].has_default_value());
+    assert_eq!(args[1].default_value.as_ref().unwrap().text, "A(1,2.0)");
+    assert!(data.methods[1].short_text().contains("other = A(1,2.0)"));
+}
+
+#[test]
+fn non_type_template_parameters() {
+    let data = run_parser(
+        "
+    template<typename T, int N>
+    class Array {
+    public:
+        T& at(int i);
+    };
+    Array<int, 4> make_array();
+
+    enum Mode { M1, M2 };
+    template<Mode M>
+    class Flagged {};
+    Flagged<M2> make_flagged();
+    ",
+    );
+    let mut array_path = CppPath::from_good_str("Array");
+    array_path.last_mut().template_arguments = Some(vec![
+        CppType::TemplateParameter(CppTemplateParameter {
+            nested_level: 0,
+            index: 0,
+            name: "T".into(),
+        }),
+        CppType::TemplateParameter(CppTemplateParameter {
+            nested_level: 0,
+            index: 1,
+            name: "N".into(),
+        }),
+    ]);
+    assert!(data.types.iter().any(|t| t.path == array_path));
+
+    let at = data
+        .methods
+        .iter()
+        .find(|m| m.path.last().name == "at")
+        .unwrap();
+    assert_eq!(at.path, array_path.join(CppPathItem::from_good_str("at")));
+
+    let make_array = data
+        .methods
+        .iter()
+        .find(|m| m.path == CppPath::from_good_str("make_array"))
+        .unwrap();
+    let mut array_instance = CppPath::from_good_str("Array");
+    array_instance.last_mut().template_arguments = Some(vec![
+        CppType::BuiltInNumeric(CppBuiltInNumericType::Int),
+        CppType::TemplateArgumentValue(CppTemplateArgumentValue {
+            value_type: Box::new(CppType::BuiltInNumeric(CppBuiltInNumericType::Int)),
+            value: 4,
+        }),
+    ]);
+    assert_eq!(make_array.return_type, CppType::Class(array_instance));
+
+    let make_flagged = data
+        .methods
+        .iter()
+        .find(|m| m.path == CppPath::from_good_str("make_flagged"))
+        .unwrap();
+    let mut flagged_instance = CppPath::from_good_str("Flagged");
+    flagged_instance.last_mut().template_arguments = Some(vec![CppType::TemplateArgumentValue(
+        CppTemplateArgumentValue {
+            value_type: Box::new(CppType::Enum {
+                path: CppPath::from_good_str("Mode"),
+            }),
+            value: 1,
+        },
+    )]);
+    assert_eq!(make_flagged.return_type, CppType::Class(flagged_instance));
+}
+
+#[test]
+fn enum_underlying_types() {
+    let data = run_parser(
+        "
+    enum class Flags : unsigned long long {
+        First = 1,
+        Last = 0x8000000000000000
+    };
+    enum Small : short { Negative = -1 };
+    enum Plain { Zero };
+    enum Unsigned { High = 0x80000000 };
+    enum Large { Huge = 0x100000000 };
+    ",
+    );
+    assert_eq!(data.types.len(), 5);
+    assert_eq!(data.types[0].path, CppPath::from_good_str("Flags"));
+    assert_eq!(
+        data.types[0].kind,
+        CppTypeDeclarationKind::Enum {
+            underlying_type: CppType::BuiltInNumeric(CppBuiltInNumericType::ULongLong),
+            is_scoped: true,
+        }
+    );
+    assert_eq!(data.types[1].path, CppPath::from_good_str("Small"));
+    assert_eq!(
+        data.types[1].kind,
+        CppTypeDeclarationKind::Enum {
+            underlying_type: CppType::BuiltInNumeric(CppBuiltInNumericType::Short),
+            is_scoped: false,
+        }
+    );
+    // types of unfixed enums are chosen by the compiler
+    let underlying_types = data.types[2..]
+        .iter()
+        .map(|declaration| match &declaration.kind {
+            CppTypeDeclarationKind::Enum {
+                underlying_type, ..
+            } => underlying_type.clone(),
+            kind => panic!("unexpected kind: {:?}", kind),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        underlying_types[0],
+        CppType::BuiltInNumeric(CppBuiltInNumericType::Int)
+    );
+    if cfg!(all(unix, target_pointer_width = "64")) {
+        assert_eq!(
+            underlying_types[1..].to_vec(),
+            vec![
+                CppType::BuiltInNumeric(CppBuiltInNumericType::UInt),
+                CppType::BuiltInNumeric(CppBuiltInNumericType::ULong),
+            ]
+        );
+    }
+    assert_eq!(
+        data.enum_values[..3].to_vec(),
+        vec![
+            CppEnumValue {
+                value: 1,
+                path: CppPath::from_good_str("Flags::First"),
+            },
+            CppEnumValue {
+                value: i64::min_value(),
+                path: CppPath::from_good_str("Flags::Last"),
+            },
+            CppEnumValue {
+                value: -1,
+                path: CppPath::from_good_str("Small::Negative"),
+            },
+        ]
+    );
+}
+
+#[test]
+fn plain_c_mode() {
+    let data = run_parser_with_config(
+        "struct Point { int x; int y; };
+         struct Handle;
+         struct Flags { unsigned int a : 1; };
+         int area(struct Point p);
+         static int helper(int a) { return a; }",
+        |config| config.set_plain_c_mode(true),
+    );
+    let type_names = data
+        .types
+        .iter()
+        .map(|t| t.path.to_cpp_pseudo_code())
+        .collect::<Vec<_>>();
+    assert_eq!(type_names, vec!["Point", "Handle", "Flags"]);
+
+    // bit fields can't be represented in Rust structs
+    let field_names = data
+        .fields
+        .iter()
+        .map(|f| f.path.to_cpp_pseudo_code())
+        .collect::<Vec<_>>();
+    assert_eq!(field_names, vec!["Point::x", "Point::y"]);
+
+    assert_eq!(data.methods.len(), 2);
+    assert_eq!(data.methods[0].path, CppPath::from_good_str("area"));
+    assert_eq!(
+        data.methods[0].arguments[0].argument_type,
+        CppType::Class(CppPath::from_good_str("Point"))
+    );
+    assert!(data.methods[0].has_c_linkage);
+    assert_eq!(data.methods[1].path, CppPath::from_good_str("helper"));
+    assert!(!data.methods[1].has_c_linkage);
+}
+
+#[test]
+fn annotate_directives() {
+    let data = run_parser(
+        "class __attribute__((annotate(\"ritual:movable\"))) Point {
+         public:
+           int x() const;
+           Point* next() const __attribute__((annotate(\"ritual:nullable\")));
+           void reset() __attribute__((annotate(\"ritual:rename=clear\")));
+           void internal() __attribute__((annotate(\"ritual:skip\")));
+         };
+         class __attribute__((annotate(\"ritual: skip\"))) Hidden {
+         public:
+           int y();
+         };
+         void use_hidden(Hidden* h);
+         void unknown() __attribute__((annotate(\"ritual:unknown\")));",
+    );
+    let type_names = data
+        .types
+        .iter()
+        .map(|t| t.path.to_cpp_pseudo_code())
+        .collect::<Vec<_>>();
+    assert_eq!(type_names, vec!["Point"]);
+
+    // skipped items and functions using skipped types are not added
+    let method_names = data
+        .methods
+        .iter()
+        .map(|m| m.path.to_cpp_pseudo_code())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        method_names,
+        vec!["Point::x", "Point::next", "Point::reset", "unknown"]
+    );
+
+    assert_eq!(
+        data.directives,
+        vec![
+            (
+                CppPath::from_good_str("Point"),
+                CppDirectives {
+                    movable: true,
+                    ..CppDirectives::default()
+                }
+            ),
+            (
+                CppPath::from_good_str("Point::next"),
+                CppDirectives {
+                    nullable: true,
+                    ..CppDirectives::default()
+                }
+            ),
+            (
+                CppPath::from_good_str("Point::reset"),
+                CppDirectives {
+                    rename: Some("clear".to_string()),
+                    ..CppDirectives::default()
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn hidden_friend_functions() {
+    let data = run_parser(
+        "
+        namespace ns {
+        class C1 {
+        public:
+            friend bool operator==(const C1& a, const C1& b) { return true; }
+            friend int distance(const C1& a, const C1& b);
+            friend class C2;
+        };
+        void visible(C1 a);
+        class C3 {
+            friend void visible(C1 a);
+        };
+        template<class T>
+        class C4 {
+            friend bool operator==(const C4& a, const C4& b) { return true; }
+        };
+        }
+        ",
+    );
+    assert_eq!(data.methods.len(), 3);
+
+    assert_eq!(
+        data.methods[0].path,
+        CppPath::from_good_str("ns::operator==")
+    );
+    assert!(data.methods[0].member.is_none());
+    assert_eq!(data.methods[0].operator, Some(CppOperator::EqualTo));
+    assert!(data.methods[0].is_hidden_friend);
+
+    assert_eq!(data.methods[1].path, CppPath::from_good_str("ns::distance"));
+    assert!(data.methods[1].member.is_none());
+    assert!(data.methods[1].is_hidden_friend);
+
+    // redeclaration of a namespace function is not hidden
+    assert_eq!(data.methods[2].path, CppPath::from_good_str("ns::visible"));
+    assert!(!data.methods[2].is_hidden_friend);
+}
+
+#[test]
+fn doc_comments() {
+    let code = "
+    /// Buffer of bytes.
+    class Buffer {
+    public:
+        /**
+         * Resizes the buffer.
+         */
+        void resize(int size);
+        void clear();
+    };
+    /// @brief Creates a buffer.
+    Buffer make_buffer();
+    ";
+    let data = run_parser_with_config(code, |config| config.set_parse_doc_comments(true));
+    assert_eq!(
+        data.docs,
+        vec![
+            (
+                CppPath::from_good_str("Buffer"),
+                "<p>Buffer of bytes.</p>".to_string()
+            ),
+            (
+                CppPath::from_good_str("Buffer::resize"),
+                "<p>Resizes the buffer.</p>".to_string()
+            ),
+            (
+                CppPath::from_good_str("make_buffer"),
+                "<p>Creates a buffer.</p>".to_string()
+            ),
+        ]
+    );
+
+    // comments are ignored by default
+    let data = run_parser(code);
+    assert!(data.docs.is_empty());
+}
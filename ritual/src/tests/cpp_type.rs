@@ -3,7 +3,8 @@ use crate::cpp_ffi_data::CppToFfiTypeConversion;
 use crate::cpp_ffi_generator::ffi_type;
 use crate::cpp_type::{
     CppBuiltInNumericType, CppFunctionPointerType, CppSpecificNumericType,
-    CppSpecificNumericTypeKind, CppTemplateParameter, CppType, CppTypeRole,
+    CppSpecificNumericTypeKind, CppTemplateArgumentValue, CppTemplateParameter, CppType,
+    CppTypeRole,
 };
 
 fn assert_type_to_ffi_unchanged(t: &CppType) {
@@ -380,3 +381,59 @@ fn instantiate1() {
         )
     );
 }
+
+#[test]
+fn instantiate_template_argument_value() {
+    let mut path = CppPath::from_good_str("Array");
+    path.last_mut().template_arguments = Some(vec![
+        CppType::TemplateParameter(CppTemplateParameter {
+            nested_level: 0,
+            index: 0,
+            name: "T".into(),
+        }),
+        CppType::TemplateParameter(CppTemplateParameter {
+            nested_level: 0,
+            index: 1,
+            name: "N".into(),
+        }),
+    ]);
+    let type1 = CppType::Class(path);
+    assert!(type1.is_or_contains_template_parameter());
+
+    let value = CppType::TemplateArgumentValue(CppTemplateArgumentValue {
+        value_type: Box::new(CppType::BuiltInNumeric(CppBuiltInNumericType::Int)),
+        value: 4,
+    });
+    assert!(ffi_type(&value, CppTypeRole::NotReturnType).is_err());
+    assert_eq!(value.ascii_caption(), "4");
+
+    let r = type1
+        .instantiate(
+            0,
+            &[CppType::BuiltInNumeric(CppBuiltInNumericType::Int), value],
+        )
+        .unwrap();
+    assert!(!r.is_or_contains_template_parameter());
+    assert_eq!(r.to_cpp_code(None).unwrap(), "Array< int, 4 >");
+}
+
+#[test]
+fn template_argument_value_to_cpp_code() {
+    let bool_value = CppTemplateArgumentValue {
+        value_type: Box::new(CppType::BuiltInNumeric(CppBuiltInNumericType::Bool)),
+        value: 1,
+    };
+    assert_eq!(bool_value.to_cpp_code().unwrap(), "true");
+
+    let enum_value = CppTemplateArgumentValue {
+        value_type: Box::new(CppType::Enum {
+            path: CppPath::from_good_str("ns::E"),
+        }),
+        value: -2,
+    };
+    assert_eq!(enum_value.to_cpp_code().unwrap(), "static_cast<ns::E>(-2)");
+    assert_eq!(
+        CppType::TemplateArgumentValue(enum_value).ascii_caption(),
+        "minus_2"
+    );
+}
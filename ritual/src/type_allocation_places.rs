@@ -113,10 +113,16 @@ pub fn suggest_allocation_places(data: &mut ProcessorData<'_>) -> Result<()> {
             if !type1.kind.is_class() {
                 continue;
             }
-            if let Some(hook) = data.config.movable_types_hook() {
-                if hook(&type1.path)? != MovableTypesHookOutput::Unknown {
-                    continue;
-                }
+            let is_movable_by_directive = data
+                .db
+                .find_directives_for_cpp_path(&type1.path)
+                .map_or(false, |directives| directives.movable);
+            if is_movable_by_directive {
+                continue;
+            }
+            if data.config.movable_type_from_hooks(&type1.path)? != MovableTypesHookOutput::Unknown
+            {
+                continue;
             }
             let good_path = type1.path.deinstantiate();
             data_map.insert(good_path, Default::default());
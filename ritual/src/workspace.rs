@@ -1,18 +1,22 @@
 use crate::config::{CrateDependency, CrateDependencyKind, CrateDependencySource};
-use crate::database::{DatabaseCache, DatabaseClient, CRATE_DB_FILE_NAME};
+use crate::database::{
+    DatabaseCache, DatabaseClient, CRATE_DB_FILE_NAME, LEGACY_CRATE_DB_FILE_NAME,
+};
+use crate::database_file::{self, DatabaseFormat, SCHEMA_VERSION};
 use crate::download_db::download_db;
 use log::info;
 use ritual_common::errors::{bail, Result};
 use ritual_common::file_utils::{
-    create_dir_all, load_json, os_string_into_string, read_dir, remove_file, save_json,
+    create_dir_all, load_json, os_string_into_string, read_dir, remove_file, rename_file,
     save_toml_table,
 };
 use ritual_common::utils::MapIfOk;
 use ritual_common::{toml, ReadOnly};
 use serde_derive::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct WorkspaceConfig {}
 
 /// Provides access to data stored in the user's project directory.
@@ -20,10 +24,11 @@ pub struct WorkspaceConfig {}
 /// to process. When running any operations, the data is read from and
 /// saved to the workspace files. Global workspace configuration
 /// can also be set through the `Workspace` object.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Workspace {
     path: PathBuf,
     config: WorkspaceConfig,
+    output_lock: Arc<Mutex<()>>,
 }
 
 fn config_path(path: &Path) -> PathBuf {
@@ -31,9 +36,7 @@ fn config_path(path: &Path) -> PathBuf {
 }
 
 fn database_path(workspace_path: &Path, crate_name: &str) -> PathBuf {
-    workspace_path
-        .join("db")
-        .join(format!("{}.json", crate_name))
+    workspace_path.join("db").join(format!("{}.db", crate_name))
 }
 
 impl Workspace {
@@ -52,6 +55,7 @@ impl Workspace {
             } else {
                 WorkspaceConfig::default()
             },
+            output_lock: Arc::default(),
         };
         Ok(w)
     }
@@ -68,6 +72,21 @@ impl Workspace {
         self.path.join("tmp")
     }
 
+    /// Returns the lock that must be held while writing files shared by all crates
+    /// of the workspace (the `out` directory and its `Cargo.toml`) or building crates.
+    /// Clones of the workspace share the lock.
+    pub fn output_lock(&self) -> Arc<Mutex<()>> {
+        Arc::clone(&self.output_lock)
+    }
+
+    /// Returns directory for temporary files of the crate, creating it if necessary.
+    /// Crates processed concurrently must not share temporary files.
+    pub fn crate_tmp_path(&self, crate_name: &str) -> Result<PathBuf> {
+        let path = self.tmp_path().join(crate_name);
+        create_dir_all(&path)?;
+        Ok(path)
+    }
+
     pub fn config(&self) -> &WorkspaceConfig {
         &self.config
     }
@@ -84,8 +103,8 @@ impl Workspace {
         let path = database_path(&self.path, crate_name);
         let mut cache = DatabaseCache::global().lock().unwrap();
         cache.remove_if_exists(&path);
-        if path.exists() {
-            remove_file(path)?;
+        if let Some(existing_path) = database_file::existing_path(&path) {
+            remove_file(existing_path)?;
         }
         Ok(())
     }
@@ -113,13 +132,20 @@ impl Workspace {
                     CrateDependencySource::CratesIo { version } => {
                         self.external_db_path(dependency.name(), version)?
                     }
-                    CrateDependencySource::Local { path } => path.join(CRATE_DB_FILE_NAME),
+                    CrateDependencySource::Local { path } => {
+                        let legacy_path = path.join(LEGACY_CRATE_DB_FILE_NAME);
+                        if path.join(CRATE_DB_FILE_NAME).exists() || !legacy_path.exists() {
+                            path.join(CRATE_DB_FILE_NAME)
+                        } else {
+                            legacy_path
+                        }
+                    }
                     CrateDependencySource::CurrentWorkspace => {
                         self.database_path(dependency.name())
                     }
                 };
 
-                cache.get(path, dependency.name(), true, false)
+                cache.get_shared(path, dependency.name())
             })?;
         Ok(DatabaseClient::new(
             current_database,
@@ -130,7 +156,7 @@ impl Workspace {
     fn database_backup_path(&self, crate_name: &str) -> PathBuf {
         let date = chrono::Local::now();
         self.path.join("backup").join(format!(
-            "db_{}_{}.json",
+            "db_{}_{}.db",
             crate_name,
             date.format("%Y-%m-%d_%H-%M-%S")
         ))
@@ -139,11 +165,28 @@ impl Workspace {
     pub fn save_database(&self, database: &mut DatabaseClient) -> Result<()> {
         if database.is_modified() {
             info!("Saving data");
-            let backup_path = self.database_backup_path(database.crate_name());
-            save_json(
-                database_path(&self.path, database.crate_name()),
+            let path = database_path(&self.path, database.crate_name());
+            // a database loaded from a legacy file is converted when it's saved,
+            // and the legacy file is moved to the backup
+            let legacy_path = database_file::legacy_path(&path);
+            if !path.exists() && legacy_path.exists() {
+                rename_file(&legacy_path, &path)?;
+            }
+            // the previous file is only kept if it's converted to the current
+            // format or schema version, otherwise it's replaced
+            let is_converted = path.exists()
+                && database_file::read_header(&path)?
+                    .map_or(true, |header| header.schema_version != SCHEMA_VERSION);
+            let backup_path = if is_converted {
+                Some(self.database_backup_path(database.crate_name()))
+            } else {
+                None
+            };
+            database_file::save(
+                &path,
                 database.data(),
-                Some(&backup_path),
+                DatabaseFormat::Binary,
+                backup_path.as_ref().map(|path| path.as_path()),
             )?;
             database.set_saved();
         }
@@ -174,13 +217,64 @@ impl Workspace {
         Ok(())
     }
 
-    fn external_db_path(&mut self, crate_name: &str, crate_version: &str) -> Result<PathBuf> {
+    /// Returns path to the database of a published crate, downloading it if necessary.
+    pub fn external_db_path(&mut self, crate_name: &str, crate_version: &str) -> Result<PathBuf> {
         let path = self
             .path
-            .join(format!("external_db/{}_{}.json", crate_name, crate_version));
-        if !path.exists() {
-            download_db(crate_name, crate_version, &path)?;
+            .join(format!("external_db/{}_{}.db", crate_name, crate_version));
+        if let Some(existing_path) = database_file::existing_path(&path) {
+            return Ok(existing_path);
         }
+        download_db(crate_name, crate_version, &path)?;
         Ok(path)
     }
 }
+
+#[test]
+fn convert_legacy_database_on_save() {
+    use crate::database::Database;
+
+    let dir = tempdir::TempDir::new("ritual_workspace").unwrap();
+    let mut workspace = Workspace::new(dir.path().to_path_buf()).unwrap();
+    let path = workspace.database_path("legacy_db_test");
+    let legacy_path = database_file::legacy_path(&path);
+    database_file::save(
+        &legacy_path,
+        &Database::empty("legacy_db_test".to_string()),
+        DatabaseFormat::Json,
+        None,
+    )
+    .unwrap();
+
+    // opening the workspace doesn't touch the legacy file
+    let mut workspace2 = Workspace::new(dir.path().to_path_buf()).unwrap();
+    assert!(legacy_path.exists());
+    assert!(!path.exists());
+
+    let mut db = workspace2
+        .get_database_client("legacy_db_test", &[], true, false)
+        .unwrap();
+    assert_eq!(db.crate_name(), "legacy_db_test");
+    workspace2.save_database(&mut db).unwrap();
+    assert!(legacy_path.exists());
+
+    db.set_crate_version("0.1.0".to_string());
+    workspace2.save_database(&mut db).unwrap();
+    assert!(!legacy_path.exists());
+    assert_eq!(
+        database_file::detect_format(&path).unwrap(),
+        DatabaseFormat::Binary
+    );
+    let backup_count = || read_dir(dir.path().join("backup")).unwrap().count();
+    assert_eq!(backup_count(), 1);
+
+    // databases in the current format are replaced without a backup
+    db.set_crate_version("0.2.0".to_string());
+    workspace2.save_database(&mut db).unwrap();
+    assert_eq!(backup_count(), 1);
+    drop(db);
+    workspace
+        .delete_database_if_exists("legacy_db_test")
+        .unwrap();
+    assert!(!path.exists());
+}
@@ -17,6 +17,10 @@
 // for exit()
 #include <cstdlib>
 
+// for catching exceptions in FFI wrappers
+#include <exception>
+#include <cstring>
+
 #ifdef _WIN32
     #define RITUAL_EXPORT __declspec(dllexport)
 #else
@@ -30,6 +34,26 @@
     }
 
 namespace ritual {
+    // Information about a C++ exception caught by an FFI wrapper.
+    // Must have the same layout as `cpp_core::CppExceptionInfo`.
+    struct ExceptionInfo {
+        // 0 if no exception was caught, 1 for `std::exception`,
+        // 2 for an object of an unknown type
+        int kind;
+        char message[1024];
+    };
+
+    inline void set_exception(ExceptionInfo* info, const std::exception& exception) {
+        info->kind = 1;
+        strncpy(info->message, exception.what(), sizeof(info->message) - 1);
+        info->message[sizeof(info->message) - 1] = 0;
+    }
+
+    inline void set_unknown_exception(ExceptionInfo* info) {
+        info->kind = 2;
+        info->message[0] = 0;
+    }
+
     // Calls destructor of `T` class. This template function
     // is necessary because it's not possible to use `x->~T()`
     // syntax directly if `T` contains `::`.
@@ -0,0 +1,10 @@
+#ifndef RITUAL_GLOBAL_H
+#define RITUAL_GLOBAL_H
+
+// This header replaces the C++ global header
+// when the library is parsed in plain C mode.
+
+// for fixed size integer types
+#include <stdint.h>
+
+#endif // RITUAL_GLOBAL_H
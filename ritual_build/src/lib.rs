@@ -12,7 +12,7 @@
 //! - Apply extra build configuration passed via environment variables
 //!   (`RITUAL_LIBRARY_PATH`, `RITUAL_FRAMEWORK_PATH`, `RITUAL_INCLUDE_PATH`);
 //! - Select active build configuration based on current target;
-//! - Build the C++ wrapper library;
+//! - Build the C++ wrapper library (unless all FFI functions are called directly);
 //! - Generate `ffi.rs` file with actual linking attributes;
 //! - Determine sizes of C++ types with stack allocation place;
 //! - Print `cargo` attributes required for linking the crate.
@@ -140,52 +140,60 @@ impl Config {
         let out_dir = out_dir()?;
         let c_lib_install_dir = out_dir.join("c_lib_install");
         let manifest_dir = manifest_dir()?;
-        let profile = env::var("PROFILE").with_context(|_| "PROFILE env var is missing")?;
-        info!("Building C++ wrapper library");
+        let has_wrapper_library = self.build_script_data.has_wrapper_library;
+        if has_wrapper_library {
+            let profile = env::var("PROFILE").with_context(|_| "PROFILE env var is missing")?;
+            info!("Building C++ wrapper library");
 
-        let library_type = cpp_build_config_data
-            .library_type()
-            .ok_or_else(|| err_msg("library type (shared or static) is not set"))?;
+            let library_type = cpp_build_config_data
+                .library_type()
+                .ok_or_else(|| err_msg("library type (shared or static) is not set"))?;
 
-        let cmake_config = CMakeConfigData {
-            cpp_build_config_data: &cpp_build_config_data,
-            cpp_build_paths: &self.cpp_build_paths,
-            library_type: Some(library_type),
-            cpp_library_version: self.current_cpp_library_version.clone(),
-        };
+            let cmake_config = CMakeConfigData {
+                cpp_build_config_data: &cpp_build_config_data,
+                cpp_build_paths: &self.cpp_build_paths,
+                library_type: Some(library_type),
+                cpp_library_version: self.current_cpp_library_version.clone(),
+            };
 
-        let cmake_vars = cmake_config.cmake_vars()?.into_iter().collect();
-        CppLibBuilder {
-            cmake_source_dir: manifest_dir.join("c_lib"),
-            build_dir: out_dir.join("c_lib_build"),
-            install_dir: Some(c_lib_install_dir.clone()),
-            num_jobs: env::var("NUM_JOBS").ok().and_then(|x| x.parse().ok()),
-            cmake_vars,
-            build_type: match profile.as_str() {
-                "debug" => BuildType::Debug,
-                "release" => BuildType::Release,
-                _ => bail!("unknown value of PROFILE env var: {}", profile),
-            },
-            capture_output: false,
-            skip_cmake: false,
-            skip_cmake_after_first_run: false,
+            let cmake_vars = cmake_config.cmake_vars()?.into_iter().collect();
+            CppLibBuilder {
+                cmake_source_dir: manifest_dir.join("c_lib"),
+                build_dir: out_dir.join("c_lib_build"),
+                install_dir: Some(c_lib_install_dir.clone()),
+                num_jobs: env::var("NUM_JOBS").ok().and_then(|x| x.parse().ok()),
+                cmake_vars,
+                build_type: match profile.as_str() {
+                    "debug" => BuildType::Debug,
+                    "release" => BuildType::Release,
+                    _ => bail!("unknown value of PROFILE env var: {}", profile),
+                },
+                capture_output: false,
+                skip_cmake: false,
+                skip_cmake_after_first_run: false,
+            }
+            .run()?;
+        } else {
+            info!("C++ wrapper library is not needed");
         }
-        .run()?;
         {
             info!("Generating ffi.rs file");
             let mut ffi_file = create_file(out_dir.join("ffi.rs"))?;
-            if cpp_build_config_data.library_type() == Some(CppLibraryType::Shared) {
-                writeln!(
-                    ffi_file,
-                    "#[link(name = \"{}\")]",
-                    &self.build_script_data.cpp_wrapper_lib_name
-                )?;
-            } else {
-                writeln!(
-                    ffi_file,
-                    "#[link(name = \"{}\", kind = \"static\")]",
-                    &self.build_script_data.cpp_wrapper_lib_name
-                )?;
+            // without the wrapper library, FFI functions are provided by the linked C library
+            if has_wrapper_library {
+                if cpp_build_config_data.library_type() == Some(CppLibraryType::Shared) {
+                    writeln!(
+                        ffi_file,
+                        "#[link(name = \"{}\")]",
+                        &self.build_script_data.cpp_wrapper_lib_name
+                    )?;
+                } else {
+                    writeln!(
+                        ffi_file,
+                        "#[link(name = \"{}\", kind = \"static\")]",
+                        &self.build_script_data.cpp_wrapper_lib_name
+                    )?;
+                }
             }
             write!(
                 ffi_file,
@@ -199,10 +207,12 @@ impl Config {
         }
 
         // TODO: make it configurable
-        if crate::common::target::current_os() == OS::MacOS {
-            println!("cargo:rustc-link-lib=c++");
-        } else if crate::common::target::current_env() != crate::common::target::Env::Msvc {
-            println!("cargo:rustc-link-lib=stdc++");
+        if has_wrapper_library {
+            if crate::common::target::current_os() == OS::MacOS {
+                println!("cargo:rustc-link-lib=c++");
+            } else if crate::common::target::current_env() != crate::common::target::Env::Msvc {
+                println!("cargo:rustc-link-lib=stdc++");
+            }
         }
 
         for name in cpp_build_config_data.linked_frameworks() {
@@ -214,10 +224,12 @@ impl Config {
         for path in self.cpp_build_paths.framework_paths() {
             println!("cargo:rustc-link-search=framework={}", path_to_str(path)?);
         }
-        println!(
-            "cargo:rustc-link-search=native={}",
-            path_to_str(&c_lib_install_dir)?
-        );
+        if has_wrapper_library {
+            println!(
+                "cargo:rustc-link-search=native={}",
+                path_to_str(&c_lib_install_dir)?
+            );
+        }
 
         if let Some(version) = self.current_cpp_library_version {
             println!("cargo:rustc-cfg=cpp_lib_version={:?}", version);
@@ -201,11 +201,13 @@ pub fn load_json<P: AsRef<Path>, T: serde::de::DeserializeOwned>(path: P) -> Res
         .with_context(|_| format!("failed to parse file as JSON: {}", path.as_ref().display()))?)
 }
 
-/// Serialize `value` into JSON file `path`.
-pub fn save_json<P: AsRef<Path>, T: ::serde::Serialize>(
+/// Writes file `path` using `write`. The content is written to a temporary file first,
+/// so `path` is not corrupted if writing fails. If `backup_path` is specified,
+/// the previous version of the file is moved to `backup_path`.
+pub fn write_file_with_backup<P: AsRef<Path>>(
     path: P,
-    value: &T,
     backup_path: Option<&Path>,
+    write: impl FnOnce(&mut BufWriter<fs::File>) -> Result<()>,
 ) -> Result<()> {
     let tmp_path = {
         let mut buf = path.as_ref().to_path_buf();
@@ -214,13 +216,10 @@ pub fn save_json<P: AsRef<Path>, T: ::serde::Serialize>(
         buf
     };
     {
-        let file = create_file(&tmp_path)?;
-        ::serde_json::to_writer(&mut file.into_inner(), value).with_context(|_| {
-            format!(
-                "failed to serialize to JSON file: {}",
-                path.as_ref().display()
-            )
-        })?;
+        let mut file = create_file(&tmp_path)?.into_inner();
+        write(&mut file)?;
+        file.flush()
+            .with_context(|_| format!("failed to write file: {}", tmp_path.display()))?;
     }
     if path.as_ref().exists() {
         if let Some(backup_path) = backup_path {
@@ -233,6 +232,23 @@ pub fn save_json<P: AsRef<Path>, T: ::serde::Serialize>(
     Ok(())
 }
 
+/// Serialize `value` into JSON file `path`.
+pub fn save_json<P: AsRef<Path>, T: ::serde::Serialize>(
+    path: P,
+    value: &T,
+    backup_path: Option<&Path>,
+) -> Result<()> {
+    write_file_with_backup(path.as_ref(), backup_path, |file| {
+        ::serde_json::to_writer(file, value).with_context(|_| {
+            format!(
+                "failed to serialize to JSON file: {}",
+                path.as_ref().display()
+            )
+        })?;
+        Ok(())
+    })
+}
+
 /// Deserialize value from binary file `path`.
 pub fn load_bincode<P: AsRef<Path>, T: serde::de::DeserializeOwned>(path: P) -> Result<T> {
     let mut file = open_file(path.as_ref())?.into_inner();
@@ -29,10 +29,20 @@ pub struct BuildScriptData {
     pub cpp_build_config: cpp_build_config::CppBuildConfig,
     /// Name of C++ wrapper library
     pub cpp_wrapper_lib_name: String,
+    /// False if the crate doesn't need the C++ wrapper library
+    /// because all FFI functions are called directly and there are no types
+    /// with sizes determined by the wrapper library
+    #[serde(default = "default_has_wrapper_library")]
+    pub has_wrapper_library: bool,
     /// Environments the generator was used in
     pub known_targets: Vec<LibraryTarget>,
 }
 
+/// Crates generated before `has_wrapper_library` was added always have the wrapper library.
+fn default_has_wrapper_library() -> bool {
+    true
+}
+
 #[derive(Debug)]
 pub struct ReadOnly<T>(T);
 
@@ -0,0 +1,19 @@
+use crate::cpp_build_config::CppBuildConfig;
+use crate::BuildScriptData;
+
+#[test]
+fn has_wrapper_library_defaults_to_true() {
+    let data = BuildScriptData {
+        cpp_build_config: CppBuildConfig::default(),
+        cpp_wrapper_lib_name: "lib1_c".to_string(),
+        has_wrapper_library: false,
+        known_targets: Vec::new(),
+    };
+    let mut value = serde_json::to_value(&data).unwrap();
+    assert_eq!(value["has_wrapper_library"], false);
+    // build_script_data.json of older crates doesn't contain the field
+    value.as_object_mut().unwrap().remove("has_wrapper_library");
+    let data: BuildScriptData = serde_json::from_value(value).unwrap();
+    assert!(data.has_wrapper_library);
+    assert_eq!(data.cpp_wrapper_lib_name, "lib1_c");
+}
@@ -1 +1,2 @@
+mod build_script_data;
 mod utils;